@@ -1,9 +1,11 @@
+use std::collections::HashSet;
+
 use crate::{
-    data::data_store::DataTaskStore,
+    data::data_store::{DataTaskStore, TaskID, TaskIDRef},
     utils::task_position::{cursor_to_task, task_to_cursor},
 };
 
-use chrono::{Local, NaiveDate};
+use chrono::{Local, NaiveDate, NaiveDateTime};
 use crossterm::event::KeyEvent;
 use itertools::Itertools;
 use tui::style::{Color, Stylize};
@@ -13,27 +15,52 @@ use crate::{
     component::{
         message_box::MessageBoxBuilder,
         overlay::{
+            comments::CommentsBox,
             dialog::{DialogAction, DialogBoxBuilder},
             fuzzy::FuzzyBoxBuilder,
             input_box::InputBoxBuilder,
+            pomodoro::PomodoroBox,
             vim::VimMode,
         },
     },
-    error::AppError,
+    error::{AppError, StoreError},
     framework::event::PostEvent,
     input,
+    screens::stats_screen::StatsScreen,
     task::{FindParentResult, Task},
     utils::{self, str_to_colour},
 };
 
+pub(crate) fn error_message(err: AppError) -> PostEvent {
+    let message = MessageBoxBuilder::default()
+        .title("An error occured")
+        .message(err.message())
+        .colour(Color::Red)
+        .build();
+    PostEvent::push_layer(message)
+}
+
+/// Shows the error if the change could not be made.
+pub(crate) fn noop_or_error<T, E: Into<AppError>>(result: Result<T, E>) -> PostEvent {
+    match result {
+        Ok(_) => PostEvent::noop(false),
+        Err(err) => error_message(err.into()),
+    }
+}
+
 // Universal functions
 impl App {
     pub fn create_add_task_menu(&mut self) -> Result<PostEvent, AppError> {
         let add_input_dialog = InputBoxBuilder::default()
             .title("Add a task")
             .on_submit(move |app, word| {
-                app.task_store
-                    .add_task(Task::from_string(word.trim()), None);
+                let task = match Task::from_quick_add(word.trim()) {
+                    Ok(task) => task,
+                    Err(err) => return error_message(err),
+                };
+                if let Err(err) = app.task_store.add_task(task, None) {
+                    return error_message(err.into());
+                }
                 if app.mode == Mode::CurrentTasks {
                     app.task_list.selected_index = app.task_store.find_tasks_draw_size() - 1;
                 }
@@ -54,6 +81,13 @@ impl App {
         Ok(PostEvent::noop(false))
     }
 
+    pub fn go_to_stats(&mut self) -> Result<PostEvent, AppError> {
+        if self.mode == Mode::Stats {
+            return Ok(PostEvent::noop(false));
+        }
+        Ok(PostEvent::push_layer(StatsScreen::new()))
+    }
+
     pub fn sort(&mut self) -> Result<PostEvent, AppError> {
         self.task_store.sort();
         Ok(PostEvent::noop(false))
@@ -107,12 +141,12 @@ impl App {
 
                         match result {
                             Ok(result) => result,
-                            Err(AppError::InvalidState(msg)) => {
+                            Err(err) => {
                                 let prev_mode = app.mode; // FIXME: why is this here???
                                 app.mode = Mode::Overlay;
                                 let message = MessageBoxBuilder::default()
                                     .title("An error occured")
-                                    .message(msg)
+                                    .message(err.message())
                                     .on_close(move |app| {
                                         app.mode = prev_mode;
                                         PostEvent::noop(false)
@@ -120,7 +154,6 @@ impl App {
                                     .colour(Color::Red);
                                 PostEvent::push_layer(message.build())
                             }
-                            _ => PostEvent::noop(false),
                         }
                     },
                 )
@@ -131,21 +164,36 @@ impl App {
     }
 
     pub fn create_delete_selected_task_menu(&mut self) -> Result<PostEvent, AppError> {
-        if self.task_store.root_tasks().is_empty() {
+        let selection = self.task_list.selection(&self.task_store);
+        if selection.is_empty() {
             return Ok(PostEvent::noop(false));
         }
+        let title = if selection.len() == 1 {
+            String::from("Delete selected task")
+        } else {
+            format!("Delete the {} selected tasks", selection.len())
+        };
         let delete_dialog = DialogBoxBuilder::default()
-            .title("Delete selected task")
+            .title(title)
             .add_option("Delete", move |app| {
-                let selected_index = &mut app.task_list.selected_index;
-                if let Some(task_to_delete) = cursor_to_task(&app.task_store, *selected_index) {
-                    app.task_store.delete_task(&task_to_delete);
+                if let Err(err) = app.check_selection(&selection) {
+                    return error_message(err.into());
                 }
+                for task_id in &selection {
+                    // Deleted along with a parent that was selected before it
+                    if app.task_store.task(task_id).is_none() {
+                        continue;
+                    }
+                    if let Err(err) = app.task_store.delete_task(task_id) {
+                        return error_message(err.into());
+                    }
+                }
+                app.task_list.clear_selection();
 
-                if *selected_index == app.task_store.find_tasks_draw_size()
-                    && !app.task_store.root_tasks().is_empty()
-                {
-                    *selected_index -= 1;
+                let draw_size = app.task_store.find_tasks_draw_size();
+                let selected_index = &mut app.task_list.selected_index;
+                if *selected_index >= draw_size && draw_size != 0 {
+                    *selected_index = draw_size - 1;
                 }
                 PostEvent::noop(false)
             })
@@ -158,17 +206,73 @@ impl App {
         if self.task_store.root_tasks().is_empty() {
             return Ok(PostEvent::noop(true));
         }
+        let time_completed = Local::now().naive_local();
+        let selection = self.task_list.selection(&self.task_store);
+        self.check_selection(&selection)?;
+        for task_id in selection {
+            // Completed along with a parent that was selected before it
+            if self.task_store.task(&task_id).is_none() {
+                continue;
+            }
+            self.complete_task_and_parents(task_id, time_completed)?;
+        }
+        self.task_list.clear_selection();
+
+        let draw_size = self.task_store.find_tasks_draw_size();
         let selected_index = &mut self.task_list.selected_index;
-        let local = Local::now();
-        let time_completed = local.naive_local();
-        if let Some(completed_task) = cursor_to_task(&self.task_store, *selected_index) {
+        if *selected_index >= draw_size && draw_size != 0 {
+            *selected_index = draw_size - 1;
+        }
+        Ok(PostEvent::noop(false))
+    }
+
+    /// Completes the task, along with its parents once they have no open
+    /// subtasks left if `auto_complete_parent` is on.
+    fn complete_task_and_parents(
+        &mut self,
+        task_id: TaskID,
+        time_completed: NaiveDateTime,
+    ) -> Result<(), AppError> {
+        let mut next = Some(task_id);
+        while let Some(task_id) = next {
+            let parent_id = self
+                .task_store
+                .find_parent(&task_id)
+                .and_then(|f| f.parent_id);
             self.task_store
-                .complete_task(&completed_task, time_completed);
-            if *selected_index == self.task_store.find_tasks_draw_size()
-                && !self.task_store.root_tasks().is_empty()
-            {
-                *selected_index -= 1;
-            }
+                .modify_task(&task_id, |task| task.stop_timer(time_completed))?;
+            self.task_store.complete_task(&task_id, time_completed)?;
+
+            // Only continue up the tree if the parent has no open subtasks left.
+            next = parent_id.filter(|parent_id| {
+                self.config.auto_complete_parent
+                    && self
+                        .task_store
+                        .subtasks(parent_id)
+                        .is_none_or(|subtasks| subtasks.is_empty())
+            });
+        }
+        Ok(())
+    }
+
+    /// Starts the timer on the selected task, stopping any other running timer.
+    /// If the selected task's timer is already running, it is stopped instead.
+    pub fn toggle_timer(&mut self) -> Result<PostEvent, AppError> {
+        let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
+            return Ok(PostEvent::noop(true));
+        };
+        let now = Local::now().naive_local();
+        let running = self.running_timer();
+        if let Some(running) = &running {
+            self.task_store
+                .modify_task(running, |task| task.stop_timer(now))?;
+        }
+        if running.as_ref() != Some(&task_id) {
+            self.task_store
+                .modify_task(&task_id, |task| task.start_timer(now))?;
+            self.set_running_timer(Some(task_id));
+        } else {
+            self.set_running_timer(None);
         }
         Ok(PostEvent::noop(false))
     }
@@ -176,51 +280,43 @@ impl App {
     pub fn create_tag_menu(&mut self) -> Result<PostEvent, AppError> {
         let mut tag_options: Vec<DialogAction> = Vec::new();
 
-        let selected_index = self.task_list.selected_index;
-        let Some(task_id) = cursor_to_task(&self.task_store, selected_index) else {
+        let selection = self.task_list.selection(&self.task_store);
+        if selection.is_empty() {
             // FIXME: should probs error?
             return Ok(PostEvent::noop(true));
-        };
+        }
 
         if !self.task_store.root_tasks().is_empty() && self.mode == Mode::CurrentTasks {
             // Loops through the tags and adds them to the menu.
             for (i, tag) in self.task_store.tags().iter() {
                 let moved = i.to_string();
-                let moved_name = task_id.clone();
+                let selection = selection.clone();
                 tag_options.push(DialogAction::new(
                     tag.name.to_owned().fg(tag.colour),
-                    move |app| {
-                        app.task_store.modify_task(&moved_name, |task: &mut Task| {
-                            task.flip_tag(moved);
-                        });
-                        PostEvent::noop(false)
-                    },
+                    move |app| noop_or_error(app.toggle_tag(&selection, &moved)),
                 ));
             }
         }
 
-        let new_task = task_id.clone();
+        let new_tasks = selection.clone();
         tag_options.push(DialogAction::new(String::from("New tag"), move |app| {
+            let new_tasks = new_tasks.clone();
             let tag_menu = InputBoxBuilder::default()
                 .title("Tag name")
                 .on_submit(move |app, tag_name| {
-                    let new_task = new_task.clone();
+                    let new_tasks = new_tasks.clone();
                     app.create_select_tag_colour("".to_string(), move |app, tag_colour| {
                         let colour = str_to_colour(&tag_colour)?;
 
                         let tag_id = app.task_store.tags().keys().len().to_string();
-                        app.task_store.tags_mut().insert(
-                            tag_id.clone(),
+                        app.task_store.set_tag(
+                            &tag_id,
                             crate::task::Tag {
                                 name: tag_name.clone(),
                                 colour,
                             },
                         );
-                        if app.task_store.find_tasks_draw_size() > selected_index {
-                            app.task_store.modify_task(&new_task, |task: &mut Task| {
-                                task.flip_tag(tag_id);
-                            });
-                        }
+                        app.toggle_tag(&new_tasks, &tag_id)?;
                         Ok(PostEvent::noop(false))
                     })
                 })
@@ -229,14 +325,21 @@ impl App {
             PostEvent::push_layer(tag_menu)
         }));
 
-        let new_task = task_id.clone();
         if !self.task_store.root_tasks().is_empty() && self.mode == Mode::CurrentTasks {
+            let selection = selection.clone();
             tag_options.push(DialogAction::new(
                 String::from("Clear all tags"),
                 move |app| {
-                    app.task_store.modify_task(&new_task, |task: &mut Task| {
-                        task.tags.clear();
-                    });
+                    if let Err(err) = app.check_selection(&selection) {
+                        return error_message(err.into());
+                    }
+                    for task_id in &selection {
+                        if let Err(err) = app.task_store.modify_task(task_id, |task: &mut Task| {
+                            task.tags.clear();
+                        }) {
+                            return error_message(err.into());
+                        }
+                    }
                     PostEvent::noop(false)
                 },
             ));
@@ -256,6 +359,53 @@ impl App {
         Ok(self.create_dialog_or_fuzzy("Add or remove a tag", tag_options))
     }
 
+    /// Removes the tag from the tasks if they all have it, otherwise adds it
+    /// to the tasks without it.
+    fn toggle_tag(&mut self, task_ids: &[TaskID], tag_id: TaskIDRef) -> Result<(), StoreError> {
+        let all_tagged = task_ids.iter().all(|task_id| {
+            self.task_store
+                .task(task_id)
+                .is_some_and(|task| task.tags.iter().any(|f| f == tag_id))
+        });
+        self.check_selection(task_ids)?;
+        for task_id in task_ids {
+            self.task_store.modify_task(task_id, |task| {
+                if task.tags.iter().any(|f| f == tag_id) == all_tagged {
+                    task.flip_tag(tag_id.to_string());
+                }
+            })?;
+        }
+        Ok(())
+    }
+
+    /// Only shows the completed tasks with the chosen tag.
+    pub fn create_completed_tag_filter(&mut self) -> Result<PostEvent, AppError> {
+        let mut tag_options: Vec<DialogAction> = self
+            .task_store
+            .tags()
+            .iter()
+            .sorted_by_key(|(_, tag)| tag.name.to_lowercase())
+            .map(|(tag_id, tag)| {
+                let tag_id = tag_id.to_string();
+                DialogAction::new(tag.name.to_owned().fg(tag.colour), move |app| {
+                    app.completed_list.tag_filter = Some(tag_id);
+                    app.completed_list.selected_index = 0;
+                    PostEvent::noop(false)
+                })
+            })
+            .collect();
+        tag_options.push(DialogAction::new(String::from("All tags"), |app| {
+            app.completed_list.tag_filter = None;
+            app.completed_list.selected_index = 0;
+            PostEvent::noop(false)
+        }));
+        tag_options.push(DialogAction::new(String::from("Cancel"), |_| {
+            PostEvent::noop(false)
+        }));
+
+        Ok(self.create_dialog_or_fuzzy("Filter by tag", tag_options))
+    }
+
     pub fn create_edit_tag_menu(&mut self) -> PostEvent {
         let mut tag_options: Vec<DialogAction> = Vec::new();
 
@@ -276,8 +426,8 @@ impl App {
                                 tag_colour.to_string(),
                                 move |app, tag_colour| {
                                     let colour = utils::str_to_colour(&tag_colour)?;
-                                    app.task_store.tags_mut().insert(
-                                        tag_id.to_string(),
+                                    app.task_store.set_tag(
+                                        &tag_id,
                                         crate::task::Tag {
                                             name: tag_name.clone(),
                                             colour,
@@ -363,34 +513,59 @@ impl App {
         self.create_dialog_or_fuzzy("Delete a tag", tag_options)
     }
 
+    /// Gives the selected tasks the priority after the first one's, so they
+    /// all end up with the same priority.
     pub fn cycle_priority(&mut self) -> Result<PostEvent, AppError> {
         if self.task_store.root_tasks().is_empty() {
             return Ok(PostEvent::noop(true));
         }
 
-        let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
+        let cursor_task = cursor_to_task(&self.task_store, self.task_list.selected_index);
+        self.task_list.mark_visual_range(&self.task_store);
+        let selection = self.task_list.selection(&self.task_store);
+        let Some(priority) = selection
+            .first()
+            .and_then(|task_id| self.task_store.task(task_id))
+            .map(|task| task.priority.next_priority())
+        else {
             return Ok(PostEvent::noop(true));
         };
 
-        self.task_store.modify_task(&task_id, |task| {
-            task.priority = task.priority.next_priority();
-        });
+        self.check_selection(&selection)?;
+        for task_id in &selection {
+            self.task_store.modify_task(task_id, |task| {
+                task.priority = priority;
+            })?;
+        }
 
         if self.task_list.auto_sort {
             self.task_store.sort();
-            let Some(new_pos) = task_to_cursor(&self.task_store, &task_id) else {
+            let Some(new_pos) = cursor_task.and_then(|f| task_to_cursor(&self.task_store, &f))
+            else {
                 return Ok(PostEvent::noop(false));
             };
             self.task_list.selected_index = new_pos;
         }
-        self.task_store.update_task(&task_id);
         Ok(PostEvent::noop(false))
     }
 
+    pub fn create_comments_menu(&mut self) -> Result<PostEvent, AppError> {
+        let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
+            return Ok(PostEvent::noop(true));
+        };
+        Ok(PostEvent::push_layer(CommentsBox::new(task_id)))
+    }
+
+    pub fn create_pomodoro(&mut self) -> Result<PostEvent, AppError> {
+        let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
+            return Ok(PostEvent::noop(true));
+        };
+        Ok(PostEvent::push_layer(PomodoroBox::new(self, task_id)))
+    }
+
     pub fn create_add_subtask_menu(&mut self) -> Result<PostEvent, AppError> {
         let index = self.task_list.selected_index;
         let Some(task_id) = cursor_to_task(&self.task_store, index) else {
-            // FIXME: panic!
             return Ok(PostEvent::noop(true));
         };
         let Some(task) = self.task_store.task(&task_id) else {
@@ -400,12 +575,18 @@ impl App {
             .title(format!("Add a subtask to {}", task.title))
             .use_vim(&self.config, VimMode::Insert)
             .on_submit(move |app, word| {
-                app.task_store
-                    .add_task(Task::from_string(word.trim()), Some(&task_id));
-                app.task_store.modify_task(&task_id, |task| {
-                    task.opened = true;
+                let task = match Task::from_quick_add(word.trim()) {
+                    Ok(task) => task,
+                    Err(err) => return error_message(err),
+                };
+                let added = app.task_store.add_task(task, Some(&task_id)).and_then(|_| {
+                    app.task_store.modify_task(&task_id, |task| {
+                        task.opened = true;
+                    })
                 });
-                app.task_store.update_task(&task_id);
+                if let Err(err) = added {
+                    return error_message(err.into());
+                }
                 app.task_list.selected_index +=
                     app.task_store.subtasks(&task_id).map_or(0, |f| f.len());
                 PostEvent::noop(false)
@@ -414,43 +595,55 @@ impl App {
     }
 
     pub fn move_selected_task_down(&mut self) -> Result<PostEvent, AppError> {
+        if self.task_list.is_selecting() {
+            return self.move_selection(true);
+        }
         let autosort = self.task_list.auto_sort;
 
         let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
-            todo!()
+            return Ok(PostEvent::noop(true));
         };
 
         let Some(FindParentResult {
-            parent_id: parent_index,
+            parent_id,
             task_local_offset: local_index,
         }) = self.task_store.find_parent(&task_id)
         else {
             return Ok(PostEvent::noop(true));
         };
 
-        let parent_subtasks = if let Some(parent) = &parent_index {
-            self.task_store.subtasks(parent).unwrap()
+        let parent_subtasks = if let Some(parent) = &parent_id {
+            self.task_store
+                .subtasks(parent)
+                .ok_or_else(|| AppError::invalid_state("Subtasks are not found in parent"))?
         } else {
             self.task_store.root_tasks()
         };
 
         let new_index = (local_index + 1) % parent_subtasks.len();
 
-        let task = &parent_subtasks[local_index];
-        let task_above = &parent_subtasks[new_index];
+        let task = self
+            .task_store
+            .task(&parent_subtasks[local_index])
+            .ok_or_else(|| AppError::invalid_state("Subtasks are not found in parent"))?;
+        let task_below = self
+            .task_store
+            .task(&parent_subtasks[new_index])
+            .ok_or_else(|| AppError::invalid_state("Subtasks are not found in parent"))?;
 
-        if self.task_store.task(task).unwrap().priority
-            == self.task_store.task(task_above).unwrap().priority
-            || !autosort
-        {
-            self.task_store.move_task(&task_id, None, new_index, None);
-            self.task_list.selected_index = task_to_cursor(&self.task_store, &task_id).unwrap();
+        if task.priority == task_below.priority || !autosort {
+            self.task_store.move_task(&task_id, None, new_index, None)?;
+            self.task_list.selected_index = task_to_cursor(&self.task_store, &task_id)
+                .ok_or_else(|| AppError::invalid_state("Did not find global position of task"))?;
         }
 
         Ok(PostEvent::noop(false))
     }
 
     pub fn move_selected_task_up(&mut self) -> Result<PostEvent, AppError> {
+        if self.task_list.is_selecting() {
+            return self.move_selection(false);
+        }
         let autosort = self.task_list.auto_sort;
 
         let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
@@ -486,7 +679,7 @@ impl App {
             .ok_or_else(|| AppError::invalid_state("Subtasks are not found in parent"))?;
 
         if task.priority == task_above.priority || !autosort {
-            self.task_store.move_task(&task_id, None, new_index, None);
+            self.task_store.move_task(&task_id, None, new_index, None)?;
             self.task_list.selected_index = task_to_cursor(&self.task_store, &task_id)
                 .ok_or_else(|| AppError::invalid_state("Did not find global position of task"))?;
         }
@@ -498,7 +691,6 @@ impl App {
         let index = self.task_list.selected_index;
 
         let Some(task_id) = cursor_to_task(&self.task_store, index) else {
-            // FIXME: panic!
             return Ok(PostEvent::noop(true));
         };
         let Some(task) = self.task_store.task(&task_id) else {
@@ -509,11 +701,9 @@ impl App {
             .use_vim(&self.config, VimMode::Normal)
             .fill(task.title.as_str())
             .on_submit(move |app, word| {
-                app.task_store.modify_task(&task_id, move |task| {
+                noop_or_error(app.task_store.modify_task(&task_id, move |task| {
                     task.title = word.trim().to_string();
-                });
-                app.task_store.update_task(&task_id);
-                PostEvent::noop(false)
+                }))
             })
             .use_vim(&self.config, VimMode::Normal)
             .build();
@@ -525,13 +715,11 @@ impl App {
             return Ok(PostEvent::noop(true));
         }
         let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
-            // FIXME: panic!
             return Ok(PostEvent::noop(true));
         };
         self.task_store.modify_task(&task_id, |task| {
             task.progress = !task.progress;
-        });
-        self.task_store.update_task(&task_id);
+        })?;
         Ok(PostEvent::noop(false))
     }
 
@@ -541,33 +729,78 @@ impl App {
             return Ok(PostEvent::noop(true));
         }
         let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
-            // FIXME: panic!
             return Ok(PostEvent::noop(true));
         };
         self.task_store.modify_task(&task_id, |task| {
             task.opened = !task.opened;
-        });
-        self.task_store.update_task(&task_id);
+        })?;
         Ok(PostEvent::noop(false))
     }
 
     pub fn move_subtask_level_up(&mut self) -> Result<PostEvent, AppError> {
-        let selected_index = &mut self.task_list.selected_index;
+        let Some(cursor_task) = cursor_to_task(&self.task_store, self.task_list.selected_index)
+        else {
+            return Ok(PostEvent::noop(true));
+        };
 
-        let Some(task_id) = cursor_to_task(&self.task_store, *selected_index) else {
-            // FIXME: panic!
+        self.task_list.mark_visual_range(&self.task_store);
+        let task_ids = self.selected_top_level();
+        let mut new_parents = Vec::new();
+        let moved = self.change_each(&task_ids, |app, task_id| {
+            let new_parent = app.indent_task(task_id)?;
+            let moved = new_parent.is_some();
+            new_parents.extend(new_parent);
+            Ok(moved)
+        })?;
+        if !moved {
             return Ok(PostEvent::noop(true));
+        }
+        // Opened once every task is moved, so nothing is left to undo
+        for parent_id in new_parents {
+            self.task_store
+                .modify_task(&parent_id, |parent| parent.opened = true)?;
+        }
+
+        self.follow_task(&cursor_task);
+        Ok(PostEvent::noop(false))
+    }
+
+    pub fn move_subtask_level_down(&mut self) -> Result<PostEvent, AppError> {
+        let Some(cursor_task) = cursor_to_task(&self.task_store, self.task_list.selected_index)
+        else {
+            return Err(AppError::invalid_state("Task does not exist"));
         };
 
+        let selecting = self.task_list.is_selecting();
+        self.task_list.mark_visual_range(&self.task_store);
+        // From the bottom, so the tasks keep their order after the parent
+        let task_ids = self.selected_top_level().into_iter().rev().collect_vec();
+        if !self.change_each(&task_ids, |app, task_id| app.outdent_task(task_id))? {
+            return Ok(PostEvent::noop(true));
+        }
+
+        if selecting || self.task_list.auto_sort {
+            self.follow_task(&cursor_task);
+        }
+        Ok(PostEvent::noop(false))
+    }
+
+    /// Makes the task the last subtask of the task above it, returns the task
+    /// it was moved into if there was a task above it.
+    fn indent_task(&mut self, task_id: TaskIDRef) -> Result<Option<TaskID>, AppError> {
         // FIXME: this should also probs return subtasks?
         let Some(FindParentResult {
             parent_id,
             task_local_offset: local_index,
-        }) = self.task_store.find_parent(&task_id)
+        }) = self.task_store.find_parent(task_id)
         else {
-            return Ok(PostEvent::noop(true));
+            return Ok(None);
         };
 
+        if local_index == 0 {
+            return Ok(None);
+        }
+
         // FIXME: should be refactored into a singular subtasks thing.
         let subtasks = if let Some(parent_id) = parent_id {
             self.task_store
@@ -577,113 +810,240 @@ impl App {
             self.task_store.root_tasks()
         };
 
-        if subtasks.is_empty() {
-            return Ok(PostEvent::noop(true));
-        }
-
-        if local_index == 0 {
-            return Ok(PostEvent::noop(true));
-        }
-
-        let prev_local_index = local_index - 1;
-        let prev_task_id = subtasks[prev_local_index].to_string();
+        let prev_task_id = subtasks[local_index - 1].to_string();
         let order = self
             .task_store
             .subtasks(&prev_task_id)
             .map_or(0, |sub| sub.len());
 
         self.task_store
-            .move_task(&task_id, Some(prev_task_id.to_string()), order, None);
-
-        let result = self.task_store.modify_task(&prev_task_id, |prev_task| {
-            if !prev_task.opened {
-                prev_task.opened = true;
-                // Have to remove the task when adding
-                true
-            } else {
-                false
-            }
-        });
-
-        if let Some(f) = result {
-            if f {
-                *selected_index += self.task_store.find_task_draw_size(&prev_task_id)
-                    - self.task_store.find_task_draw_size(&task_id)
-                    - 1;
-            }
-        }
-        self.task_store.update_task(&prev_task_id);
-
-        if self.task_list.auto_sort {
-            self.task_store.sort();
-            if let Some(task_pos) = task_to_cursor(&self.task_store, &task_id) {
-                *selected_index = task_pos;
-            }
-        }
-        Ok(PostEvent::noop(false))
+            .move_task(task_id, Some(prev_task_id.to_string()), order, None)?;
+        Ok(Some(prev_task_id))
     }
 
-    pub fn move_subtask_level_down(&mut self) -> Result<PostEvent, AppError> {
-        let selected_index = &mut self.task_list.selected_index;
-        let Some(task_id) = cursor_to_task(&self.task_store, *selected_index) else {
-            return Err(AppError::invalid_state("Task does not exist"));
-        };
-        let Some(FindParentResult { parent_id, .. }) = self.task_store.find_parent(&task_id) else {
-            return Ok(PostEvent::noop(true));
-        };
-
-        let Some(parent_id) = parent_id else {
-            return Ok(PostEvent::noop(true));
+    /// Moves the task out of its parent to just below it, returns whether the
+    /// task had a parent.
+    fn outdent_task(&mut self, task_id: TaskIDRef) -> Result<bool, AppError> {
+        let Some(FindParentResult {
+            parent_id: Some(parent_id),
+            ..
+        }) = self.task_store.find_parent(task_id)
+        else {
+            return Ok(false);
         };
 
-        // let Some(task) = self.task_store.delete_task(*selected_index) else {
-        //     return Ok(PostEvent::noop(true));
-        // };
-
         let Some(FindParentResult {
             parent_id: grand_parent_id,
             task_local_offset: parent_local_index,
-            ..
         }) = self.task_store.find_parent(&parent_id)
         else {
-            return Ok(PostEvent::noop(true));
+            return Ok(false);
         };
 
         if let Some(grand_parent_id) = grand_parent_id {
             self.task_store.move_task(
-                &task_id,
+                task_id,
                 Some(grand_parent_id),
                 parent_local_index + 1,
                 None,
-            );
+            )?;
         } else {
             self.task_store
-                .move_task(&task_id, None, parent_local_index + 1, Some(()));
+                .move_task(task_id, None, parent_local_index + 1, Some(()))?;
+        }
+        Ok(true)
+    }
+
+    /// Moves each selected task one place within its parent. A task stays
+    /// where it is when it is already at the end or the task it would pass
+    /// could not move either.
+    fn move_selection(&mut self, down: bool) -> Result<PostEvent, AppError> {
+        let cursor_task = cursor_to_task(&self.task_store, self.task_list.selected_index);
+        self.task_list.mark_visual_range(&self.task_store);
+        let mut task_ids = self.selected_top_level();
+        if down {
+            task_ids.reverse();
         }
 
+        let mut blocked = HashSet::new();
+        self.change_each(&task_ids, |app, task_id| {
+            let Some(FindParentResult {
+                parent_id,
+                task_local_offset: local_index,
+            }) = app.task_store.find_parent(task_id)
+            else {
+                return Ok(false);
+            };
+            let siblings = match &parent_id {
+                Some(parent_id) => app.task_store.subtasks(parent_id),
+                None => Some(app.task_store.root_tasks()),
+            };
+            let new_index = if down {
+                Some(local_index + 1)
+            } else {
+                local_index.checked_sub(1)
+            };
+            let passed = new_index
+                .and_then(|index| siblings?.get(index))
+                .filter(|passed| !blocked.contains(*passed))
+                .filter(|passed| {
+                    !app.task_list.auto_sort
+                        || app.task_store.task(passed).map(|f| f.priority)
+                            == app.task_store.task(task_id).map(|f| f.priority)
+                });
+            match (passed, new_index) {
+                (Some(_), Some(new_index)) => {
+                    app.task_store.move_task(task_id, None, new_index, None)?;
+                    Ok(true)
+                }
+                _ => {
+                    blocked.insert(task_id.clone());
+                    Ok(false)
+                }
+            }
+        })?;
+
+        if let Some(cursor_task) = cursor_task {
+            self.follow_task(&cursor_task);
+        }
+        Ok(PostEvent::noop(false))
+    }
+
+    /// Checks that every task can still be found, so a change to a selection
+    /// is made to all of the tasks or to none of them.
+    fn check_selection(&self, task_ids: &[TaskID]) -> Result<(), StoreError> {
+        match task_ids
+            .iter()
+            .find(|id| self.task_store.task(id).is_none())
+        {
+            Some(task_id) => Err(StoreError::NotFound(task_id.clone())),
+            None => Ok(()),
+        }
+    }
+
+    /// Changes each task in turn, returning whether any of them changed. When
+    /// a change fails the tasks are put back where they were, so a selection
+    /// is never left partly moved.
+    fn change_each(
+        &mut self,
+        task_ids: &[TaskID],
+        mut change: impl FnMut(&mut App, &TaskID) -> Result<bool, AppError>,
+    ) -> Result<bool, AppError> {
+        let mut positions = task_ids
+            .iter()
+            .filter_map(|task_id| Some((task_id.clone(), self.task_store.find_parent(task_id)?)))
+            .collect_vec();
+        let mut changed = false;
+        for task_id in task_ids {
+            match change(self, task_id) {
+                Ok(task_changed) => changed |= task_changed,
+                Err(err) => {
+                    // Earlier places are restored first, so the later places
+                    // are counted in lists that are already put back.
+                    positions.sort_by_key(|(_, position)| position.task_local_offset);
+                    for (task_id, position) in positions {
+                        let global = position.parent_id.is_none().then_some(());
+                        let _ = self.task_store.move_task(
+                            &task_id,
+                            position.parent_id,
+                            position.task_local_offset,
+                            global,
+                        );
+                    }
+                    return Err(err);
+                }
+            }
+        }
+        Ok(changed)
+    }
+
+    /// The selected tasks, leaving out the tasks whose parent is also
+    /// selected as they are moved along with it.
+    fn selected_top_level(&self) -> Vec<TaskID> {
+        let selection = self.task_list.selection(&self.task_store);
+        let selected = selection.iter().collect::<HashSet<_>>();
+        let parent_of = |task_id: &TaskID| {
+            self.task_store
+                .find_parent(task_id)
+                .and_then(|f| f.parent_id)
+        };
+        selection
+            .iter()
+            .filter(|task_id| {
+                !std::iter::successors(parent_of(task_id), parent_of)
+                    .any(|parent_id| selected.contains(&parent_id))
+            })
+            .cloned()
+            .collect()
+    }
+
+    /// Sorts the tasks if auto sort is on, and keeps the cursor on the task
+    /// after it was moved.
+    fn follow_task(&mut self, task_id: TaskIDRef) {
         if self.task_list.auto_sort {
             self.task_store.sort();
-            if let Some(task_pos) = task_to_cursor(&self.task_store, &task_id) {
-                *selected_index = task_pos;
-            }
+        }
+        if let Some(task_pos) = task_to_cursor(&self.task_store, task_id) {
+            self.task_list.selected_index = task_pos;
+        }
+    }
+
+    pub fn toggle_visual_mode(&mut self) -> Result<PostEvent, AppError> {
+        if self.task_list.visual_anchor.is_some() {
+            self.task_list.mark_visual_range(&self.task_store);
+        } else if !self.task_store.visible_rows().is_empty() {
+            self.task_list.visual_anchor = Some(self.task_list.selected_index);
         }
         Ok(PostEvent::noop(false))
     }
 
-    pub fn create_due_date_dialog(&mut self) -> Result<PostEvent, AppError> {
+    pub fn toggle_select_all(&mut self) -> Result<PostEvent, AppError> {
+        let rows = self
+            .task_store
+            .visible_rows()
+            .iter()
+            .map(|(task_id, _)| task_id.to_string())
+            .collect::<Vec<_>>();
+        if rows
+            .iter()
+            .all(|task_id| self.task_list.marked.contains(task_id))
+        {
+            self.task_list.clear_selection();
+        } else {
+            self.task_list.visual_anchor = None;
+            self.task_list.marked.extend(rows);
+        }
+        Ok(PostEvent::noop(false))
+    }
+
+    pub fn toggle_mark(&mut self) -> Result<PostEvent, AppError> {
         let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
-            // FIXME: panic!
             return Ok(PostEvent::noop(true));
         };
+        if !self.task_list.marked.remove(&task_id) {
+            self.task_list.marked.insert(task_id);
+        }
+        Ok(PostEvent::noop(false))
+    }
+
+    pub fn clear_selection(&mut self) -> Result<PostEvent, AppError> {
+        if !self.task_list.is_selecting() {
+            return Ok(PostEvent::noop(true));
+        }
+        self.task_list.clear_selection();
+        Ok(PostEvent::noop(false))
+    }
+
+    pub fn create_due_date_dialog(&mut self) -> Result<PostEvent, AppError> {
+        let selection = self.task_list.selection(&self.task_store);
+        if selection.is_empty() {
+            return Ok(PostEvent::noop(true));
+        }
         let date_dialog = InputBoxBuilder::default()
             .title("Add date or specify \"none\" to remove".to_string())
             .on_submit(move |app, date_str| {
                 if date_str.to_lowercase() == "none" {
-                    app.task_store.modify_task(&task_id, |task| {
-                        task.due_date = None;
-                    });
-                    app.task_store.update_task(&task_id);
-                    return PostEvent::noop(false);
+                    return noop_or_error(app.set_due_date(&selection, None));
                 }
                 let date = NaiveDate::parse_from_str(&date_str, "%d/%m/%y")
                     .or_else(|_| NaiveDate::parse_from_str(&date_str, "%d/%m/%Y"))
@@ -691,10 +1051,9 @@ impl App {
 
                 match date {
                     Ok(due) => {
-                        app.task_store.modify_task(&task_id, |task| {
-                            task.due_date = Some(due);
-                        });
-                        app.task_store.update_task(&task_id);
+                        if let Err(err) = app.set_due_date(&selection, Some(due)) {
+                            return error_message(err.into());
+                        }
                     }
                     Err(err) => {
                         let error_message = MessageBoxBuilder::default()
@@ -716,8 +1075,65 @@ impl App {
         Ok(PostEvent::push_layer(date_dialog))
     }
 
+    fn set_due_date(
+        &mut self,
+        task_ids: &[TaskID],
+        due_date: Option<NaiveDate>,
+    ) -> Result<(), StoreError> {
+        self.check_selection(task_ids)?;
+        for task_id in task_ids {
+            self.task_store.modify_task(task_id, |task| {
+                task.due_date = due_date;
+            })?;
+        }
+        Ok(())
+    }
+
+    pub fn create_estimate_dialog(&mut self) -> Result<PostEvent, AppError> {
+        let Some(task_id) = cursor_to_task(&self.task_store, self.task_list.selected_index) else {
+            return Ok(PostEvent::noop(true));
+        };
+        let Some(task) = self.task_store.task(&task_id) else {
+            return Ok(PostEvent::noop(true));
+        };
+        let estimate = task
+            .estimate
+            .map(utils::format_duration)
+            .unwrap_or_default();
+        let estimate_dialog = InputBoxBuilder::default()
+            .title("Estimate (eg: 1h30m) or specify \"none\" to remove".to_string())
+            .fill(&estimate.replace(' ', ""))
+            .on_submit(move |app, estimate_str| {
+                let estimate = if estimate_str.trim().to_lowercase() == "none" {
+                    None
+                } else {
+                    match utils::parse_duration(&estimate_str) {
+                        Ok(estimate) => Some(estimate),
+                        Err(err) => {
+                            let error_message = MessageBoxBuilder::default()
+                                .title("An error occured")
+                                .message(err.to_string())
+                                .colour(Color::Red)
+                                .on_close(|app| {
+                                    app.create_estimate_dialog()
+                                        .expect("Should always be ok...")
+                                })
+                                .build();
+                            return PostEvent::push_layer(error_message);
+                        }
+                    }
+                };
+                noop_or_error(app.task_store.modify_task(&task_id, |task| {
+                    task.estimate = estimate;
+                }))
+            })
+            .use_vim(&self.config, VimMode::Insert)
+            .build();
+        Ok(PostEvent::push_layer(estimate_dialog))
+    }
+
     pub fn refresh(&mut self) -> Result<PostEvent, AppError> {
-        self.task_store.refresh();
+        self.task_store.refresh()?;
         Ok(PostEvent::noop(false))
     }
 }
@@ -4,7 +4,10 @@ use crate::{
         status_line::StatusLine, task_list::TaskListContext,
     },
     config::Config,
-    data::data_store::{DataTaskStore, DataTaskStoreKind},
+    data::{
+        data_store::{DataTaskStore, DataTaskStoreKind, TaskID},
+        todoist::todoist_main::{handle_sync, TaskSync},
+    },
     error::AppError,
     framework::event::PostEvent,
 };
@@ -22,12 +25,15 @@ pub struct App {
 
     pub tick: usize,
 
+    /// The task the timer was last started on, so the tree is not searched on
+    /// every draw.
+    running_timer: Option<TaskID>,
     should_shutdown: bool,
 }
 
 impl App {
     pub fn new(theme: Config, task_data: DataTaskStoreKind) -> App {
-        App {
+        let mut app = App {
             config: theme,
             task_store: task_data,
             status_line: StatusLine::new(String::from("Press x for help. Press q to exit.")),
@@ -35,8 +41,11 @@ impl App {
             task_list: TaskListContext::default(),
             completed_list: CompletedListContext::default(),
             tick: 0,
+            running_timer: None,
             should_shutdown: false,
-        }
+        };
+        app.running_timer = app.find_running_timer();
+        app
     }
 
     /// Returns the selected index only for the current tasks and completed tasks
@@ -45,7 +54,56 @@ impl App {
         match mode {
             Mode::CurrentTasks => Some(&mut self.task_list.selected_index),
             Mode::CompletedTasks => Some(&mut self.completed_list.selected_index),
-            Mode::Overlay => None,
+            Mode::Stats | Mode::Overlay => None,
+        }
+    }
+
+    /// Returns the task that currently has a running timer.
+    pub fn running_timer(&self) -> Option<TaskID> {
+        self.running_timer.clone().filter(|task_id| {
+            self.task_store
+                .task(task_id)
+                .is_some_and(|task| task.running_timer().is_some())
+        })
+    }
+
+    /// Remembers the task the timer was started on, or None once it is stopped.
+    pub fn set_running_timer(&mut self, task_id: Option<TaskID>) {
+        self.running_timer = task_id;
+    }
+
+    fn find_running_timer(&self) -> Option<TaskID> {
+        let mut to_visit = self.task_store.root_tasks().clone();
+        while let Some(task_id) = to_visit.pop() {
+            if self
+                .task_store
+                .task(&task_id)
+                .is_some_and(|task| task.running_timer().is_some())
+            {
+                return Some(task_id);
+            }
+            if let Some(subtasks) = self.task_store.subtasks(&task_id) {
+                to_visit.extend(subtasks.iter().cloned());
+            }
+        }
+        None
+    }
+
+    /// Applies the changes from Todoist, keeping the same completed task
+    /// selected when older completed tasks are loaded above it.
+    pub fn handle_sync(&mut self, sync: TaskSync) {
+        let DataTaskStoreKind::Todoist(todoist) = &mut self.task_store else {
+            return;
+        };
+        let selected = self.completed_list.selected_row(todoist);
+        handle_sync(todoist, sync);
+        if let Some(index) = selected.and_then(|selected| {
+            self.completed_list
+                .rows(todoist)
+                .iter()
+                .position(|row| *row == selected)
+        }) {
+            self.completed_list.selected_index = index;
         }
     }
 
@@ -73,15 +131,11 @@ impl App {
     }
 }
 
-#[derive(PartialEq, Eq, Clone, Copy)]
+#[derive(PartialEq, Eq, Clone, Copy, Default)]
 pub enum Mode {
+    #[default]
     CurrentTasks,
     CompletedTasks,
+    Stats,
     Overlay,
 }
-
-impl Default for Mode {
-    fn default() -> Self {
-        Self::CurrentTasks
-    }
-}
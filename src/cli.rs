@@ -0,0 +1,385 @@
+use std::collections::{BTreeMap, HashMap};
+
+use chrono::{Datelike, Duration, Local, NaiveDate, NaiveDateTime};
+use itertools::Itertools;
+
+use crate::{
+    data::{
+        data_store::{DataTaskStore, DataTaskStoreKind, TaskID},
+        json_data_store::JsonDataStore,
+    },
+    error::AppError,
+    storage::json::validate,
+    utils::{
+        self,
+        task_position::{all_tasks, open_tasks},
+    },
+};
+
+pub const USAGE: &str = "Usage: dotodo [command]
+
+Commands:
+  report [--from YYYY-MM-DD] [--to YYYY-MM-DD]
+      Prints the time tracked on each task between the two dates (inclusive).
+      Defaults to the start of this month until today.
+  estimates
+      Prints the summed estimates of the open tasks for each tag.
+  list
+      Prints the open tasks along with their short ids.
+  show <id>
+      Prints the details of a task. The id can be shortened to any start of it
+      that no other task shares.
+  doctor [--dry-run]
+      Checks the task data for problems and repairs them.
+      With --dry-run, only prints the problems and how they would be repaired.
+  help
+      Prints this message.
+
+Without a command, the interface is opened.";
+
+/// A command that is run instead of opening the interface.
+#[derive(Debug, PartialEq)]
+pub enum Command {
+    Report { from: NaiveDate, to: NaiveDate },
+    Estimates,
+    List,
+    Show { id: String },
+    Doctor { dry_run: bool },
+    Help,
+}
+
+/// Parses the arguments, excluding the program name.
+/// Returns None if the interface should be opened.
+pub fn parse_args<I: IntoIterator<Item = String>>(args: I) -> Result<Option<Command>, AppError> {
+    let mut args = args.into_iter();
+    let Some(command) = args.next() else {
+        return Ok(None);
+    };
+
+    match command.as_str() {
+        "report" => {
+            let today = Local::now().date_naive();
+            let mut from = today.with_day(1).expect("The first day always exists");
+            let mut to = today;
+            while let Some(flag) = args.next() {
+                let value = args
+                    .next()
+                    .ok_or_else(|| AppError::InvalidArgument(format!("{flag} needs a date")))?;
+                let date = NaiveDate::parse_from_str(&value, "%Y-%m-%d")?;
+                match flag.as_str() {
+                    "--from" => from = date,
+                    "--to" => to = date,
+                    _ => return Err(AppError::InvalidArgument(flag)),
+                }
+            }
+            if from > to {
+                return Err(AppError::InvalidArgument(String::from(
+                    "--from must be before --to",
+                )));
+            }
+            Ok(Some(Command::Report { from, to }))
+        }
+        "estimates" => Ok(Some(Command::Estimates)),
+        "list" => Ok(Some(Command::List)),
+        "show" => {
+            let id = args
+                .next()
+                .filter(|id| !id.is_empty())
+                .ok_or_else(|| AppError::InvalidArgument(String::from("show needs an id")))?;
+            Ok(Some(Command::Show { id }))
+        }
+        "doctor" => {
+            let mut dry_run = false;
+            for flag in args {
+                match flag.as_str() {
+                    "--dry-run" => dry_run = true,
+                    _ => return Err(AppError::InvalidArgument(flag)),
+                }
+            }
+            Ok(Some(Command::Doctor { dry_run }))
+        }
+        "help" | "--help" | "-h" => Ok(Some(Command::Help)),
+        _ => Err(AppError::InvalidArgument(format!("{command}\n\n{USAGE}"))),
+    }
+}
+
+pub fn run(command: Command, store: &mut DataTaskStoreKind) {
+    match command {
+        Command::Report { from, to } => {
+            print!("{}", report(store, from, to, Local::now().naive_local()))
+        }
+        Command::Estimates => print!("{}", estimates(store)),
+        Command::List => print!("{}", list(store)),
+        Command::Show { id } => match show(store, &id) {
+            Ok(output) => print!("{}", output),
+            Err(err) => eprintln!("{}", err),
+        },
+        Command::Doctor { dry_run } => match store {
+            DataTaskStoreKind::Json(store) => {
+                print!("{}", doctor(store, dry_run));
+                if !dry_run {
+                    store.save();
+                }
+            }
+            DataTaskStoreKind::Todoist(_) => {
+                println!("Only the JSON data can be checked, Todoist keeps the tasks itself.")
+            }
+        },
+        Command::Help => println!("{USAGE}"),
+    }
+}
+
+/// The shortest an id is shown as, unless the id itself is shorter.
+const MIN_SHORT_ID: usize = 7;
+
+/// The shortest start of each id that no other id shares, like the short
+/// hashes of git.
+pub fn short_ids<'a>(ids: &[&'a str]) -> HashMap<&'a str, &'a str> {
+    let sorted = ids.iter().copied().sorted().dedup().collect_vec();
+    // Counted in characters, so an id is never cut inside a character.
+    let shared = |a: &str, b: &str| a.chars().zip(b.chars()).take_while(|(a, b)| a == b).count();
+    sorted
+        .iter()
+        .enumerate()
+        .map(|(i, id)| {
+            let before = i.checked_sub(1).map_or(0, |prev| shared(sorted[prev], id));
+            let after = sorted.get(i + 1).map_or(0, |next| shared(id, next));
+            let len = (before.max(after) + 1).max(MIN_SHORT_ID);
+            let end = id.char_indices().nth(len).map_or(id.len(), |(end, _)| end);
+            (*id, &id[..end])
+        })
+        .collect()
+}
+
+/// Finds the open or completed task with this id, or the only one whose id
+/// starts with it.
+pub fn resolve_id<T: DataTaskStore>(store: &T, prefix: &str) -> Result<TaskID, AppError> {
+    let ids = all_tasks(store).into_iter().map(|(id, _)| id).collect_vec();
+    if ids.iter().any(|id| id == prefix) {
+        return Ok(prefix.to_string());
+    }
+    let matches = ids
+        .into_iter()
+        .filter(|id| id.starts_with(prefix))
+        .collect_vec();
+    match matches.as_slice() {
+        [id] => Ok(id.clone()),
+        [] => Err(AppError::InvalidArgument(format!(
+            "no task has the id {prefix}"
+        ))),
+        _ => Err(AppError::InvalidArgument(format!(
+            "the id {prefix} could be {}",
+            matches.join(", ")
+        ))),
+    }
+}
+
+/// The open tasks with their short ids, subtasks are indented below their parent.
+pub fn list<T: DataTaskStore>(store: &T) -> String {
+    let tasks = all_tasks(store);
+    let ids = tasks.iter().map(|(id, _)| id.as_str()).collect_vec();
+    let short_ids = short_ids(&ids);
+    let width = short_ids
+        .values()
+        .map(|id| id.len())
+        .max()
+        .unwrap_or_default();
+
+    let mut output = String::new();
+    let mut to_visit = store
+        .root_tasks()
+        .iter()
+        .rev()
+        .map(|id| (id, 0))
+        .collect_vec();
+    while let Some((id, depth)) = to_visit.pop() {
+        let Some(task) = store.task(id) else {
+            continue;
+        };
+        if let Some(subtasks) = store.subtasks(id) {
+            to_visit.extend(subtasks.iter().rev().map(|id| (id, depth + 1)));
+        }
+        output += &format!(
+            "{:<width$}  {}{}\n",
+            short_ids.get(id.as_str()).unwrap_or(&id.as_str()),
+            "  ".repeat(depth),
+            task.title.lines().next().unwrap_or_default()
+        );
+    }
+
+    if output.is_empty() {
+        output += "There are no open tasks.\n";
+    }
+    output
+}
+
+/// The details of the task with this id, see `resolve_id`.
+pub fn show<T: DataTaskStore>(store: &T, id: &str) -> Result<String, AppError> {
+    let id = resolve_id(store, id)?;
+    let (task, time_completed) = match store.completed_task(&id) {
+        Some(completed_task) => (&completed_task.task, Some(completed_task.time_completed)),
+        None => (
+            store
+                .task(&id)
+                .ok_or_else(|| AppError::invalid_state("The task has no data"))?,
+            None,
+        ),
+    };
+    let format_time = |time: NaiveDateTime| time.format("%Y-%m-%d %H:%M").to_string();
+
+    let mut details = vec![
+        ("Id", id.clone()),
+        ("Priority", task.priority.display_string().to_string()),
+    ];
+    if !task.tags.is_empty() {
+        let tags = task
+            .tags
+            .iter()
+            .map(|tag| store.tags().get(tag).map_or(tag.as_str(), |f| &f.name))
+            .join(", ");
+        details.push(("Tags", tags));
+    }
+    if let Some(due_date) = task.due_date {
+        details.push(("Due", due_date.to_string()));
+    }
+    if let Some(estimate) = task.estimate {
+        details.push(("Estimate", utils::format_duration(estimate)));
+    }
+    if let Some(created_at) = task.created_at {
+        details.push(("Created", format_time(created_at)));
+    }
+    if let Some(updated_at) = task.updated_at {
+        details.push(("Updated", format_time(updated_at)));
+    }
+    if let Some(time_completed) = time_completed {
+        details.push(("Completed", format_time(time_completed)));
+    }
+
+    let mut output = format!("{}\n\n", task.title.trim_end());
+    for (name, value) in details {
+        output += &format!("{:<10} {}\n", name, value);
+    }
+    Ok(output)
+}
+
+/// The problems with the task data and how they are repaired, they are only
+/// repaired if this is not a dry run.
+pub fn doctor(store: &mut JsonDataStore, dry_run: bool) -> String {
+    let problems = if dry_run {
+        validate::validate(store)
+    } else {
+        validate::repair(store)
+    };
+    if problems.is_empty() {
+        return String::from("No problems were found.\n");
+    }
+
+    let mut output = String::new();
+    for problem in &problems {
+        output += &format!("{}\n  {}\n", problem, problem.repair_description());
+    }
+    output += &if dry_run {
+        format!(
+            "\nFound {} problems, run without --dry-run to repair them.\n",
+            problems.len()
+        )
+    } else {
+        format!("\nRepaired {} problems.\n", problems.len())
+    };
+    output
+}
+
+/// The time tracked on each task and each day between `from` and `to` (inclusive).
+pub fn report<T: DataTaskStore>(
+    store: &T,
+    from: NaiveDate,
+    to: NaiveDate,
+    now: NaiveDateTime,
+) -> String {
+    let mut output = format!("Time tracked from {from} to {to}\n\n");
+    let mut per_day = BTreeMap::new();
+    let mut total = Duration::zero();
+
+    for (_, task) in all_tasks(store) {
+        let days = task.tracked_time_by_day(now, from, to);
+        let tracked = days.values().fold(Duration::zero(), |acc, f| acc + *f);
+        if tracked.is_zero() {
+            continue;
+        }
+        for (day, duration) in days {
+            *per_day.entry(day).or_insert_with(Duration::zero) += duration;
+        }
+        total += tracked;
+        output += &format!(
+            "{:>9}  {}\n",
+            utils::format_duration(tracked),
+            task.title.lines().next().unwrap_or_default()
+        );
+    }
+
+    if total.is_zero() {
+        output += "No time was tracked.\n";
+        return output;
+    }
+
+    output += "\n";
+    for (day, duration) in per_day {
+        output += &format!(
+            "{:>9}  {}\n",
+            utils::format_duration(duration),
+            day.format("%a %Y-%m-%d")
+        );
+    }
+    output += &format!(
+        "\n{:>9}  Total ({:.2} hours)\n",
+        utils::format_duration(total),
+        total.num_minutes() as f64 / 60.0
+    );
+    output
+}
+
+/// The estimates of the open tasks summed for each tag, a task with multiple
+/// tags is counted in each of them.
+pub fn estimates<T: DataTaskStore>(store: &T) -> String {
+    let mut output = String::from("Estimates of the open tasks by tag\n\n");
+    let mut per_tag: BTreeMap<String, Duration> = BTreeMap::new();
+    let mut untagged = Duration::zero();
+    let mut total = Duration::zero();
+
+    for (_, task) in open_tasks(store) {
+        let Some(estimate) = task.estimate else {
+            continue;
+        };
+        total += estimate;
+        let tags = task
+            .tags
+            .iter()
+            .map(|tag| {
+                store
+                    .tags()
+                    .get(tag)
+                    .map_or(tag.clone(), |f| f.name.clone())
+            })
+            .collect::<Vec<_>>();
+        if tags.is_empty() {
+            untagged += estimate;
+        }
+        for tag in tags {
+            *per_tag.entry(tag).or_insert_with(Duration::zero) += estimate;
+        }
+    }
+
+    if total.is_zero() {
+        output += "No open task has an estimate.\n";
+        return output;
+    }
+
+    for (tag, estimate) in per_tag {
+        output += &format!("{:>9}  {}\n", utils::format_duration(estimate), tag);
+    }
+    if !untagged.is_zero() {
+        output += &format!("{:>9}  No tag\n", utils::format_duration(untagged));
+    }
+    output += &format!("\n{:>9}  Total\n", utils::format_duration(total));
+    output
+}
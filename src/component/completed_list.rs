@@ -1,13 +1,18 @@
-use crate::{data::data_store::DataTaskStore, utils::task_position::cursor_to_completed_task};
+use std::{cmp::Reverse, collections::HashSet};
+
+use chrono::{Datelike, Duration, Local, NaiveDate};
+use itertools::Itertools;
 use tui::{
     layout::Rect,
-    style::{Color, Style},
+    style::{Color, Modifier, Style},
     text::{Line, Span},
     widgets::{List, ListItem, ListState},
 };
 
 use crate::{
     app::{App, Mode},
+    data::data_store::{DataTaskStore, TaskID, TaskIDRef},
+    error::StoreError,
     framework::{
         component::{Component, Drawer},
         event::PostEvent,
@@ -21,9 +26,136 @@ pub struct CompletedList {
     pub area: Rect,
 }
 
+/// A row of the completed list.
+#[derive(Debug, Clone, PartialEq)]
+pub enum CompletedRow {
+    /// The header of the tasks completed on this day.
+    Day(NaiveDate),
+    /// A completed task and how deeply it is nested.
+    Task(TaskID, usize),
+}
+
 #[derive(Default)]
 pub struct CompletedListContext {
     pub selected_index: usize,
+    /// The days that are collapsed, hiding the tasks completed on them.
+    pub collapsed_days: HashSet<NaiveDate>,
+    /// Only shows the completed tasks with this tag.
+    pub tag_filter: Option<String>,
+}
+
+impl CompletedListContext {
+    /// Whether the completed task, or any of the subtasks completed with it,
+    /// has the tag that is being filtered.
+    fn matches_filter<T: DataTaskStore>(&self, store: &T, id: TaskIDRef) -> bool {
+        let Some(tag) = &self.tag_filter else {
+            return true;
+        };
+        store
+            .completed_task(id)
+            .is_some_and(|completed_task| completed_task.task.tags.contains(tag))
+            || store.completed_subtasks(id).is_some_and(|subtasks| {
+                subtasks
+                    .iter()
+                    .any(|subtask| self.matches_filter(store, subtask))
+            })
+    }
+
+    /// The top level completed tasks grouped by the day they were completed,
+    /// the most recent first.
+    pub fn days<'a, T: DataTaskStore>(&self, store: &'a T) -> Vec<(NaiveDate, Vec<&'a TaskID>)> {
+        let tasks = store
+            .completed_root_tasks()
+            .iter()
+            .rev()
+            .filter(|id| self.matches_filter(store, id))
+            .filter_map(|id| Some((id, store.completed_task(id)?.time_completed)))
+            .sorted_by_key(|(_, time_completed)| Reverse(*time_completed));
+        tasks
+            .group_by(|(_, time_completed)| time_completed.date())
+            .into_iter()
+            .map(|(day, tasks)| (day, tasks.map(|(id, _)| id).collect()))
+            .collect()
+    }
+
+    /// The rows that are shown, each day is followed by the tasks completed
+    /// on it unless it is collapsed.
+    pub fn rows<T: DataTaskStore>(&self, store: &T) -> Vec<CompletedRow> {
+        let mut rows = Vec::new();
+        for (day, tasks) in self.days(store) {
+            rows.push(CompletedRow::Day(day));
+            if self.collapsed_days.contains(&day) {
+                continue;
+            }
+            for id in tasks {
+                push_subtree(store, id, 0, &mut rows);
+            }
+        }
+        rows
+    }
+
+    pub fn selected_row<T: DataTaskStore>(&self, store: &T) -> Option<CompletedRow> {
+        self.rows(store).into_iter().nth(self.selected_index)
+    }
+
+    /// The number of tasks completed each day, including the subtasks
+    /// completed with them.
+    pub fn count_by_day<T: DataTaskStore>(&self, store: &T) -> Vec<(NaiveDate, usize)> {
+        self.days(store)
+            .into_iter()
+            .map(|(day, tasks)| {
+                let count = tasks
+                    .into_iter()
+                    .map(|id| store.find_completed_task_draw_size(id))
+                    .sum();
+                (day, count)
+            })
+            .collect()
+    }
+}
+
+fn push_subtree<T: DataTaskStore>(
+    store: &T,
+    id: TaskIDRef,
+    nested_level: usize,
+    rows: &mut Vec<CompletedRow>,
+) {
+    rows.push(CompletedRow::Task(id.to_string(), nested_level));
+    if let Some(subtasks) = store.completed_subtasks(id) {
+        for subtask in subtasks {
+            push_subtree(store, subtask, nested_level + 1, rows);
+        }
+    }
+}
+
+/// The name of the day shown in its header, eg: "Today" or "Mon 12 Oct".
+pub fn day_label(day: NaiveDate, today: NaiveDate) -> String {
+    if day == today {
+        String::from("Today")
+    } else if Some(day) == today.pred_opt() {
+        String::from("Yesterday")
+    } else if day.year() == today.year() {
+        day.format("%a %-d %b").to_string()
+    } else {
+        day.format("%a %-d %b %Y").to_string()
+    }
+}
+
+/// The number of tasks completed this week and last week, weeks start on Monday.
+pub fn weekly_counts(counts: &[(NaiveDate, usize)], today: NaiveDate) -> (usize, usize) {
+    let this_week = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+    let last_week = this_week - Duration::weeks(1);
+    let count_between = |from: NaiveDate, to: NaiveDate| {
+        counts
+            .iter()
+            .filter(|(day, _)| (from..to).contains(day))
+            .map(|(_, count)| count)
+            .sum()
+    };
+    (
+        count_between(this_week, this_week + Duration::weeks(1)),
+        count_between(last_week, this_week),
+    )
 }
 
 impl CompletedList {
@@ -33,71 +165,175 @@ impl CompletedList {
         }
     }
 
-    pub fn restore_task(app: &mut App) {
-        if app.task_store.completed_root_tasks().is_empty() {
+    /// Loads older completed tasks once the oldest loaded task is selected.
+    pub fn load_history_if_needed(app: &mut App) {
+        let rows = app.completed_list.rows(&app.task_store).len();
+        if app.completed_list.selected_index + 1 >= rows {
+            app.task_store.load_completed_history();
+        }
+    }
+
+    pub fn restore_task(app: &mut App) -> Result<(), StoreError> {
+        let Some(CompletedRow::Task(completed_task, _)) =
+            app.completed_list.selected_row(&app.task_store)
+        else {
+            return Ok(());
+        };
+        app.task_store.restore(&completed_task)?;
+        let rows = app.completed_list.rows(&app.task_store).len();
+        if app.completed_list.selected_index >= rows && rows != 0 {
+            app.completed_list.selected_index = rows - 1;
+        }
+        Ok(())
+    }
+
+    /// Collapses or expands the day of the selected row and selects the
+    /// header of that day.
+    pub fn flip_day(app: &mut App) {
+        let rows = app.completed_list.rows(&app.task_store);
+        let Some((index, day)) = rows
+            .iter()
+            .enumerate()
+            .take(app.completed_list.selected_index + 1)
+            .rev()
+            .find_map(|(index, row)| match row {
+                CompletedRow::Day(day) => Some((index, *day)),
+                CompletedRow::Task(..) => None,
+            })
+        else {
             return;
+        };
+        let collapsed_days = &mut app.completed_list.collapsed_days;
+        if !collapsed_days.remove(&day) {
+            collapsed_days.insert(day);
         }
+        app.completed_list.selected_index = index;
+    }
 
-        if let Some(completed_task) =
-            cursor_to_completed_task(&app.task_store, app.completed_list.selected_index)
-        {
-            app.task_store.restore(&completed_task);
-            if app.completed_list.selected_index >= app.task_store.completed_root_tasks().len()
-                && !app.task_store.completed_root_tasks().is_empty()
-            {
-                app.completed_list.selected_index -= 1;
-            }
+    fn draw_task<'a>(
+        app: &'a App,
+        task_id: TaskIDRef,
+        nested_level: usize,
+        selected: bool,
+    ) -> Line<'a> {
+        let theme = &app.config;
+        let Some(completed_task) = app.task_store.completed_task(task_id) else {
+            return Line::default();
+        };
+
+        let colour = if selected {
+            theme.selected_task_colour
+        } else {
+            theme.default_task_colour
+        };
+        let mut spans = vec![
+            Span::styled(
+                completed_task
+                    .time_completed
+                    .format("    %l:%M %p ")
+                    .to_string(),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                theme.nested_padding.repeat(nested_level),
+                Style::default().fg(Color::DarkGray),
+            ),
+            Span::styled(
+                completed_task
+                    .task
+                    .title
+                    .split('\n')
+                    .next()
+                    .unwrap_or_default(),
+                Style::default().fg(colour),
+            ),
+        ];
+        for tag in completed_task.task.iter_tags(app) {
+            spans.push(Span::styled(
+                format!(" ({})", tag.name),
+                Style::default().fg(tag.colour),
+            ));
         }
+        Line::from(spans)
+    }
+
+    fn draw_day<'a>(app: &'a App, day: NaiveDate, count: usize, selected: bool) -> Line<'a> {
+        let theme = &app.config;
+        let glyph = if app.completed_list.collapsed_days.contains(&day) {
+            &theme.closed_subtask
+        } else {
+            &theme.open_subtask
+        };
+        let colour = if selected {
+            theme.selected_task_colour
+        } else {
+            theme.default_task_colour
+        };
+        Line::from(vec![
+            Span::styled(glyph.as_str(), Style::default().fg(colour)),
+            Span::styled(
+                day_label(day, Local::now().date_naive()),
+                Style::default().fg(colour).add_modifier(Modifier::BOLD),
+            ),
+            Span::styled(
+                format!(" ({})", count),
+                Style::default().fg(Color::DarkGray),
+            ),
+        ])
     }
 }
 
 impl Component for CompletedList {
     fn draw(&self, app: &App, drawer: &mut Drawer) {
-        let theme = &app.config;
-
         let selected_index = app.completed_list.selected_index;
+        let counts = app.completed_list.count_by_day(&app.task_store);
 
-        let completed_tasks: Vec<ListItem> = app
-            .task_store
-            .completed_root_tasks()
+        let rows = app.completed_list.rows(&app.task_store);
+        let completed_tasks: Vec<ListItem> = rows
             .iter()
             .enumerate()
-            .map(|(i, task_id)| {
-                let Some(task) = app.task_store.completed_task(task_id) else {
-                    // FIXME: Error gracefyly
-                    panic!("ok");
-                };
-                let colour = if let Mode::CompletedTasks = app.mode {
-                    if selected_index == i {
-                        theme.selected_task_colour
-                    } else {
-                        theme.default_task_colour
+            .map(|(i, row)| {
+                let selected = COMPONENT_TYPE == app.mode && selected_index == i;
+                match row {
+                    CompletedRow::Day(day) => {
+                        let count = counts
+                            .iter()
+                            .find(|(counted_day, _)| counted_day == day)
+                            .map_or(0, |(_, count)| *count);
+                        Self::draw_day(app, *day, count, selected)
+                    }
+                    CompletedRow::Task(task_id, nested_level) => {
+                        Self::draw_task(app, task_id, *nested_level, selected)
                     }
-                } else {
-                    theme.default_task_colour
-                };
-                let content = Line::from(Span::styled(
-                    format!(
-                        "{} {}",
-                        task.time_completed.format("%d/%m/%y %-I:%M:%S %p"),
-                        task.task.title
-                    ),
-                    Style::default().fg(colour),
-                ));
-                ListItem::new(content)
+                }
             })
+            .map(ListItem::new)
             .collect();
 
+        let (this_week, last_week) = weekly_counts(&counts, Local::now().date_naive());
+        let mut title = format!(
+            "Completed tasks - {} this week, {} last week",
+            this_week, last_week
+        );
+        if let Some(tag) = app
+            .completed_list
+            .tag_filter
+            .as_ref()
+            .and_then(|tag| app.task_store.tags().get(tag))
+        {
+            title += &format!(" ({})", tag.name);
+        }
+
         let completed_list = List::new(completed_tasks)
             .block(utils::ui::generate_default_block(
                 app,
-                "Completed tasks",
+                &title,
                 COMPONENT_TYPE,
             ))
             .style(Style::default().fg(Color::White));
 
         let mut completed_state = ListState::default();
-        if !app.task_store.completed_root_tasks().is_empty() {
+        if !rows.is_empty() {
             completed_state.select(Some(selected_index));
         }
 
@@ -109,13 +345,15 @@ impl Component for CompletedList {
         app: &mut App,
         mouse_event: crossterm::event::MouseEvent,
     ) -> PostEvent {
-        utils::handle_mouse_movement_app(
+        let event = utils::handle_mouse_movement_app(
             app,
             self.area,
             COMPONENT_TYPE,
-            app.task_store.completed_root_tasks().len(),
+            app.completed_list.rows(&app.task_store).len(),
             mouse_event,
-        )
+        );
+        CompletedList::load_history_if_needed(app);
+        event
     }
 
     fn update_layout(&mut self, rect: Rect) {
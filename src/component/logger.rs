@@ -15,7 +15,7 @@ use tui::{
 
 use crate::{
     framework::{
-        component::{Component, Drawer},
+        component::{Component, Drawer, ANIMATION_TICK},
         event::PostEvent,
     },
     utils::{self},
@@ -99,6 +99,11 @@ impl Component for Logger {
         PostEvent::noop(true)
     }
 
+    // New logs are only seen once it is drawn again
+    fn tick_rate(&self, _: &crate::app::App) -> Option<std::time::Duration> {
+        self.opened.then_some(ANIMATION_TICK)
+    }
+
     fn update_layout(&mut self, draw_area: tui::layout::Rect) {
         self.draw_area = utils::centre_rect(
             tui::layout::Constraint::Percentage(70),
@@ -0,0 +1,188 @@
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent, MouseEventKind};
+
+use chrono::Local;
+use tui::{
+    layout::{Constraint, Rect},
+    style::Style,
+    text::{Line, Span},
+    widgets::{Clear, List, ListItem, ListState},
+};
+
+use crate::{
+    actions::noop_or_error,
+    app::{App, Mode},
+    component::overlay::{input_box::InputBoxBuilder, vim::VimMode},
+    data::data_store::{DataTaskStore, TaskID},
+    framework::{
+        component::{Component, Drawer},
+        event::{AppEvent, PostEvent},
+    },
+    task::Comment,
+    utils,
+};
+
+/// Shows the comments of a task, allowing new ones to be added and existing
+/// ones to be edited.
+pub struct CommentsBox {
+    draw_area: Rect,
+    task_id: TaskID,
+    pub index: usize,
+    prev_mode: Option<Mode>,
+}
+
+impl CommentsBox {
+    pub fn new(task_id: TaskID) -> CommentsBox {
+        CommentsBox {
+            draw_area: Rect::default(),
+            task_id,
+            index: 0,
+            prev_mode: None,
+        }
+    }
+
+    fn comments<'a>(&self, app: &'a App) -> &'a [Comment] {
+        app.task_store
+            .task(&self.task_id)
+            .map_or(&[], |task| task.comments.as_slice())
+    }
+
+    fn add_comment(&mut self, app: &App) -> PostEvent {
+        let task_id = self.task_id.clone();
+        // The new comment is placed at the end.
+        self.index = self.comments(app).len();
+        let input_box = InputBoxBuilder::default()
+            .title("Add a comment")
+            .use_vim(&app.config, VimMode::Insert)
+            .on_submit(move |app, content| {
+                noop_or_error(app.task_store.add_comment(
+                    &task_id,
+                    content.trim().to_string(),
+                    Local::now().naive_local(),
+                ))
+            })
+            .build();
+        PostEvent::push_layer(input_box)
+    }
+
+    fn edit_comment(&self, app: &App) -> PostEvent {
+        let Some(comment) = self.comments(app).get(self.index) else {
+            return PostEvent::noop(false);
+        };
+        let task_id = self.task_id.clone();
+        let comment_id = comment.id.clone();
+        let input_box = InputBoxBuilder::default()
+            .title("Edit the comment")
+            .fill(&comment.content)
+            .use_vim(&app.config, VimMode::Normal)
+            .on_submit(move |app, content| {
+                noop_or_error(app.task_store.update_comment(
+                    &task_id,
+                    &comment_id,
+                    content.trim().to_string(),
+                ))
+            })
+            .build();
+        PostEvent::push_layer(input_box)
+    }
+}
+
+impl Component for CommentsBox {
+    fn draw(&self, app: &App, drawer: &mut Drawer) {
+        let comments = self.comments(app);
+        let items = if comments.is_empty() {
+            vec![ListItem::new(Line::from(format!(
+                "No comments, press {} to add one",
+                app.config.add_key
+            )))]
+        } else {
+            comments
+                .iter()
+                .map(|comment| {
+                    ListItem::new(Line::from(vec![
+                        Span::styled(
+                            comment.posted_at.format("%d/%m/%y %-I:%M %p ").to_string(),
+                            Style::default().fg(app.config.default_border_colour),
+                        ),
+                        Span::raw(comment.content.replace('\n', " ")),
+                    ]))
+                })
+                .collect()
+        };
+
+        let list = List::new(items)
+            .highlight_symbol(&app.config.selected_cursor)
+            .highlight_style(app.config.highlight_dropdown_style())
+            .block(utils::ui::generate_default_block(
+                app,
+                "Comments",
+                Mode::Overlay,
+            ));
+
+        let mut list_state = ListState::default();
+        if !comments.is_empty() {
+            list_state.select(Some(self.index.min(comments.len() - 1)));
+        }
+
+        drawer.draw_widget(Clear, self.draw_area);
+        drawer.draw_stateful_widget(list, &mut list_state, self.draw_area);
+    }
+
+    fn key_event(&mut self, app: &mut App, key_event: KeyEvent) -> PostEvent {
+        if app.config.add_key.is_pressed(key_event) {
+            return self.add_comment(app);
+        }
+        if app.config.edit_key.is_pressed(key_event) {
+            return self.edit_comment(app);
+        }
+        match key_event.code {
+            KeyCode::Esc | KeyCode::Char('q') => {
+                return PostEvent::pop_layer(Some(AppEvent::Cancel))
+            }
+            _ => {}
+        }
+
+        let len = self.comments(app).len();
+        if len != 0 {
+            self.index = self.index.min(len - 1);
+            utils::handle_key_movement(&app.config, key_event, &mut self.index, len);
+        }
+        PostEvent::noop(false)
+    }
+
+    fn mount(&mut self, app: &mut App) {
+        self.prev_mode = Some(app.mode);
+        app.mode = Mode::Overlay;
+    }
+
+    fn unmount(&mut self, app: &mut App, _: Option<AppEvent>) -> PostEvent {
+        if let Some(prev_mode) = self.prev_mode {
+            app.mode = prev_mode;
+        }
+        PostEvent::noop(false)
+    }
+
+    fn mouse_event(&mut self, app: &mut App, mouse_event: MouseEvent) -> PostEvent {
+        if utils::inside_rect((mouse_event.row, mouse_event.column), self.draw_area) {
+            let draw_area = self.draw_area;
+            let size = self.comments(app).len();
+            return utils::handle_mouse_movement(
+                &mut self.index,
+                &mut app.mode,
+                draw_area,
+                Mode::Overlay,
+                size,
+                mouse_event,
+            );
+        }
+
+        if let MouseEventKind::Down(_) = mouse_event.kind {
+            return PostEvent::pop_layer(Some(AppEvent::Cancel));
+        }
+        PostEvent::noop(false)
+    }
+
+    fn update_layout(&mut self, area: Rect) {
+        self.draw_area =
+            utils::centre_rect(Constraint::Percentage(70), Constraint::Percentage(60), area);
+    }
+}
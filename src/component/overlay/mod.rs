@@ -5,9 +5,11 @@ use crate::{
     framework::{event::PostEvent, screen_manager::ScreenManager},
 };
 
+pub mod comments;
 pub mod dialog;
 pub mod fuzzy;
 pub mod input_box;
+pub mod pomodoro;
 pub mod vim;
 
 pub struct Overlay;
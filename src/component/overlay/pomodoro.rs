@@ -0,0 +1,222 @@
+use std::{
+    io::{stdout, Write},
+    time::{Duration, Instant},
+};
+
+use crossterm::event::{KeyCode, KeyEvent, MouseEvent};
+use tui::{
+    layout::{Constraint, Rect},
+    style::{Modifier, Style},
+    text::Span,
+    widgets::{Clear, Gauge},
+};
+
+use crate::{
+    app::{App, Mode},
+    data::data_store::{DataTaskStore, TaskID},
+    framework::{
+        component::{Component, Drawer, SECOND_TICK},
+        event::{AppEvent, PostEvent},
+    },
+    utils,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Phase {
+    Work,
+    Break,
+}
+
+impl Phase {
+    fn length(&self, app: &App) -> Duration {
+        let minutes = match self {
+            Phase::Work => app.config.pomodoro_work_minutes,
+            Phase::Break => app.config.pomodoro_break_minutes,
+        };
+        Duration::from_secs(minutes * 60)
+    }
+
+    fn next(&self) -> Phase {
+        match self {
+            Phase::Work => Phase::Break,
+            Phase::Break => Phase::Work,
+        }
+    }
+}
+
+/// Counts down work and break intervals while focusing on a task, a
+/// pomodoro is added to the task after each work interval.
+pub struct PomodoroBox {
+    draw_area: Rect,
+    task_id: TaskID,
+    pub phase: Phase,
+    pub remaining: Duration,
+    pub paused: bool,
+    last_tick: Option<Instant>,
+    prev_mode: Option<Mode>,
+}
+
+impl PomodoroBox {
+    pub fn new(app: &App, task_id: TaskID) -> PomodoroBox {
+        PomodoroBox {
+            draw_area: Rect::default(),
+            task_id,
+            phase: Phase::Work,
+            remaining: Phase::Work.length(app),
+            paused: false,
+            last_tick: None,
+            prev_mode: None,
+        }
+    }
+
+    /// Moves the countdown forward, finishing the phase if there is no time left.
+    pub fn advance(&mut self, app: &mut App, elapsed: Duration) {
+        if self.paused {
+            return;
+        }
+        match self.remaining.checked_sub(elapsed) {
+            Some(remaining) if !remaining.is_zero() => self.remaining = remaining,
+            _ => self.finish_phase(app),
+        }
+    }
+
+    /// Ends the current phase and starts the next one.
+    pub fn finish_phase(&mut self, app: &mut App) {
+        let message = match self.phase {
+            Phase::Work => {
+                if let Err(err) = app
+                    .task_store
+                    .modify_task(&self.task_id, |task| task.pomodoros += 1)
+                {
+                    tracing::error!("Could not count the pomodoro: {}", err);
+                }
+                "Pomodoro finished, time for a break"
+            }
+            Phase::Break => "Break finished, time to focus",
+        };
+        notify(app, self.phase, message);
+        self.phase = self.phase.next();
+        self.remaining = self.phase.length(app);
+    }
+}
+
+fn notify(app: &App, phase: Phase, message: &str) {
+    if app.config.pomodoro_bell {
+        let mut stdout = stdout();
+        let _ = stdout.write_all(b"\x07").and_then(|_| stdout.flush());
+    }
+    if let Some(command) = &app.config.pomodoro_command {
+        let result = utils::shell(command)
+            .env("DOTODO_MESSAGE", message)
+            .env(
+                "DOTODO_PHASE",
+                match phase {
+                    Phase::Work => "work",
+                    Phase::Break => "break",
+                },
+            )
+            .spawn();
+        if let Err(err) = result {
+            tracing::error!("Could not run the pomodoro command: {}", err);
+        }
+    }
+}
+
+impl Component for PomodoroBox {
+    fn draw(&self, app: &App, drawer: &mut Drawer) {
+        let Some(task) = app.task_store.task(&self.task_id) else {
+            return;
+        };
+        let seconds = self.remaining.as_secs();
+        let mut label = format!(
+            "{} {:02}:{:02}",
+            match self.phase {
+                Phase::Work => "Focus",
+                Phase::Break => "Break",
+            },
+            seconds / 60,
+            seconds % 60
+        );
+        if self.paused {
+            label += " (paused)";
+        }
+        let length = self.phase.length(app).as_secs_f64();
+        let ratio = if length == 0.0 {
+            1.0
+        } else {
+            1.0 - self.remaining.as_secs_f64() / length
+        };
+
+        let title = format!(
+            "{} - {} pomodoros",
+            task.title.lines().next().unwrap_or_default(),
+            task.pomodoros
+        );
+        let gauge = Gauge::default()
+            .block(utils::ui::generate_default_block(
+                app,
+                &title,
+                Mode::Overlay,
+            ))
+            .gauge_style(Style::default().fg(match self.phase {
+                Phase::Work => app.config.high_priority_colour,
+                Phase::Break => app.config.low_priority_colour,
+            }))
+            .label(Span::styled(
+                label,
+                Style::default().add_modifier(Modifier::BOLD),
+            ))
+            .ratio(ratio.clamp(0.0, 1.0));
+
+        drawer.draw_widget(Clear, self.draw_area);
+        drawer.draw_widget(gauge, self.draw_area);
+    }
+
+    fn key_event(&mut self, app: &mut App, key_event: KeyEvent) -> PostEvent {
+        match key_event.code {
+            KeyCode::Char(' ') => {
+                self.paused = !self.paused;
+                self.last_tick = None;
+            }
+            KeyCode::Char('s') => self.finish_phase(app),
+            KeyCode::Esc | KeyCode::Char('q') => {
+                return PostEvent::pop_layer(Some(AppEvent::Cancel))
+            }
+            _ => {}
+        }
+        PostEvent::noop(false)
+    }
+
+    fn mouse_event(&mut self, _: &mut App, _: MouseEvent) -> PostEvent {
+        PostEvent::noop(false)
+    }
+
+    fn tick(&mut self, app: &mut App) {
+        let now = Instant::now();
+        if let Some(last_tick) = self.last_tick {
+            self.advance(app, now - last_tick);
+        }
+        self.last_tick = Some(now);
+    }
+
+    fn tick_rate(&self, _: &App) -> Option<Duration> {
+        (!self.paused).then_some(SECOND_TICK)
+    }
+
+    fn mount(&mut self, app: &mut App) {
+        self.prev_mode = Some(app.mode);
+        app.mode = Mode::Overlay;
+    }
+
+    fn unmount(&mut self, app: &mut App, _: Option<AppEvent>) -> PostEvent {
+        if let Some(prev_mode) = self.prev_mode {
+            app.mode = prev_mode;
+        }
+        PostEvent::noop(false)
+    }
+
+    fn update_layout(&mut self, area: Rect) {
+        self.draw_area =
+            utils::centre_rect(Constraint::Percentage(70), Constraint::Length(3), area);
+    }
+}
@@ -1,4 +1,5 @@
 use crate::data::data_store::DataTaskStore;
+use chrono::{Duration, Local};
 use crossterm::event::KeyEvent;
 use tui::{
     layout::Rect,
@@ -10,7 +11,7 @@ use tui::{
 use crate::{
     app::App,
     framework::{
-        component::{Component, Drawer},
+        component::{Component, Drawer, ANIMATION_TICK, SECOND_TICK},
         event::{Action, PostEvent},
     },
 };
@@ -43,6 +44,7 @@ impl StatusLine {
 }
 
 const SPINNER: [&str; 4] = ["-", "\\", "|", "/"];
+const TIMER: [&str; 2] = ["●", "○"];
 
 impl Component for StatusLine {
     // Should be able to do commands?!
@@ -51,6 +53,33 @@ impl Component for StatusLine {
         if app.task_list.auto_sort {
             status_line += " Auto sort is current enabled"
         }
+        if app.task_list.is_selecting() {
+            status_line += &format!(
+                " {} selected",
+                app.task_list.selection(&app.task_store).len()
+            );
+        }
+        if let Some(task) = app
+            .running_timer()
+            .and_then(|task_id| app.task_store.task(&task_id))
+        {
+            let elapsed = task
+                .running_timer()
+                .map_or(Duration::zero(), |entry| {
+                    entry.duration(Local::now().naive_local())
+                })
+                .num_seconds();
+            // Blinks with the seconds, as the timer may only be drawn once a second
+            let indicator = TIMER[elapsed as usize % TIMER.len()];
+            status_line += &format!(
+                " {} {} {}:{:02}:{:02}",
+                indicator,
+                task.title.lines().next().unwrap_or_default(),
+                elapsed / 3600,
+                elapsed / 60 % 60,
+                elapsed % 60
+            );
+        }
         if app.task_store.is_syncing() {
             status_line += &format!(" {}", SPINNER[app.tick % SPINNER.len()]);
         }
@@ -69,4 +98,14 @@ impl Component for StatusLine {
     fn update_layout(&mut self, draw_area: Rect) {
         self.draw_area = draw_area;
     }
+
+    fn tick_rate(&self, app: &App) -> Option<std::time::Duration> {
+        if app.task_store.is_syncing() {
+            Some(ANIMATION_TICK)
+        } else if app.running_timer().is_some() {
+            Some(SECOND_TICK)
+        } else {
+            None
+        }
+    }
 }
@@ -1,5 +1,6 @@
+use std::collections::HashSet;
+
 use crate::data::data_store::DataTaskStore;
-use itertools::Itertools;
 use tui::{
     layout::Rect,
     style::{Color, Modifier, Style},
@@ -9,12 +10,12 @@ use tui::{
 
 use crate::{
     app::{App, Mode},
-    data::data_store::TaskIDRef,
+    data::data_store::{TaskID, TaskIDRef},
     framework::{
         component::{Component, Drawer},
         event::PostEvent,
     },
-    utils::{self, handle_mouse_movement_app},
+    utils::{self, handle_mouse_movement_app, task_position::cursor_to_task},
 };
 
 const COMPONENT_TYPE: Mode = Mode::CurrentTasks;
@@ -27,6 +28,60 @@ pub struct TaskList {
 pub struct TaskListContext {
     pub selected_index: usize,
     pub auto_sort: bool,
+    /// The row visual mode was started on, the rows between it and the
+    /// selected row are selected.
+    pub visual_anchor: Option<usize>,
+    /// The tasks that were marked, kept by id so they stay marked as they
+    /// are moved.
+    pub marked: HashSet<TaskID>,
+}
+
+impl TaskListContext {
+    /// Whether any task is marked or visual mode is on.
+    pub fn is_selecting(&self) -> bool {
+        self.visual_anchor.is_some() || !self.marked.is_empty()
+    }
+
+    fn in_visual_range(&self, row: usize) -> bool {
+        self.visual_anchor.is_some_and(|anchor| {
+            (anchor.min(self.selected_index)..=anchor.max(self.selected_index)).contains(&row)
+        })
+    }
+
+    /// The tasks the actions apply to in the order they are drawn, this is
+    /// the marked tasks along with the visual range or the task under the
+    /// cursor if neither are used. Marked tasks hidden under a closed task
+    /// are left out.
+    pub fn selection<T: DataTaskStore>(&self, store: &T) -> Vec<TaskID> {
+        if !self.is_selecting() {
+            return cursor_to_task(store, self.selected_index)
+                .into_iter()
+                .collect();
+        }
+        store
+            .visible_rows()
+            .iter()
+            .enumerate()
+            .filter(|(row, (id, _))| self.marked.contains(*id) || self.in_visual_range(*row))
+            .map(|(_, (id, _))| id.to_string())
+            .collect()
+    }
+
+    /// Keeps the visual range as marked tasks, so they stay selected when
+    /// the rows change.
+    pub fn mark_visual_range<T: DataTaskStore>(&mut self, store: &T) {
+        if self.visual_anchor.is_none() {
+            return;
+        }
+        let selection = self.selection(store);
+        self.marked.extend(selection);
+        self.visual_anchor = None;
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.visual_anchor = None;
+        self.marked.clear();
+    }
 }
 
 impl TaskList {
@@ -44,12 +99,14 @@ impl TaskList {
         app: &'a App,
         task_id: TaskIDRef,
         nested_level: usize,
-        task_index: &mut usize,
-    ) -> Vec<Line<'a>> {
+        task_index: &usize,
+        marked: bool,
+        width: usize,
+    ) -> Line<'a> {
         let config = &app.config;
 
         let Some(task) = app.task_store.task(task_id) else {
-            return vec![];
+            return Line::default();
         };
 
         let mut spans = Vec::new();
@@ -60,13 +117,17 @@ impl TaskList {
             Style::default()
         };
 
+        let colour = if Self::is_task_selected(app, task_index) {
+            config.selected_task_colour
+        } else if marked {
+            config.marked_task_colour
+        } else {
+            config.default_task_colour
+        };
+
         let progress = Span::styled(
             if task.progress { "[~] " } else { "[ ] " },
-            style.fg(if Self::is_task_selected(app, task_index) {
-                config.selected_task_colour
-            } else {
-                config.default_task_colour
-            }),
+            style.fg(colour),
         );
         spans.push(progress);
 
@@ -93,16 +154,18 @@ impl TaskList {
             spans.push(priority);
         }
 
-        let content = Span::styled(
-            task.title.split('\n').next().unwrap(),
-            style.fg(if Self::is_task_selected(app, task_index) {
-                config.selected_task_colour
-            } else {
-                config.default_task_colour
-            }),
-        );
+        let content = Span::styled(task.title.split('\n').next().unwrap(), style.fg(colour));
         spans.push(content);
 
+        let (completed, total) = app.task_store.subtask_progress(task_id);
+        if total != 0 {
+            let progress = Span::styled(
+                format!(" {}/{}", completed, total),
+                Style::default().fg(Color::DarkGray),
+            );
+            spans.push(progress);
+        }
+
         for tag in task.iter_tags(app) {
             let tag_label =
                 Span::styled(format!(" ({})", tag.name), Style::default().fg(tag.colour));
@@ -117,34 +180,48 @@ impl TaskList {
             spans.push(due_label);
         }
 
-        *task_index += 1;
-
-        if task.opened {
-            if let Some(subtasks) = subtasks {
-                let mut drawn_tasks = subtasks
-                    .iter()
-                    .flat_map(|sub_task| {
-                        let drawn_task =
-                            Self::draw_task(app, sub_task, nested_level + 1, task_index);
-                        drawn_task
-                    })
-                    .collect_vec();
-                (drawn_tasks).insert(0, Line::from(spans));
-                return drawn_tasks;
+        if config.show_estimates {
+            if let Some(estimate) = app.task_store.rolled_up_estimate(task_id) {
+                let estimate = format!(" {}", utils::format_duration(estimate));
+                let used = spans.iter().map(|span| span.width()).sum::<usize>();
+                let padding = width.saturating_sub(used + estimate.len());
+                spans.push(Span::raw(" ".repeat(padding)));
+                spans.push(Span::styled(estimate, Style::default().fg(Color::DarkGray)));
             }
         }
-        vec![Line::from(spans)]
+
+        Line::from(spans)
     }
 }
 
 impl Component for TaskList {
     fn draw(&self, app: &App, drawer: &mut Drawer) {
-        let mut current_index = 0;
+        // Excludes the borders
+        let width = self.area.width.saturating_sub(2) as usize;
+        let height = self.area.height.saturating_sub(2) as usize;
+        let selected = (COMPONENT_TYPE == app.mode).then_some(app.task_list.selected_index);
+
+        // Only the rows that fit are drawn, scrolled the same way the list
+        // would scroll to keep the selected task at the bottom.
+        let first_row = selected.map_or(0, |f| f.saturating_sub(height.saturating_sub(1)));
+        let marked = if app.task_list.is_selecting() {
+            app.task_list
+                .selection(&app.task_store)
+                .into_iter()
+                .collect()
+        } else {
+            HashSet::new()
+        };
         let tasks: Vec<ListItem> = app
             .task_store
-            .root_tasks()
+            .visible_rows()
             .iter()
-            .flat_map(|task| Self::draw_task(app, task, 0, &mut current_index))
+            .enumerate()
+            .skip(first_row)
+            .take(height)
+            .map(|(row, (task, depth))| {
+                Self::draw_task(app, task, depth, &row, marked.contains(task), width)
+            })
             .map(ListItem::from)
             .collect();
 
@@ -155,11 +232,7 @@ impl Component for TaskList {
         ));
 
         let mut state = ListState::default();
-        state.select(if COMPONENT_TYPE == app.mode {
-            Some(app.task_list.selected_index)
-        } else {
-            None
-        });
+        state.select(selected.map(|f| f - first_row));
 
         drawer.draw_stateful_widget(current, &mut state, self.area);
     }
@@ -173,7 +246,7 @@ impl Component for TaskList {
             app,
             self.area,
             COMPONENT_TYPE,
-            app.task_store.find_tasks_draw_size(),
+            app.task_store.visible_rows().len(),
             mouse_event,
         )
     }
@@ -1,11 +1,13 @@
 use crate::{
-    data::data_store::DataTaskStore,
-    utils::task_position::{cursor_to_completed_task, cursor_to_task},
+    component::completed_list::{day_label, CompletedRow},
+    data::data_store::{DataTaskStore, TaskIDRef},
+    utils::task_position::cursor_to_task,
 };
-use chrono::Local;
+use chrono::{Duration, Local, NaiveDate};
+use itertools::Itertools;
 use tui::{
     layout::{Constraint, Rect},
-    style::Style,
+    style::{Modifier, Style},
     text::{Line, Span},
     widgets::Block,
 };
@@ -63,7 +65,21 @@ impl Viewer {
                 )),
             ),
             (Span::raw("Tags"), tag_names(app, task)),
+            (Span::raw("Comments"), latest_comment(task)),
         ];
+        items.append(&mut tracked_time(task));
+        if let Some(estimate) = app.task_store.rolled_up_estimate(&task_id) {
+            items.push((
+                Span::raw("Estimate"),
+                estimate_comparison(app, task, estimate),
+            ));
+        }
+        if task.pomodoros > 0 {
+            items.push((
+                Span::raw("Pomodoros"),
+                Line::from(task.pomodoros.to_string()),
+            ));
+        }
 
         if let Some(due_date) = task.due_date {
             let num_days = due_date
@@ -81,6 +97,8 @@ impl Viewer {
             ));
         }
 
+        items.append(&mut timestamps(task));
+
         let table = utils::ui::generate_table(
             items,
             block.inner(self.area).width as usize * Self::PERCENT as usize / 100,
@@ -97,13 +115,9 @@ impl Viewer {
         block: Block,
         draw_area: Rect,
         drawer: &mut Drawer,
+        task_id: TaskIDRef,
     ) {
-        let Some(task_id) =
-            cursor_to_completed_task(&app.task_store, app.completed_list.selected_index)
-        else {
-            return;
-        };
-        let Some(completed_task) = &app.task_store.completed_task(&task_id) else {
+        let Some(completed_task) = &app.task_store.completed_task(task_id) else {
             return;
         };
         let completed_time = completed_task
@@ -139,7 +153,15 @@ impl Viewer {
                 )),
             ),
             (Span::raw("Tags"), tag_names(app, &completed_task.task)),
+            (Span::raw("Comments"), latest_comment(&completed_task.task)),
         ];
+        items.append(&mut tracked_time(&completed_task.task));
+        if let Some(estimate) = completed_task.task.estimate {
+            items.push((
+                Span::raw("Estimate"),
+                estimate_comparison(app, &completed_task.task, estimate),
+            ));
+        }
 
         if let Some(due_date) = completed_task.task.due_date {
             let num_days = due_date
@@ -157,6 +179,100 @@ impl Viewer {
             ));
         }
 
+        items.append(&mut timestamps(&completed_task.task));
+
+        let table = utils::ui::generate_table(
+            items,
+            block.inner(draw_area).width as usize * Self::PERCENT as usize / 100,
+        )
+        .block(block)
+        .widths(constraints);
+
+        drawer.draw_widget(table, draw_area)
+    }
+
+    /// A summary of the tasks completed on this day.
+    fn draw_day_viewer(
+        &self,
+        app: &App,
+        block: Block,
+        draw_area: Rect,
+        drawer: &mut Drawer,
+        day: NaiveDate,
+    ) {
+        let store = &app.task_store;
+        let Some((_, tasks)) = app
+            .completed_list
+            .days(store)
+            .into_iter()
+            .find(|(completed_day, _)| *completed_day == day)
+        else {
+            return;
+        };
+        let count: usize = tasks
+            .iter()
+            .map(|id| store.find_completed_task_draw_size(id))
+            .sum();
+
+        let constraints = [
+            Constraint::Percentage(100 - Self::PERCENT),
+            Constraint::Percentage(Self::PERCENT),
+        ];
+        let mut items = vec![
+            (
+                Span::raw("Day"),
+                Line::from(format!(
+                    "{} ({})",
+                    day_label(day, Local::now().date_naive()),
+                    day.format("%d/%m/%y")
+                )),
+            ),
+            (Span::raw("Completed"), Line::from(count.to_string())),
+            (
+                Span::raw("Tasks"),
+                Line::from(
+                    tasks
+                        .iter()
+                        .filter_map(|id| store.completed_task(id))
+                        .map(|completed_task| {
+                            completed_task
+                                .task
+                                .title
+                                .split('\n')
+                                .next()
+                                .unwrap_or_default()
+                        })
+                        .join(", "),
+                ),
+            ),
+        ];
+
+        let tag_counts = tasks
+            .iter()
+            .filter_map(|id| store.completed_task(id))
+            .flat_map(|completed_task| completed_task.task.iter_tags(app))
+            .counts_by(|tag| tag.name.as_str());
+        if !tag_counts.is_empty() {
+            let tags = tag_counts
+                .into_iter()
+                .sorted()
+                .map(|(name, count)| format!("{} {}", name, count))
+                .join(", ");
+            items.push((Span::raw("Tags"), Line::from(tags)));
+        }
+
+        let now = Local::now().naive_local();
+        let tracked = utils::task_position::all_tasks(store)
+            .into_iter()
+            .flat_map(|(_, task)| task.tracked_time_by_day(now, day, day).into_values())
+            .fold(Duration::zero(), |acc, f| acc + f);
+        if !tracked.is_zero() {
+            items.push((
+                Span::raw("Time tracked"),
+                Line::from(utils::format_duration(tracked)),
+            ));
+        }
+
         let table = utils::ui::generate_table(
             items,
             block.inner(draw_area).width as usize * Self::PERCENT as usize / 100,
@@ -186,6 +302,88 @@ fn tag_names<'a>(app: &'a App, task: &'a Task) -> Line<'a> {
     }
 }
 
+/// Compares the estimate with the time that has been tracked.
+fn estimate_comparison<'a>(app: &App, task: &Task, estimate: Duration) -> Line<'a> {
+    let mut spans = vec![Span::raw(utils::format_duration(estimate))];
+    if !task.time_entries.is_empty() {
+        let tracked = task.tracked_time(Local::now().naive_local());
+        spans.push(Span::raw(format!(
+            ", {} tracked ",
+            utils::format_duration(tracked)
+        )));
+        if tracked > estimate {
+            spans.push(Span::styled(
+                format!("({} over)", utils::format_duration(tracked - estimate)),
+                Style::default().fg(app.config.high_priority_colour),
+            ));
+        } else {
+            spans.push(Span::styled(
+                format!("({} left)", utils::format_duration(estimate - tracked)),
+                Style::default().fg(app.config.low_priority_colour),
+            ));
+        }
+    }
+    Line::from(spans)
+}
+
+/// The total time tracked along with how much was tracked each day.
+fn tracked_time<'a>(task: &Task) -> Vec<(Span<'a>, Line<'a>)> {
+    if task.time_entries.is_empty() {
+        return Vec::new();
+    }
+    let now = Local::now().naive_local();
+    let mut total = utils::format_duration(task.tracked_time(now));
+    if task.running_timer().is_some() {
+        total += " (running)";
+    }
+    let per_day = task
+        .tracked_time_by_day(now, NaiveDate::MIN, NaiveDate::MAX)
+        .into_iter()
+        .rev()
+        .map(|(day, duration)| {
+            format!(
+                "{} {}",
+                day.format("%a %d/%m"),
+                utils::format_duration(duration)
+            )
+        })
+        .join(", ");
+    vec![
+        (Span::raw("Time tracked"), Line::from(total)),
+        (Span::raw("Per day"), Line::from(per_day)),
+    ]
+}
+
+/// When the task was added and last changed, if known.
+fn timestamps<'a>(task: &Task) -> Vec<(Span<'a>, Line<'a>)> {
+    [("Created", task.created_at), ("Updated", task.updated_at)]
+        .into_iter()
+        .filter_map(|(name, time)| {
+            Some((
+                Span::raw(name),
+                Line::from(time?.format("%d/%m/%y %-I:%M %p").to_string()),
+            ))
+        })
+        .collect()
+}
+
+fn latest_comment<'a>(task: &'a Task) -> Line<'a> {
+    let Some(comment) = task.comments.last() else {
+        return Line::from("None");
+    };
+    Line::from(vec![
+        Span::raw(format!("{} - ", task.comments.len())),
+        Span::raw(comment.content.as_str()),
+        Span::styled(
+            comment
+                .posted_at
+                .format(" (%d/%m/%y %-I:%M %p)")
+                .to_string(),
+            Style::default().add_modifier(Modifier::DIM),
+        ),
+    ])
+}
+
 impl Component for Viewer {
     fn draw(&self, app: &App, drawer: &mut Drawer) {
         let theme = &app.config;
@@ -200,13 +398,15 @@ impl Component for Viewer {
                     drawer.draw_widget(block, draw_area);
                 }
             }
-            Mode::CompletedTasks => {
-                if !app.task_store.completed_root_tasks().is_empty() {
-                    self.draw_completed_task_viewer(app, block, draw_area, drawer)
-                } else {
-                    drawer.draw_widget(block, draw_area);
+            Mode::CompletedTasks => match app.completed_list.selected_row(&app.task_store) {
+                Some(CompletedRow::Day(day)) => {
+                    self.draw_day_viewer(app, block, draw_area, drawer, day)
                 }
-            }
+                Some(CompletedRow::Task(task_id, _)) => {
+                    self.draw_completed_task_viewer(app, block, draw_area, drawer, &task_id)
+                }
+                None => drawer.draw_widget(block, draw_area),
+            },
             Mode::Overlay => {
                 if !app.task_store.root_tasks().is_empty() {
                     self.draw_task_viewer(app, block, drawer)
@@ -214,6 +414,7 @@ impl Component for Viewer {
                     drawer.draw_widget(block, draw_area);
                 }
             }
+            Mode::Stats => drawer.draw_widget(block, draw_area),
         }
     }
 
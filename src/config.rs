@@ -7,12 +7,15 @@ use tui::{
     widgets::{Block, BorderType, Borders},
 };
 
-use crate::framework::key::Key;
+use crate::{
+    credentials::{token_source_parser, TokenSource},
+    framework::key::Key,
+};
 
 #[derive(Deserialize, Serialize)]
 pub enum DataSource {
     Json,
-    Todoist(String),
+    Todoist(#[serde(with = "token_source_parser")] TokenSource),
 }
 
 #[derive(Deserialize, Serialize)]
@@ -35,6 +38,9 @@ pub struct Config {
 
     #[serde(with = "color_parser")]
     pub default_task_colour: Color,
+    /// The colour of the tasks that are marked or in the visual selection.
+    #[serde(with = "color_parser")]
+    pub marked_task_colour: Color,
 
     pub use_fuzzy: bool,
     pub vim_mode: bool,
@@ -57,9 +63,14 @@ pub struct Config {
     pub change_priority_key: Key,
     pub restore_key: Key,
     pub refresh_key: Key,
+    pub comments_key: Key,
+    pub timer_key: Key,
+    pub pomodoro_key: Key,
+    pub estimate_key: Key,
 
     pub tasks_menu_key: Key,
     pub completed_tasks_menu_key: Key,
+    pub stats_menu_key: Key,
     pub open_help_key: Key,
     pub quit_key: Key,
 
@@ -71,6 +82,12 @@ pub struct Config {
     pub move_subtask_level_up: Key,
     pub move_subtask_level_down: Key,
 
+    pub visual_mode_key: Key,
+    pub visual_line_mode_key: Key,
+    pub select_all_key: Key,
+    pub mark_key: Key,
+    pub clear_selection_key: Key,
+
     #[serde(with = "border_parser")]
     pub border_type: BorderType,
 
@@ -84,7 +101,25 @@ pub struct Config {
     pub low_priority_display: String,
     pub none_priority_display: String,
 
+    /// Shows the estimate of each task on the right of the task list.
+    pub show_estimates: bool,
+    /// Completes a task once its last open subtask is completed.
+    pub auto_complete_parent: bool,
+
     pub data_source: DataSource,
+    /// How often, in seconds, to poll the data source for remote changes.
+    /// Set to 0 to only sync when changes are made or on refresh.
+    pub sync_interval: u64,
+
+    /// The length of a pomodoro in minutes.
+    pub pomodoro_work_minutes: u64,
+    /// The length of the break after each pomodoro in minutes.
+    pub pomodoro_break_minutes: u64,
+    /// Rings the terminal bell at the end of each pomodoro phase.
+    pub pomodoro_bell: bool,
+    /// Runs this command at the end of each pomodoro phase, the message is
+    /// available in `$DOTODO_MESSAGE` (eg: `notify-send "$DOTODO_MESSAGE"`).
+    pub pomodoro_command: Option<String>,
 
     pub debug: bool,
 }
@@ -100,6 +135,7 @@ impl Default for Config {
             low_priority_colour: Color::Green,
             none_priority_colour: Color::default(),
             default_task_colour: Color::default(),
+            marked_task_colour: Color::Yellow,
             use_fuzzy: true,
             vim_mode: false,
             up_keys: [
@@ -127,9 +163,14 @@ impl Default for Config {
             change_priority_key: Key::new(KeyCode::Char('p'), KeyModifiers::NONE),
             restore_key: Key::new(KeyCode::Char('r'), KeyModifiers::NONE),
             refresh_key: Key::new(KeyCode::Char('r'), KeyModifiers::NONE),
+            comments_key: Key::new(KeyCode::Char('C'), KeyModifiers::NONE),
+            timer_key: Key::new(KeyCode::Char('T'), KeyModifiers::NONE),
+            pomodoro_key: Key::new(KeyCode::Char('P'), KeyModifiers::NONE),
+            estimate_key: Key::new(KeyCode::Char('E'), KeyModifiers::NONE),
 
             tasks_menu_key: Key::new(KeyCode::Char('1'), KeyModifiers::NONE),
             completed_tasks_menu_key: Key::new(KeyCode::Char('2'), KeyModifiers::NONE),
+            stats_menu_key: Key::new(KeyCode::Char('3'), KeyModifiers::NONE),
             tag_menu: Key::new(KeyCode::Char('t'), KeyModifiers::NONE),
             open_help_key: Key::new(KeyCode::Char('x'), KeyModifiers::NONE),
             quit_key: Key::new(KeyCode::Char('q'), KeyModifiers::NONE),
@@ -141,6 +182,12 @@ impl Default for Config {
             move_subtask_level_up: Key::new(KeyCode::Char('L'), KeyModifiers::NONE),
             move_subtask_level_down: Key::new(KeyCode::Char('H'), KeyModifiers::NONE),
 
+            visual_mode_key: Key::new(KeyCode::Char('v'), KeyModifiers::NONE),
+            visual_line_mode_key: Key::new(KeyCode::Char('V'), KeyModifiers::NONE),
+            select_all_key: Key::new(KeyCode::Char('a'), KeyModifiers::CONTROL),
+            mark_key: Key::new(KeyCode::Char('m'), KeyModifiers::NONE),
+            clear_selection_key: Key::new(KeyCode::Esc, KeyModifiers::NONE),
+
             border_type: BorderType::Plain,
             selected_cursor: String::from(" > "),
             nested_padding: String::from(" │  "),
@@ -152,8 +199,16 @@ impl Default for Config {
             low_priority_display: String::from("!   "),
             none_priority_display: String::from("    "),
 
+            show_estimates: false,
+            auto_complete_parent: false,
+
             debug: false,
             data_source: DataSource::Json,
+            sync_interval: 30,
+            pomodoro_work_minutes: 25,
+            pomodoro_break_minutes: 5,
+            pomodoro_bell: true,
+            pomodoro_command: None,
         }
     }
 }
@@ -0,0 +1,137 @@
+use std::{
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::{error::AppError, utils::shell};
+
+/// The environment variable that is used when a token can no longer be
+/// stored in the config.
+pub const TOKEN_ENV: &str = "DOTODO_TODOIST_TOKEN";
+
+/// Where the API token of a data source is read from.
+#[derive(Deserialize, Serialize, Clone, Debug, PartialEq)]
+pub enum TokenSource {
+    /// Read from this environment variable.
+    Env(String),
+    /// Read from this file, which should only be readable by the user.
+    File(PathBuf),
+    /// Read from the stdout of this command (eg: `pass show todoist`).
+    Command(String),
+    /// A token that was written directly into the config. This is moved into
+    /// the credentials file when loading and is never written back, the
+    /// config is left as it is if moving it fails.
+    #[serde(skip)]
+    Plain(String),
+}
+
+impl TokenSource {
+    pub fn token(&self) -> Result<String, AppError> {
+        let token = match self {
+            TokenSource::Env(var) => std::env::var(var).map_err(|_| {
+                AppError::InvalidToken(format!("the environment variable {var} is not set"))
+            })?,
+            TokenSource::File(path) => {
+                warn_if_readable(path);
+                fs::read_to_string(path)?
+            }
+            TokenSource::Command(command) => {
+                let output = shell(command).output()?;
+                if !output.status.success() {
+                    return Err(AppError::InvalidToken(format!(
+                        "`{command}` exited with {}",
+                        output.status
+                    )));
+                }
+                String::from_utf8_lossy(&output.stdout).into_owned()
+            }
+            TokenSource::Plain(token) => token.clone(),
+        };
+
+        let token = token.trim();
+        if token.is_empty() {
+            return Err(AppError::InvalidToken(String::from("the token is empty")));
+        }
+        Ok(token.to_string())
+    }
+}
+
+#[cfg(unix)]
+fn warn_if_readable(path: &Path) {
+    use std::os::unix::fs::PermissionsExt;
+
+    if let Ok(metadata) = fs::metadata(path) {
+        if metadata.permissions().mode() & 0o077 != 0 {
+            eprintln!(
+                "The credentials file '{}' is readable by other users, consider running `chmod 600` on it.",
+                path.display()
+            );
+        }
+    }
+}
+
+#[cfg(not(unix))]
+fn warn_if_readable(_: &Path) {}
+
+/// Writes the token to a file that only the current user is able to read.
+pub fn write_credentials(path: &Path, token: &str) -> Result<(), AppError> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::{OpenOptionsExt, PermissionsExt};
+
+        options.mode(0o600);
+        // The mode is only used when creating the file.
+        if path.exists() {
+            fs::set_permissions(path, fs::Permissions::from_mode(0o600))?;
+        }
+    }
+
+    let mut file = options.open(path)?;
+    file.write_all(token.as_bytes())?;
+    Ok(())
+}
+
+pub mod token_source_parser {
+    use super::*;
+
+    pub fn serialize<S>(source: &TokenSource, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match source {
+            TokenSource::Plain(_) => Err(serde::ser::Error::custom(
+                "A token written directly in the config is never saved",
+            )),
+            source => source.serialize(serializer),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<TokenSource, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum MultipleType {
+            Source(TokenSource),
+            Plain(String),
+        }
+
+        match MultipleType::deserialize(deserializer)
+            .map_err(|_| serde::de::Error::custom("Failed to parse the token source"))?
+        {
+            MultipleType::Source(source) => Ok(source),
+            MultipleType::Plain(token) => Ok(TokenSource::Plain(token)),
+        }
+    }
+}
@@ -1,12 +1,15 @@
-use std::collections::HashMap;
+use std::{collections::HashMap, sync::mpsc::Receiver};
 
-use chrono::NaiveDateTime;
+use chrono::{Duration, NaiveDateTime};
 use enum_dispatch::enum_dispatch;
 
+use crate::error::StoreError;
 use crate::task::{CompletedTask, FindParentResult, Tag, Task};
 
 use super::json_data_store::JsonDataStore;
+use super::store_event::StoreEvent;
 use super::todoist::todoist_data_store::TodoistDataStore;
+use super::visible_rows::VisibleRows;
 
 pub type TaskID = String;
 pub type TaskIDRef<'a> = &'a str;
@@ -23,35 +26,25 @@ pub enum DataTaskStoreKind {
 /// Handles how tasks are stored
 #[enum_dispatch(DataTaskStoreKind)]
 pub trait DataTaskStore {
-    /// Returns this mutable task with this id.
-    fn modify_task<F, T: FnOnce(&mut Task) -> F>(&mut self, id: TaskIDRef, closure: T)
-        -> Option<F>;
-
-    /// Notify the server that this task has been modified.
-    fn update_task(&mut self, id: TaskIDRef);
+    /// Changes the task with this id, sending [`StoreEvent::TaskUpdated`].
+    fn modify_task<F, T: FnOnce(&mut Task) -> F>(
+        &mut self,
+        id: TaskIDRef,
+        closure: T,
+    ) -> Result<F, StoreError>;
 
     /// Returns the task with this id.
     fn task(&self, id: TaskIDRef) -> Option<&Task>;
 
-    /// Returns this mutable completed task with this id.
-    #[allow(dead_code)]
-    fn completed_task_mut(&mut self, id: TaskIDRef) -> Option<&mut CompletedTask>;
-
     /// Returns the completed task with this id.
     fn completed_task(&self, id: TaskIDRef) -> Option<&CompletedTask>;
 
-    /// Deletes the task with this id.
-    fn delete_task(&mut self, id: TaskIDRef) -> Option<Task>;
+    /// Deletes the task with this id, along with its subtasks.
+    fn delete_task(&mut self, id: TaskIDRef) -> Result<Task, StoreError>;
 
     /// Gets the parent of this task with this id.
     fn find_parent(&self, id: TaskIDRef) -> Option<FindParentResult>;
 
-    /// Returns the subtasks of a task if `id` is some
-    /// Otherwise returns the global tasks.
-    ///
-    /// * `id` - The id to get, if None, will return root tasks
-    fn subtasks_mut(&mut self, id: Option<TaskIDRef>) -> Option<&mut Vec<TaskID>>;
-
     /// Returns the subtasks of a task if `id` is some
     /// Otherwise returns the global tasks.
     ///
@@ -70,19 +63,23 @@ pub trait DataTaskStore {
     /// * `id` - The id to get, if None, will return root tasks
     fn completed_root_tasks(&self) -> &Vec<TaskID>;
 
+    /// Returns the subtasks that were completed along with the completed
+    /// task with this id.
+    fn completed_subtasks(&self, id: TaskIDRef) -> Option<&Vec<TaskID>>;
+
     fn delete_tag(&mut self, tag_id: TaskIDRef);
 
     /// Sorts all the task based on priority
     fn sort(&mut self);
 
-    /// Adds a task to this data store
+    /// Adds a task to this data store, returning the id it was given.
     ///
     /// * `task` - The task to be added.
     /// * `parent` - The parent of the task to be added.
-    fn add_task(&mut self, task: Task, parent: Option<TaskIDRef>);
+    fn add_task(&mut self, task: Task, parent: Option<TaskIDRef>) -> Result<TaskID, StoreError>;
 
     /// Fetches data from the data source
-    fn refresh(&mut self);
+    fn refresh(&mut self) -> Result<(), StoreError>;
 
     /// Saves the data to the data source
     fn save(&self);
@@ -91,7 +88,11 @@ pub trait DataTaskStore {
     ///
     /// * `id` - The id of the task to be moved
     /// * `parent` - If specified, where the task should be moved to
-    /// * `order` - What place should the task be placed within the order.
+    /// * `order` - What place should the task be placed within the order,
+    ///   past the end of the list places it last.
+    ///
+    /// Moving a task below itself or one of its subtasks is a
+    /// [`StoreError::CycleDetected`] and leaves the tree unchanged.
     ///
     /// FIXME: global task moving?
     fn move_task(
@@ -100,19 +101,118 @@ pub trait DataTaskStore {
         parent: Option<TaskID>,
         order: usize,
         global: Option<()>,
-    );
+    ) -> Result<(), StoreError>;
+
+    /// The number of rows the task with this id takes, including its subtasks
+    /// when it is opened.
+    fn find_task_draw_size(&self, id: TaskIDRef) -> usize {
+        let Some(task) = self.task(id) else {
+            return 0;
+        };
+        if !task.opened {
+            return 1;
+        }
+        self.subtasks(id).map_or(0, |subtasks| {
+            subtasks
+                .iter()
+                .map(|subtask| self.find_task_draw_size(subtask))
+                .sum()
+        }) + 1
+    }
+
+    /// The open tasks in the order they are drawn, this is kept until the
+    /// tree changes or a task is opened or closed.
+    fn visible_rows(&self) -> &VisibleRows;
+
+    fn find_tasks_draw_size(&self) -> usize {
+        self.visible_rows().len()
+    }
+
+    /// The estimate of this task added to the estimates of its subtasks, this
+    /// follows the same recursion as `find_task_draw_size` but includes
+    /// subtasks that are closed.
+    fn rolled_up_estimate(&self, id: TaskIDRef) -> Option<Duration> {
+        let task = self.task(id)?;
+        self.subtasks(id)
+            .into_iter()
+            .flatten()
+            .filter_map(|subtask| self.rolled_up_estimate(subtask))
+            .chain(task.estimate)
+            .reduce(|acc, f| acc + f)
+    }
+
+    /// The number of completed descendants of this task and the total number
+    /// of descendants, counting both open and completed tasks.
+    fn subtask_progress(&self, id: TaskIDRef) -> (usize, usize) {
+        self.subtasks(id)
+            .into_iter()
+            .flatten()
+            .chain(self.completed_subtasks(id).into_iter().flatten())
+            .chain(self.visible_rows().completed_children(id))
+            .map(|subtask| {
+                let (completed, total) = self.subtask_progress(subtask);
+                let is_completed = self.completed_task(subtask).is_some();
+                (completed + usize::from(is_completed), total + 1)
+            })
+            .fold((0, 0), |acc, f| (acc.0 + f.0, acc.1 + f.1))
+    }
+
+    /// The number of rows the completed task with this id takes, including
+    /// the subtasks that were completed with it.
+    fn find_completed_task_draw_size(&self, id: TaskIDRef) -> usize {
+        self.completed_subtasks(id).map_or(0, |subtasks| {
+            subtasks
+                .iter()
+                .map(|subtask| self.find_completed_task_draw_size(subtask))
+                .sum()
+        }) + 1
+    }
+
+    fn find_completed_tasks_draw_size(&self) -> usize {
+        self.completed_root_tasks()
+            .iter()
+            .map(|t| self.find_completed_task_draw_size(t))
+            .sum()
+    }
+
+    /// Completes the task with this id along with its subtasks, which are kept
+    /// below it.
+    fn complete_task(
+        &mut self,
+        id: TaskIDRef,
+        time_completed: NaiveDateTime,
+    ) -> Result<(), StoreError>;
 
-    fn find_task_draw_size(&self, id: TaskIDRef) -> usize;
+    /// Restores the completed task with this id, along with the subtasks that
+    /// were completed with it, to its original parent and position.
+    fn restore(&mut self, id: TaskIDRef) -> Result<(), StoreError>;
 
-    fn find_tasks_draw_size(&self) -> usize;
+    /// Adds a comment to the task with this id.
+    fn add_comment(
+        &mut self,
+        id: TaskIDRef,
+        content: String,
+        posted_at: NaiveDateTime,
+    ) -> Result<(), StoreError>;
 
-    fn complete_task(&mut self, id: TaskIDRef, time_completed: NaiveDateTime);
+    /// Changes the content of a comment on the task with this id.
+    fn update_comment(
+        &mut self,
+        id: TaskIDRef,
+        comment_id: TaskIDRef,
+        content: String,
+    ) -> Result<(), StoreError>;
 
-    fn restore(&mut self, id: TaskIDRef);
+    /// Loads older completed tasks, if they are not all loaded yet.
+    fn load_completed_history(&mut self);
 
     fn tags(&self) -> &HashMap<String, Tag>;
 
-    fn tags_mut(&mut self) -> &mut HashMap<String, Tag>;
+    /// Adds or replaces the tag with this id.
+    fn set_tag(&mut self, tag_id: TaskIDRef, tag: Tag);
+
+    /// Returns a receiver that is sent each change made through this trait.
+    fn subscribe(&mut self) -> Receiver<StoreEvent>;
 
     fn is_syncing(&self) -> bool;
 }
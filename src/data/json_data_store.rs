@@ -1,15 +1,22 @@
-use std::{cmp, collections::HashMap};
+use std::{cell::OnceCell, collections::HashMap, sync::mpsc::Receiver};
 
-use chrono::NaiveDateTime;
+use chrono::{Local, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 
 use crate::{
     data_io,
-    task::{CompletedTask, FindParentResult, Tag, Task},
+    error::StoreError,
+    task::{Comment, CompletedTask, FindParentResult, Tag, Task},
     utils,
 };
 
-use super::data_store::{DataTaskStore, TaskID, TaskIDRef};
+use super::{
+    data_store::{DataTaskStore, TaskID, TaskIDRef},
+    parent_index::ParentIndex,
+    store_event::{StoreEvent, Subscribers},
+    task_tree::TaskTree,
+    visible_rows::VisibleRows,
+};
 
 #[derive(Default, Clone, Deserialize, Serialize)]
 pub struct JsonDataStore {
@@ -18,8 +25,38 @@ pub struct JsonDataStore {
     pub subtasks: HashMap<TaskID, Vec<TaskID>>,
     pub root: Vec<TaskID>,
     pub completed_root: Vec<TaskID>,
+    /// The subtasks of completed tasks that were completed with them.
+    #[serde(default)]
+    pub completed_subtasks: HashMap<TaskID, Vec<TaskID>>,
     pub tags: HashMap<String, Tag>,
-    pub task_count: usize,
+    /// Built from `subtasks` when the store is loaded.
+    #[serde(skip)]
+    pub parent_index: ParentIndex,
+    #[serde(skip)]
+    pub visible_rows: OnceCell<VisibleRows>,
+    #[serde(skip)]
+    pub subscribers: Subscribers,
+}
+
+impl JsonDataStore {
+    pub fn tree(&mut self) -> TaskTree<'_> {
+        TaskTree {
+            tasks: &mut self.tasks,
+            completed_tasks: &mut self.completed_tasks,
+            subtasks: &mut self.subtasks,
+            root: &mut self.root,
+            completed_root: &mut self.completed_root,
+            completed_subtasks: &mut self.completed_subtasks,
+            parent_index: &mut self.parent_index,
+            visible_rows: &mut self.visible_rows,
+        }
+    }
+
+    /// Builds the parent index again and drops the visible rows, after the
+    /// lists of tasks were changed directly.
+    pub fn rebuild_indexes(&mut self) {
+        self.tree().rebuild_indexes();
+    }
 }
 
 impl DataTaskStore for JsonDataStore {
@@ -27,60 +64,30 @@ impl DataTaskStore for JsonDataStore {
         &mut self,
         id: TaskIDRef,
         closure: T,
-    ) -> Option<F> {
-        self.tasks.get_mut(id).map(closure)
-    }
-
-    fn update_task(&mut self, _: TaskIDRef) {
-        // noop, we already update json data
+    ) -> Result<F, StoreError> {
+        let result = self.tree().modify(id, closure)?;
+        self.subscribers
+            .send(StoreEvent::TaskUpdated(id.to_string()));
+        Ok(result)
     }
 
     fn task(&self, id: TaskIDRef) -> Option<&Task> {
         self.tasks.get(id)
     }
 
-    fn completed_task_mut(&mut self, id: TaskIDRef) -> Option<&mut CompletedTask> {
-        self.completed_tasks.get_mut(id)
-    }
-
     fn completed_task(&self, id: TaskIDRef) -> Option<&CompletedTask> {
         self.completed_tasks.get(id)
     }
 
-    fn delete_task(&mut self, id: TaskIDRef) -> Option<Task> {
-        self.root.retain(|f| f != id);
-        self.subtasks
-            .values_mut()
-            .for_each(|val| val.retain(|f| f != id));
-        self.tasks.remove(id)
+    fn delete_task(&mut self, id: TaskIDRef) -> Result<Task, StoreError> {
+        let task = self.tree().delete(id)?;
+        self.subscribers
+            .send(StoreEvent::TaskDeleted(id.to_string()));
+        Ok(task)
     }
 
     fn find_parent(&self, id: TaskIDRef) -> Option<FindParentResult> {
-        let parent = self
-            .subtasks
-            .iter()
-            .find(|(_, subs)| subs.contains(&id.to_string()))
-            .map(|p| p.0);
-        let local_index = if let Some(p) = parent {
-            self.subtasks
-                .get(p)
-                .map_or(Some(0), |f| f.iter().position(|t| t == id))
-        } else {
-            self.root.iter().position(|t| t == id)
-        };
-        Some(FindParentResult {
-            task_local_offset: local_index?,
-            parent_id: parent.cloned(),
-        })
-    }
-
-    // FIXME: might be able to wrap this in a &mut Vec<Task> perhaps?
-    fn subtasks_mut(&mut self, id: Option<TaskIDRef>) -> Option<&mut Vec<TaskID>> {
-        if let Some(id) = id {
-            self.subtasks.get_mut(id)
-        } else {
-            Some(&mut self.root)
-        }
+        self.parent_index.find(id, &self.root, &self.subtasks)
     }
 
     fn subtasks(&self, id: TaskIDRef) -> Option<&Vec<TaskID>> {
@@ -95,6 +102,10 @@ impl DataTaskStore for JsonDataStore {
         &self.completed_root
     }
 
+    fn completed_subtasks(&self, id: TaskIDRef) -> Option<&Vec<TaskID>> {
+        self.completed_subtasks.get(id)
+    }
+
     fn delete_tag(&mut self, tag_id: TaskIDRef) {
         self.tags.remove(tag_id);
         for task in &mut self.tasks.values_mut() {
@@ -103,30 +114,26 @@ impl DataTaskStore for JsonDataStore {
         for completed_task in &mut self.completed_tasks.values_mut() {
             completed_task.task.tags.retain(|f| f != tag_id);
         }
+        self.subscribers
+            .send(StoreEvent::TagChanged(tag_id.to_string()));
     }
 
     fn sort(&mut self) {
-        self.root
-            .sort_by_key(|f| cmp::Reverse(self.tasks[f].priority));
-        for subtasks in self.subtasks.values_mut() {
-            subtasks.sort_by_key(|f| cmp::Reverse(self.tasks[f].priority));
-        }
+        self.tree().sort();
     }
 
-    fn add_task(&mut self, task: Task, parent: Option<TaskIDRef>) {
-        let parents = if let Some(parent_id) = parent {
-            self.subtasks.entry(parent_id.to_string()).or_default()
-        } else {
-            &mut self.root
-        };
-        let key = (self.task_count + 1).to_string();
-        self.task_count += 1;
-        self.tasks.insert(key.clone(), task);
-        parents.push(key);
+    fn add_task(&mut self, task: Task, parent: Option<TaskIDRef>) -> Result<TaskID, StoreError> {
+        let key = self.tree().add(task, parent)?;
+        self.subscribers.send(StoreEvent::TaskAdded {
+            id: key.clone(),
+            parent: parent.map(str::to_string),
+        });
+        Ok(key)
     }
 
-    fn refresh(&mut self) {
-        todo!()
+    fn refresh(&mut self) -> Result<(), StoreError> {
+        // The local data is only changed by dotodo, there is nothing to refresh
+        Ok(())
     }
 
     fn save(&self) {
@@ -139,83 +146,111 @@ impl DataTaskStore for JsonDataStore {
         parent: Option<TaskID>,
         order: usize,
         global: Option<()>,
-    ) {
-        let hash_map = &mut self.subtasks;
-        let subtasks = if let Some((_, subtasks)) = hash_map
-            .iter_mut()
-            .find(|(_, subtasks)| subtasks.contains(&id.to_string()))
-        {
-            subtasks
-        } else {
-            &mut self.root
-        };
-        subtasks.retain(|f| f != id);
-        if let Some(p) = parent {
-            if let Some(subtasks) = self.subtasks_mut(Some(&p)) {
-                subtasks.insert(order, id.to_string());
-            } else {
-                self.subtasks.insert(p, vec![id.to_string()]);
-            }
-        } else if global.is_some() {
-            self.root.insert(order, id.to_string());
-        } else {
-            subtasks.insert(order, id.to_string());
-        }
+    ) -> Result<(), StoreError> {
+        let (from, to) = self.tree().move_task(id, parent, order, global)?;
+        self.subscribers.send(StoreEvent::TaskMoved {
+            id: id.to_string(),
+            from,
+            to,
+        });
+        Ok(())
     }
 
-    fn find_task_draw_size(&self, task_id: TaskIDRef) -> usize {
-        if let Some(task) = self.task(task_id) {
-            return if !task.opened {
-                0
-            } else {
-                self.subtasks(task_id)
-                    .map_or(0, |f| f.iter().map(|k| self.find_task_draw_size(k)).sum())
-            } + 1;
-        }
-        0
-    }
-
-    fn find_tasks_draw_size(&self) -> usize {
-        self.root_tasks()
-            .iter()
-            .map(|t| self.find_task_draw_size(t))
-            .sum()
-    }
-
-    fn complete_task(&mut self, id: TaskIDRef, time_completed: NaiveDateTime) {
-        self.root.retain(|f| f != id);
-        self.subtasks
-            .values_mut()
-            .for_each(|subtasks| subtasks.retain(|f| f != id));
-        if let Some(task) = self.tasks.remove(id) {
-            self.completed_tasks.insert(
-                id.to_string(),
-                CompletedTask::from_task(task, time_completed),
-            );
-            self.completed_root.push(id.to_string());
-        }
+    fn visible_rows(&self) -> &VisibleRows {
+        self.visible_rows.get_or_init(|| VisibleRows::new(self))
     }
 
-    fn restore(&mut self, id: TaskIDRef) {
-        self.completed_root.retain(|f| f != id);
-        self.subtasks
-            .values_mut()
-            .for_each(|subtasks| subtasks.retain(|f| f != id));
-        if let Some(task) = self.completed_tasks.remove(id) {
-            self.tasks.insert(id.to_string(), task.task);
-            self.root.push(id.to_string());
-        }
+    fn complete_task(
+        &mut self,
+        id: TaskIDRef,
+        time_completed: NaiveDateTime,
+    ) -> Result<(), StoreError> {
+        self.tree().complete(id, time_completed)?;
+        self.subscribers
+            .send(StoreEvent::TaskCompleted(id.to_string()));
+        Ok(())
+    }
+
+    fn restore(&mut self, id: TaskIDRef) -> Result<(), StoreError> {
+        self.tree().restore(id)?;
+        self.subscribers
+            .send(StoreEvent::TaskRestored(id.to_string()));
+        Ok(())
     }
 
     fn tags(&self) -> &HashMap<String, Tag> {
         &self.tags
     }
 
-    fn tags_mut(&mut self) -> &mut HashMap<String, Tag> {
-        &mut self.tags
+    fn set_tag(&mut self, tag_id: TaskIDRef, tag: Tag) {
+        self.tags.insert(tag_id.to_string(), tag);
+        self.subscribers
+            .send(StoreEvent::TagChanged(tag_id.to_string()));
+    }
+
+    fn add_comment(
+        &mut self,
+        id: TaskIDRef,
+        content: String,
+        posted_at: NaiveDateTime,
+    ) -> Result<(), StoreError> {
+        if content.trim().is_empty() {
+            return Err(StoreError::EmptyComment);
+        }
+        let task = self
+            .tasks
+            .get_mut(id)
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        task.touch(Local::now().naive_local());
+        let comment_id = uuid::Uuid::new_v4().to_string();
+        task.comments.push(Comment {
+            id: comment_id.clone(),
+            content,
+            posted_at,
+        });
+        self.subscribers.send(StoreEvent::CommentAdded {
+            id: id.to_string(),
+            comment_id,
+        });
+        Ok(())
+    }
+
+    fn update_comment(
+        &mut self,
+        id: TaskIDRef,
+        comment_id: TaskIDRef,
+        content: String,
+    ) -> Result<(), StoreError> {
+        if content.trim().is_empty() {
+            return Err(StoreError::EmptyComment);
+        }
+        let task = self
+            .tasks
+            .get_mut(id)
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        let comment = task
+            .comments
+            .iter_mut()
+            .find(|f| f.id == comment_id)
+            .ok_or_else(|| StoreError::CommentNotFound(comment_id.to_string()))?;
+        comment.content = content;
+        task.touch(Local::now().naive_local());
+        self.subscribers.send(StoreEvent::CommentUpdated {
+            id: id.to_string(),
+            comment_id: comment_id.to_string(),
+        });
+        Ok(())
+    }
+
+    fn load_completed_history(&mut self) {
+        // noop, the whole history is stored locally
     }
 
     fn is_syncing(&self) -> bool {
         false
     }
+
+    fn subscribe(&mut self) -> Receiver<StoreEvent> {
+        self.subscribers.subscribe()
+    }
 }
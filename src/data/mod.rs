@@ -1,3 +1,7 @@
 pub mod data_store;
 pub mod json_data_store;
+pub mod parent_index;
+pub mod store_event;
+pub mod task_tree;
 pub mod todoist;
+pub mod visible_rows;
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use crate::task::FindParentResult;
+
+use super::data_store::{TaskID, TaskIDRef};
+
+/// The parent of each subtask, so a task can be found in the tree without
+/// searching every list of subtasks. Root tasks have no entry.
+///
+/// The stores keep this up to date as they change the tree, `find` checks the
+/// entry against the lists so a change made to them directly (eg: by a sync)
+/// is still found, at the cost of a search.
+#[derive(Default, Clone)]
+pub struct ParentIndex {
+    parents: HashMap<TaskID, TaskID>,
+}
+
+impl ParentIndex {
+    pub fn new(subtasks: &HashMap<TaskID, Vec<TaskID>>) -> Self {
+        let parents = subtasks
+            .iter()
+            .flat_map(|(parent, subtasks)| {
+                subtasks
+                    .iter()
+                    .map(move |subtask| (subtask.clone(), parent.clone()))
+            })
+            .collect();
+        Self { parents }
+    }
+
+    /// Records that the task is now below this parent, or a root task if there
+    /// is no parent.
+    pub fn set(&mut self, id: TaskIDRef, parent: Option<TaskIDRef>) {
+        if let Some(parent) = parent {
+            self.parents.insert(id.to_string(), parent.to_string());
+        } else {
+            self.parents.remove(id);
+        }
+    }
+
+    /// Records that each of the subtasks is below this parent.
+    pub fn set_all(&mut self, subtasks: &[TaskID], parent: TaskIDRef) {
+        for subtask in subtasks {
+            self.set(subtask, Some(parent));
+        }
+    }
+
+    /// Forgets the task, once it is no longer in the tree.
+    pub fn remove(&mut self, id: TaskIDRef) {
+        self.parents.remove(id);
+    }
+
+    /// Finds the parent of the task and its position within the parent's
+    /// subtasks, or within the root tasks.
+    pub fn find(
+        &self,
+        id: TaskIDRef,
+        root: &[TaskID],
+        subtasks: &HashMap<TaskID, Vec<TaskID>>,
+    ) -> Option<FindParentResult> {
+        let position = |list: &[TaskID]| list.iter().position(|f| f == id);
+        let indexed = match self.parents.get(id) {
+            Some(parent) => subtasks
+                .get(parent)
+                .and_then(|list| position(list))
+                .map(|offset| (Some(parent.clone()), offset)),
+            None => position(root).map(|offset| (None, offset)),
+        };
+        let (parent_id, task_local_offset) = indexed.or_else(|| {
+            subtasks
+                .iter()
+                .find_map(|(parent, list)| Some((Some(parent.clone()), position(list)?)))
+                .or_else(|| Some((None, position(root)?)))
+        })?;
+        Some(FindParentResult {
+            parent_id,
+            task_local_offset,
+        })
+    }
+}
@@ -0,0 +1,59 @@
+use std::sync::mpsc::{channel, Receiver, Sender};
+
+use super::data_store::TaskID;
+
+/// A change made to the tasks or tags of a store, sent to each subscriber
+/// after the change is made.
+///
+/// Changes made to the lists directly (eg: by a sync or a repair) are not sent.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StoreEvent {
+    TaskAdded {
+        id: TaskID,
+        parent: Option<TaskID>,
+    },
+    /// The task was changed through `modify_task`.
+    TaskUpdated(TaskID),
+    /// The task was moved from one parent to another, or within the same
+    /// parent. A parent of None is the root tasks.
+    TaskMoved {
+        id: TaskID,
+        from: Option<TaskID>,
+        to: Option<TaskID>,
+    },
+    /// The task was completed, along with its subtasks.
+    TaskCompleted(TaskID),
+    /// The task was restored, along with the subtasks completed with it.
+    TaskRestored(TaskID),
+    TaskDeleted(TaskID),
+    CommentAdded {
+        id: TaskID,
+        comment_id: String,
+    },
+    CommentUpdated {
+        id: TaskID,
+        comment_id: String,
+    },
+    /// The tag with this id was added, changed or deleted.
+    TagChanged(String),
+}
+
+/// The receivers that are sent the events of a store, receivers that were
+/// dropped are forgotten the next time an event is sent.
+#[derive(Default, Clone)]
+pub struct Subscribers {
+    senders: Vec<Sender<StoreEvent>>,
+}
+
+impl Subscribers {
+    pub fn subscribe(&mut self) -> Receiver<StoreEvent> {
+        let (sender, receiver) = channel();
+        self.senders.push(sender);
+        receiver
+    }
+
+    pub fn send(&mut self, event: StoreEvent) {
+        self.senders
+            .retain(|sender| sender.send(event.clone()).is_ok());
+    }
+}
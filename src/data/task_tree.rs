@@ -0,0 +1,297 @@
+use std::{cell::OnceCell, cmp, collections::HashMap};
+
+use chrono::{Local, NaiveDateTime};
+
+use crate::{
+    error::StoreError,
+    task::{CompletedTask, FindParentResult, Priority, Task},
+};
+
+use super::{
+    data_store::{TaskID, TaskIDRef},
+    parent_index::ParentIndex,
+    visible_rows::VisibleRows,
+};
+
+/// The open and completed tasks of a store along with how they are nested.
+/// Both stores make their changes to the tree through this, and only add
+/// what is sent on to their backend.
+pub struct TaskTree<'a> {
+    pub tasks: &'a mut HashMap<TaskID, Task>,
+    pub completed_tasks: &'a mut HashMap<TaskID, CompletedTask>,
+    pub subtasks: &'a mut HashMap<TaskID, Vec<TaskID>>,
+    pub root: &'a mut Vec<TaskID>,
+    pub completed_root: &'a mut Vec<TaskID>,
+    pub completed_subtasks: &'a mut HashMap<TaskID, Vec<TaskID>>,
+    pub parent_index: &'a mut ParentIndex,
+    pub visible_rows: &'a mut OnceCell<VisibleRows>,
+}
+
+impl TaskTree<'_> {
+    /// Builds the parent index again and drops the visible rows, after the
+    /// lists of tasks were changed directly.
+    pub fn rebuild_indexes(&mut self) {
+        *self.parent_index = ParentIndex::new(self.subtasks);
+        self.visible_rows.take();
+    }
+
+    pub fn find_parent(&self, id: TaskIDRef) -> Option<FindParentResult> {
+        self.parent_index.find(id, self.root, self.subtasks)
+    }
+
+    /// Whether the task is this task or one of its parents, a broken tree
+    /// that already has a cycle is only followed once around it.
+    fn is_ancestor(&self, ancestor: TaskIDRef, id: TaskIDRef) -> bool {
+        std::iter::successors(Some(id.to_string()), |current| {
+            self.find_parent(current).and_then(|f| f.parent_id)
+        })
+        .take(self.tasks.len() + 1)
+        .any(|current| current == ancestor)
+    }
+
+    /// Checks that the task can be moved below this parent.
+    fn check_move(&self, id: TaskIDRef, parent: Option<TaskIDRef>) -> Result<(), StoreError> {
+        if !self.tasks.contains_key(id) {
+            return Err(StoreError::NotFound(id.to_string()));
+        }
+        match parent {
+            Some(parent) if self.completed_tasks.contains_key(parent) => Err(
+                StoreError::InvalidMove(format!("the task {parent} is completed")),
+            ),
+            Some(parent) if !self.tasks.contains_key(parent) => {
+                Err(StoreError::NotFound(parent.to_string()))
+            }
+            Some(parent) if self.is_ancestor(id, parent) => {
+                Err(StoreError::CycleDetected(id.to_string()))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    /// Changes the task, the visible rows are dropped if it was opened or
+    /// closed.
+    pub fn modify<F, T: FnOnce(&mut Task) -> F>(
+        &mut self,
+        id: TaskIDRef,
+        closure: T,
+    ) -> Result<F, StoreError> {
+        let task = self
+            .tasks
+            .get_mut(id)
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        task.touch(Local::now().naive_local());
+        let opened = task.opened;
+        let result = closure(task);
+        if task.opened != opened {
+            self.visible_rows.take();
+        }
+        Ok(result)
+    }
+
+    /// Adds the task last below the parent, returning the id it was given.
+    pub fn add(&mut self, mut task: Task, parent: Option<TaskIDRef>) -> Result<TaskID, StoreError> {
+        if let Some(parent) = parent.filter(|parent| !self.tasks.contains_key(*parent)) {
+            return Err(StoreError::NotFound(parent.to_string()));
+        }
+        task.touch(Local::now().naive_local());
+        let key = uuid::Uuid::new_v4().to_string();
+        self.tasks.insert(key.clone(), task);
+        let parents = if let Some(parent_id) = parent {
+            self.subtasks.entry(parent_id.to_string()).or_default()
+        } else {
+            &mut *self.root
+        };
+        parents.push(key.clone());
+        self.parent_index.set(&key, parent);
+        self.visible_rows.take();
+        Ok(key)
+    }
+
+    /// Removes the task and its subtasks.
+    pub fn delete(&mut self, id: TaskIDRef) -> Result<Task, StoreError> {
+        if !self.tasks.contains_key(id) {
+            return Err(StoreError::NotFound(id.to_string()));
+        }
+        self.detach(id);
+        Ok(self.remove_subtree(id).expect("The task was found above"))
+    }
+
+    /// Removes the task and its subtasks from the tasks, without removing the
+    /// task from the list it is in.
+    fn remove_subtree(&mut self, id: TaskIDRef) -> Option<Task> {
+        let task = self.tasks.remove(id)?;
+        self.parent_index.remove(id);
+        for subtask in self.subtasks.remove(id).unwrap_or_default() {
+            self.remove_subtree(&subtask);
+        }
+        Some(task)
+    }
+
+    /// Removes the task from the list it is in, returning where it was.
+    pub fn detach(&mut self, id: TaskIDRef) -> Option<FindParentResult> {
+        let parent = self.find_parent(id)?;
+        let subtasks = match &parent.parent_id {
+            Some(parent_id) => self.subtasks.get_mut(parent_id)?,
+            None => &mut *self.root,
+        };
+        subtasks.remove(parent.task_local_offset);
+        self.parent_index.remove(id);
+        self.visible_rows.take();
+        Some(parent)
+    }
+
+    /// Moves the task to `order` within the parent, or within the list it is
+    /// in when there is no parent and the move is not global. Returns the
+    /// parent it was moved from and the parent it was moved to.
+    pub fn move_task(
+        &mut self,
+        id: TaskIDRef,
+        parent: Option<TaskID>,
+        order: usize,
+        global: Option<()>,
+    ) -> Result<(Option<TaskID>, Option<TaskID>), StoreError> {
+        self.check_move(id, parent.as_deref())?;
+        let previous_parent = self.detach(id).and_then(|f| f.parent_id);
+        let parent = parent.or(previous_parent.clone().filter(|_| global.is_none()));
+        let subtasks = if let Some(p) = &parent {
+            self.subtasks.entry(p.clone()).or_default()
+        } else {
+            &mut *self.root
+        };
+        subtasks.insert(order.min(subtasks.len()), id.to_string());
+        self.parent_index.set(id, parent.as_deref());
+        if let Some(task) = self.tasks.get_mut(id) {
+            task.touch(Local::now().naive_local());
+        }
+        Ok((previous_parent, parent))
+    }
+
+    /// Places the task last, in the same way as [`TaskTree::move_task`].
+    pub fn append(&mut self, id: TaskIDRef, parent: Option<TaskID>, global: Option<()>) {
+        let previous_parent = self.detach(id).and_then(|f| f.parent_id);
+        let parent = parent.or(previous_parent.filter(|_| global.is_none()));
+        let subtasks = if let Some(p) = &parent {
+            self.subtasks.entry(p.clone()).or_default()
+        } else {
+            &mut *self.root
+        };
+        subtasks.push(id.to_string());
+        self.parent_index.set(id, parent.as_deref());
+        self.visible_rows.take();
+    }
+
+    /// Moves the task and its subtasks into the completed tasks.
+    pub fn complete(
+        &mut self,
+        id: TaskIDRef,
+        time_completed: NaiveDateTime,
+    ) -> Result<(), StoreError> {
+        if !self.tasks.contains_key(id) {
+            return Err(StoreError::NotFound(id.to_string()));
+        }
+        let parent = self
+            .detach(id)
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        self.complete_subtree(id, parent, time_completed);
+        self.completed_root.push(id.to_string());
+        Ok(())
+    }
+
+    /// Moves the task and its subtasks into the completed tasks, the subtasks
+    /// are kept below the task.
+    fn complete_subtree(
+        &mut self,
+        id: TaskIDRef,
+        parent: FindParentResult,
+        time_completed: NaiveDateTime,
+    ) {
+        let Some(mut task) = self.tasks.remove(id) else {
+            return;
+        };
+        task.touch(time_completed);
+        self.parent_index.remove(id);
+        let subtasks = self.subtasks.remove(id).unwrap_or_default();
+        for (position, subtask) in subtasks.iter().enumerate() {
+            let parent = FindParentResult {
+                parent_id: Some(id.to_string()),
+                task_local_offset: position,
+            };
+            self.complete_subtree(subtask, parent, time_completed);
+        }
+        if !subtasks.is_empty() {
+            self.completed_subtasks.insert(id.to_string(), subtasks);
+        }
+        self.completed_tasks.insert(
+            id.to_string(),
+            CompletedTask {
+                parent_id: parent.parent_id,
+                position: Some(parent.task_local_offset),
+                ..CompletedTask::from_task(task, time_completed)
+            },
+        );
+    }
+
+    /// Moves the completed task back to its parent and position, along with
+    /// the subtasks completed with it. The task is restored to the root tasks
+    /// if its parent is no longer open.
+    pub fn restore(&mut self, id: TaskIDRef) -> Result<(), StoreError> {
+        let completed_task = self
+            .completed_tasks
+            .get(id)
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        let position = completed_task.position;
+        let parent_id = completed_task.parent_id.clone();
+        self.completed_root.retain(|f| f != id);
+        self.completed_subtasks
+            .values_mut()
+            .for_each(|subtasks| subtasks.retain(|f| f != id));
+        self.restore_subtree(id);
+
+        let (parent_id, position) = match parent_id {
+            Some(parent_id) if self.tasks.contains_key(&parent_id) => (Some(parent_id), position),
+            // The parent is no longer open.
+            Some(_) => (None, None),
+            None => (None, position),
+        };
+        self.parent_index.set(id, parent_id.as_deref());
+        self.visible_rows.take();
+        let subtasks = match parent_id {
+            Some(parent_id) => self.subtasks.entry(parent_id).or_default(),
+            None => &mut *self.root,
+        };
+        let position = position.map_or(subtasks.len(), |f| f.min(subtasks.len()));
+        subtasks.insert(position, id.to_string());
+        if let Some(task) = self.tasks.get_mut(id) {
+            task.touch(Local::now().naive_local());
+        }
+        Ok(())
+    }
+
+    /// Moves the completed task and the subtasks completed with it back into
+    /// the tasks, without placing the task in the tree.
+    fn restore_subtree(&mut self, id: TaskIDRef) {
+        if let Some(completed_task) = self.completed_tasks.remove(id) {
+            self.tasks
+                .insert(id.to_string(), Task::from_completed_task(completed_task));
+        }
+        if let Some(subtasks) = self.completed_subtasks.remove(id) {
+            for subtask in &subtasks {
+                self.restore_subtree(subtask);
+            }
+            self.parent_index.set_all(&subtasks, id);
+            self.subtasks.insert(id.to_string(), subtasks);
+        }
+    }
+
+    /// Sorts each list of tasks by priority, highest first.
+    pub fn sort(&mut self) {
+        self.visible_rows.take();
+        let tasks = &*self.tasks;
+        let priority =
+            |id: &TaskID| cmp::Reverse(tasks.get(id).map_or(Priority::None, |f| f.priority));
+        self.root.sort_by_key(priority);
+        for subtasks in self.subtasks.values_mut() {
+            subtasks.sort_by_key(priority);
+        }
+    }
+}
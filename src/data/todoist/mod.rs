@@ -1,6 +1,7 @@
-mod todoist_command;
+pub mod todoist_command;
 pub mod todoist_data_store;
 pub mod todoist_main;
 pub mod todoist_project;
 pub mod todoist_response;
 pub mod todoist_task;
+pub mod todoist_user;
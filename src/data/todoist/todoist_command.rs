@@ -1,6 +1,6 @@
 use std::collections::HashMap;
 
-use chrono::NaiveDate;
+use chrono::{Months, NaiveDate};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 
@@ -11,6 +11,56 @@ use crate::task::{Priority, Task};
 pub enum TodoistCommand {
     Send(TodoistSendCommand),
     Refresh,
+    LoadCompleted(CompletedPageRequest),
+}
+
+/// A page of the tasks that were completed between `since` and `until`.
+#[derive(Clone, PartialEq, Debug)]
+pub struct CompletedPageRequest {
+    pub since: NaiveDate,
+    pub until: NaiveDate,
+    pub cursor: Option<String>,
+}
+
+impl CompletedPageRequest {
+    /// The maximum amount of time Todoist allows between `since` and `until`.
+    const WINDOW: Months = Months::new(3);
+
+    /// When Todoist was released, used as the start of the history when it is
+    /// unknown when the user joined.
+    const HISTORY_START: NaiveDate = match NaiveDate::from_ymd_opt(2007, 1, 1) {
+        Some(date) => date,
+        None => panic!("Date should be valid"),
+    };
+
+    pub fn ending_at(until: NaiveDate) -> CompletedPageRequest {
+        CompletedPageRequest {
+            since: until
+                .checked_sub_months(Self::WINDOW)
+                .expect("Date should be in range"),
+            until,
+            cursor: None,
+        }
+    }
+
+    /// Returns the page after this one, or None if the whole history has been loaded.
+    ///
+    /// * `next_cursor` - The cursor Todoist returned for this page.
+    /// * `joined_at` - When the user joined Todoist, nothing was completed before this.
+    pub fn next(
+        &self,
+        next_cursor: Option<String>,
+        joined_at: Option<NaiveDate>,
+    ) -> Option<CompletedPageRequest> {
+        if next_cursor.is_some() {
+            return Some(CompletedPageRequest {
+                cursor: next_cursor,
+                ..self.clone()
+            });
+        }
+        let reached_start = self.since <= joined_at.unwrap_or(Self::HISTORY_START);
+        (!reached_start).then(|| CompletedPageRequest::ending_at(self.since))
+    }
 }
 
 #[derive(Serialize, Clone, PartialEq, Deserialize, Debug)]
@@ -52,14 +102,29 @@ pub enum TodoistSendCommand {
         uuid: String,
         args: TodoistItemMoveCommand,
     },
+    #[serde(rename = "note_add")]
+    NoteAdd {
+        uuid: String,
+        temp_id: String,
+        args: TodoistNoteAddCommand,
+    },
+    #[serde(rename = "note_update")]
+    NoteUpdate {
+        uuid: String,
+        args: TodoistNoteUpdateCommand,
+    },
 }
 
 impl TodoistSendCommand {
     pub fn update_id(&mut self, temp_id_mapping: &HashMap<String, String>) {
-        if let TodoistSendCommand::Delete { args, .. } = self {
-            if let Some(new_id) = temp_id_mapping.get(&args.id) {
-                args.id = new_id.to_string();
-            }
+        let id = match self {
+            TodoistSendCommand::Delete { args, .. } => &mut args.id,
+            TodoistSendCommand::NoteAdd { args, .. } => &mut args.item_id,
+            TodoistSendCommand::NoteUpdate { args, .. } => &mut args.id,
+            _ => return,
+        };
+        if let Some(new_id) = temp_id_mapping.get(id) {
+            *id = new_id.to_string();
         }
     }
 }
@@ -102,6 +167,7 @@ pub struct TodoistUpdateItem {
     pub is_collapsed: bool,
     pub priority: usize,
     pub due: Option<TodoistDue>,
+    pub labels: Vec<String>,
 }
 
 #[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
@@ -116,6 +182,18 @@ pub struct TodoistItemUncompleteCommand {
     pub id: String,
 }
 
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct TodoistNoteAddCommand {
+    pub item_id: String,
+    pub content: String,
+}
+
+#[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
+pub struct TodoistNoteUpdateCommand {
+    pub id: String,
+    pub content: String,
+}
+
 #[derive(Serialize, Clone, Deserialize, Debug, PartialEq)]
 pub struct TodoistDue {
     pub date: NaiveDate,
@@ -128,6 +206,7 @@ pub fn task_to_todoist(id: String, task: &Task) -> TodoistUpdateItem {
         is_collapsed: !task.opened,
         priority: priority_to_todoist(task.priority),
         due: task.due_date.map(|date| TodoistDue { date }),
+        labels: task.tags.clone(),
     }
 }
 
@@ -1,22 +1,31 @@
 use std::{
-    cmp,
+    cell::OnceCell,
     collections::HashMap,
-    sync::{Arc, Mutex},
+    sync::{mpsc::Receiver, Arc, Mutex},
 };
 
-use chrono::NaiveDateTime;
+use chrono::{Local, NaiveDate, NaiveDateTime};
 use tokio::{sync::mpsc::Sender, task};
+use tui::style::Color;
 
 use crate::{
-    data::data_store::{DataTaskStore, TaskID, TaskIDRef},
-    task::{CompletedTask, FindParentResult, Priority, Tag, Task},
+    data::{
+        data_store::{DataTaskStore, TaskID, TaskIDRef},
+        parent_index::ParentIndex,
+        store_event::{StoreEvent, Subscribers},
+        task_tree::TaskTree,
+        visible_rows::VisibleRows,
+    },
+    error::StoreError,
+    task::{Comment, CompletedTask, FindParentResult, Tag, Task},
     utils::task_position::cursor_to_task,
 };
 
 use super::todoist_command::{
-    task_to_todoist, TodoistCommand, TodoistItemAddCommand, TodoistItemCompleteCommand,
-    TodoistItemDeleteCommand, TodoistItemMoveCommand, TodoistItemReorder,
-    TodoistItemReorderCommand, TodoistItemUncompleteCommand, TodoistSendCommand,
+    task_to_todoist, CompletedPageRequest, TodoistCommand, TodoistItemAddCommand,
+    TodoistItemCompleteCommand, TodoistItemDeleteCommand, TodoistItemMoveCommand,
+    TodoistItemReorder, TodoistItemReorderCommand, TodoistItemUncompleteCommand,
+    TodoistNoteAddCommand, TodoistNoteUpdateCommand, TodoistSendCommand,
 };
 
 // FIXME: we can seperate this into the state and the sender. This seperates them and we can use an
@@ -27,48 +36,247 @@ pub struct TodoistDataStore {
     pub subtasks: HashMap<TaskID, Vec<TaskID>>,
     pub root: Vec<TaskID>,
     pub completed_root: Vec<TaskID>,
+    /// The subtasks of completed tasks that were completed with them.
+    pub completed_subtasks: HashMap<TaskID, Vec<TaskID>>,
     pub tags: HashMap<String, Tag>,
     pub task_count: usize,
+    /// Rebuilt after each sync, as the sync changes the lists directly.
+    pub parent_index: ParentIndex,
+    pub visible_rows: OnceCell<VisibleRows>,
+    pub subscribers: Subscribers,
 
     pub currently_syncing: Arc<Mutex<bool>>,
     pub command_sender: Sender<TodoistCommand>,
     pub inbox_project: Option<String>,
 
     pub temporary_mappings: HashMap<TaskID, TaskID>,
+
+    /// The next page of completed tasks, None once the whole history is loaded.
+    pub completed_history: Option<CompletedPageRequest>,
+    pub loading_completed_history: bool,
+    /// The pages in a row that had no completed tasks in them.
+    pub empty_completed_pages: usize,
+    /// When the user joined Todoist, no tasks were completed before this.
+    pub joined_at: Option<NaiveDate>,
 }
 
 impl TodoistDataStore {
+    pub fn tree(&mut self) -> TaskTree<'_> {
+        TaskTree {
+            tasks: &mut self.tasks,
+            completed_tasks: &mut self.completed_tasks,
+            subtasks: &mut self.subtasks,
+            root: &mut self.root,
+            completed_root: &mut self.completed_root,
+            completed_subtasks: &mut self.completed_subtasks,
+            parent_index: &mut self.parent_index,
+            visible_rows: &mut self.visible_rows,
+        }
+    }
+
+    /// Sends the command, the change is already made to the store so it is
+    /// only logged if the command can not be sent.
+    ///
+    /// It counts as syncing from now on, so the spinner is drawn straight away
+    /// instead of once the sync loop picks the command up.
     pub fn send_command(&self, command: TodoistSendCommand) {
-        self.command(TodoistCommand::Send(command));
+        self.set_syncing(true);
+        if let Err(err) = self.command(TodoistCommand::Send(command)) {
+            self.set_syncing(false);
+            tracing::error!("{}", err);
+        }
+    }
+
+    fn set_syncing(&self, syncing: bool) {
+        if let Ok(mut currently_syncing) = self.currently_syncing.lock() {
+            *currently_syncing = syncing;
+        }
     }
 
-    pub fn command(&self, command: TodoistCommand) {
+    pub fn command(&self, command: TodoistCommand) -> Result<(), StoreError> {
         let sender = self.command_sender.clone();
-        task::block_in_place(move || {
-            sender.blocking_send(command).unwrap();
-        });
+        task::block_in_place(move || sender.blocking_send(command)).map_err(|_| {
+            StoreError::Backend(
+                "Could not send the command, the sync loop has stopped.".to_string(),
+            )
+        })
+    }
+
+    /// Sends the change to Todoist and then to the subscribers.
+    fn emit(&mut self, event: StoreEvent) {
+        for command in self.commands_for(&event) {
+            self.send_command(command);
+        }
+        self.subscribers.send(event);
+    }
+
+    /// The commands that make the same change on Todoist, this is called
+    /// after the change is made to the store.
+    pub fn commands_for(&self, event: &StoreEvent) -> Vec<TodoistSendCommand> {
+        let uuid = || uuid::Uuid::new_v4().to_string();
+        match event {
+            StoreEvent::TaskAdded { id, parent } => self
+                .task(id)
+                .map(|task| TodoistSendCommand::Add {
+                    uuid: uuid(),
+                    temp_id: id.clone(),
+                    args: TodoistItemAddCommand {
+                        content: task.title.to_string(),
+                        parent_id: parent.clone(),
+                    },
+                })
+                .into_iter()
+                .collect(),
+            StoreEvent::TaskUpdated(id) => self
+                .task(id)
+                .map(|task| TodoistSendCommand::Update {
+                    uuid: uuid(),
+                    args: task_to_todoist(id.clone(), task),
+                })
+                .into_iter()
+                .collect(),
+            StoreEvent::TaskMoved { id, from, to } => {
+                let mut commands = Vec::new();
+                if from != to {
+                    if let Some(parent_id) = to {
+                        commands.push(TodoistSendCommand::Move {
+                            uuid: uuid(),
+                            args: TodoistItemMoveCommand {
+                                id: id.clone(),
+                                parent_id: Some(parent_id.clone()),
+                                section_id: None,
+                                project_id: None,
+                            },
+                        });
+                    } else if let Some(inbox) = &self.inbox_project {
+                        commands.push(TodoistSendCommand::Move {
+                            uuid: uuid(),
+                            args: TodoistItemMoveCommand {
+                                id: id.clone(),
+                                parent_id: None,
+                                section_id: None,
+                                project_id: Some(inbox.to_string()),
+                            },
+                        });
+                    }
+                }
+                let items = (0..self.find_tasks_draw_size())
+                    .filter_map(|i| {
+                        Some(TodoistItemReorder {
+                            id: cursor_to_task(self, i)?,
+                            child_order: i,
+                        })
+                    })
+                    .collect();
+                commands.push(TodoistSendCommand::Reorder {
+                    uuid: uuid(),
+                    args: TodoistItemReorderCommand { items },
+                });
+                commands
+            }
+            StoreEvent::TaskCompleted(id) => vec![TodoistSendCommand::Complete {
+                uuid: uuid(),
+                args: TodoistItemCompleteCommand {
+                    id: id.clone(),
+                    date_completed: None,
+                },
+            }],
+            // The subtasks completed with the task are restored with it.
+            StoreEvent::TaskRestored(id) => {
+                let mut commands = Vec::new();
+                let mut to_visit = vec![id.clone()];
+                while let Some(id) = to_visit.pop() {
+                    if let Some(subtasks) = self.subtasks(&id) {
+                        to_visit.extend(subtasks.iter().rev().cloned());
+                    }
+                    commands.push(TodoistSendCommand::Uncomplete {
+                        uuid: uuid(),
+                        args: TodoistItemUncompleteCommand { id },
+                    });
+                }
+                commands
+            }
+            StoreEvent::TaskDeleted(id) => vec![TodoistSendCommand::Delete {
+                uuid: uuid(),
+                args: TodoistItemDeleteCommand { id: id.clone() },
+            }],
+            StoreEvent::CommentAdded { id, comment_id } => self
+                .comment(id, comment_id)
+                .map(|comment| TodoistSendCommand::NoteAdd {
+                    uuid: uuid(),
+                    temp_id: comment_id.clone(),
+                    args: TodoistNoteAddCommand {
+                        item_id: id.clone(),
+                        content: comment.content.clone(),
+                    },
+                })
+                .into_iter()
+                .collect(),
+            StoreEvent::CommentUpdated { id, comment_id } => self
+                .comment(id, comment_id)
+                .map(|comment| TodoistSendCommand::NoteUpdate {
+                    uuid: uuid(),
+                    args: TodoistNoteUpdateCommand {
+                        id: comment_id.clone(),
+                        content: comment.content.clone(),
+                    },
+                })
+                .into_iter()
+                .collect(),
+            // Labels are only read from Todoist.
+            StoreEvent::TagChanged(_) => Vec::new(),
+        }
+    }
+
+    fn comment(&self, id: TaskIDRef, comment_id: TaskIDRef) -> Option<&Comment> {
+        self.task(id)?
+            .comments
+            .iter()
+            .find(|comment| comment.id == comment_id)
+    }
+
+    /// Adds the labels that are not yet known as tags.
+    pub fn register_labels(&mut self, labels: &[String]) {
+        for label in labels {
+            self.tags.entry(label.clone()).or_insert_with(|| Tag {
+                name: label.clone(),
+                colour: Color::Reset,
+            });
+        }
+    }
+
+    /// Builds the parent index again and drops the visible rows, after the
+    /// lists of tasks were changed directly.
+    pub fn rebuild_indexes(&mut self) {
+        self.tree().rebuild_indexes();
     }
 
     pub fn append_internal(&mut self, id: TaskIDRef, parent: Option<TaskID>, global: Option<()>) {
-        let hash_map = &mut self.subtasks;
-        let subtasks = if let Some((_, subtasks)) = hash_map
-            .iter_mut()
-            .find(|(_, subtasks)| subtasks.contains(&id.to_string()))
-        {
-            subtasks
-        } else {
-            &mut self.root
-        };
-        subtasks.retain(|f| f != id);
-        let mutable_subtasks = if let Some(p) = parent {
-            self.subtasks.entry(p).or_default()
-        } else if global.is_some() {
-            &mut self.root
-        } else {
-            subtasks
-        };
+        self.tree().append(id, parent, global);
+    }
+
+    /// Removes the task from the tree, the completed list and the tasks
+    /// without notifying Todoist.
+    pub fn remove_internal(&mut self, id: TaskIDRef) {
+        self.root.retain(|f| f != id);
+        self.completed_root.retain(|f| f != id);
+        self.subtasks
+            .values_mut()
+            .chain(self.completed_subtasks.values_mut())
+            .for_each(|val| val.retain(|f| f != id));
+        self.subtasks.remove(id);
+        self.completed_subtasks.remove(id);
+        self.tasks.remove(id);
+        self.completed_tasks.remove(id);
+        self.parent_index.remove(id);
+        self.visible_rows.take();
+    }
 
-        mutable_subtasks.push(id.to_string());
+    /// Moves the task and its subtasks into the completed tasks without
+    /// notifying Todoist.
+    pub fn complete_internal(&mut self, id: TaskIDRef, time_completed: NaiveDateTime) {
+        // Nothing to complete if the task is no longer in the tree.
+        let _ = self.tree().complete(id, time_completed);
     }
 }
 
@@ -77,19 +285,19 @@ impl DataTaskStore for TodoistDataStore {
         &mut self,
         id: TaskIDRef,
         closure: T,
-    ) -> Option<F> {
-        self.tasks.get_mut(id).map(closure)
-        // Some(closure(self.todoist_state.lock().ok()?.tasks.get_mut(id)?))
-    }
-
-    fn update_task(&mut self, id: TaskIDRef) {
-        self.send_command(TodoistSendCommand::Update {
-            uuid: uuid::Uuid::new_v4().to_string(),
-            args: task_to_todoist(
-                id.to_string(),
-                self.task(id).expect("Updating a task that has no ID."),
-            ),
-        });
+    ) -> Result<F, StoreError> {
+        let synced = |task: &Task| task_to_todoist(id.to_string(), task);
+        let before = self.tasks.get(id).map(synced);
+        let result = self.tree().modify(id, closure)?;
+        let event = StoreEvent::TaskUpdated(id.to_string());
+        // Timers, pomodoros and estimates are only kept locally, so there is
+        // nothing to send to Todoist when only they changed.
+        if self.tasks.get(id).map(synced) == before {
+            self.subscribers.send(event);
+        } else {
+            self.emit(event);
+        }
+        Ok(result)
     }
 
     fn task(&self, id: TaskIDRef) -> Option<&Task> {
@@ -100,51 +308,18 @@ impl DataTaskStore for TodoistDataStore {
         })
     }
 
-    fn completed_task_mut(&mut self, id: TaskIDRef) -> Option<&mut CompletedTask> {
-        self.completed_tasks.get_mut(id)
-    }
-
     fn completed_task(&self, id: TaskIDRef) -> Option<&CompletedTask> {
         self.completed_tasks.get(id)
     }
 
-    fn delete_task(&mut self, id: TaskIDRef) -> Option<Task> {
-        self.root.retain(|f| f != id);
-        self.subtasks
-            .values_mut()
-            .for_each(|val| val.retain(|f| f != id));
-
-        self.send_command(TodoistSendCommand::Delete {
-            uuid: uuid::Uuid::new_v4().to_string(),
-            args: TodoistItemDeleteCommand { id: id.to_string() },
-        });
-
-        self.tasks.remove(id)
+    fn delete_task(&mut self, id: TaskIDRef) -> Result<Task, StoreError> {
+        let task = self.tree().delete(id)?;
+        self.emit(StoreEvent::TaskDeleted(id.to_string()));
+        Ok(task)
     }
 
     fn find_parent(&self, id: TaskIDRef) -> Option<FindParentResult> {
-        let parent = self
-            .subtasks
-            .iter()
-            .find(|(_, subs)| subs.contains(&id.to_string()))
-            .map(|p| p.0);
-        let local_index = if let Some(p) = parent {
-            self.subtasks.get(p).unwrap().iter().position(|t| t == id)
-        } else {
-            self.root.iter().position(|t| t == id)
-        };
-        Some(FindParentResult {
-            task_local_offset: local_index?,
-            parent_id: parent.cloned(),
-        })
-    }
-
-    fn subtasks_mut(&mut self, id: Option<TaskIDRef>) -> Option<&mut Vec<TaskID>> {
-        if let Some(id) = id {
-            self.subtasks.get_mut(id)
-        } else {
-            Some(&mut self.root)
-        }
+        self.parent_index.find(id, &self.root, &self.subtasks)
     }
 
     fn subtasks(&self, id: TaskIDRef) -> Option<&Vec<TaskID>> {
@@ -159,6 +334,10 @@ impl DataTaskStore for TodoistDataStore {
         &self.completed_root
     }
 
+    fn completed_subtasks(&self, id: TaskIDRef) -> Option<&Vec<TaskID>> {
+        self.completed_subtasks.get(id)
+    }
+
     fn delete_tag(&mut self, tag_id: TaskIDRef) {
         self.tags.remove(tag_id);
         for task in &mut self.tasks.values_mut() {
@@ -167,41 +346,24 @@ impl DataTaskStore for TodoistDataStore {
         for completed_task in &mut self.completed_tasks.values_mut() {
             completed_task.task.tags.retain(|f| f != tag_id);
         }
+        self.emit(StoreEvent::TagChanged(tag_id.to_string()));
     }
 
     fn sort(&mut self) {
-        self.root
-            .sort_by_key(|f| cmp::Reverse(self.tasks[f].priority));
-        for subtasks in self.subtasks.values_mut() {
-            subtasks.sort_by_key(|f| {
-                cmp::Reverse(self.tasks.get(f).map_or(Priority::None, |k| k.priority))
-            });
-        }
+        self.tree().sort();
     }
 
-    fn add_task(&mut self, task: Task, parent: Option<TaskIDRef>) {
-        let parents = if let Some(parent_id) = parent {
-            // FIXME: consider writing ugly version that avoids a clone.
-            self.subtasks.entry(parent_id.to_string()).or_default()
-        } else {
-            &mut self.root
-        };
-        let key = uuid::Uuid::new_v4().to_string();
+    fn add_task(&mut self, task: Task, parent: Option<TaskIDRef>) -> Result<TaskID, StoreError> {
+        let key = self.tree().add(task, parent)?;
         self.task_count += 1;
-        self.tasks.insert(key.clone(), task.clone());
-        parents.push(key.clone());
-
-        self.send_command(TodoistSendCommand::Add {
-            uuid: uuid::Uuid::new_v4().to_string(),
-            temp_id: key,
-            args: TodoistItemAddCommand {
-                content: task.title.to_string(),
-                parent_id: parent.map(|f| f.to_string()),
-            },
+        self.emit(StoreEvent::TaskAdded {
+            id: key.clone(),
+            parent: parent.map(str::to_string),
         });
+        Ok(key)
     }
 
-    fn refresh(&mut self) {
+    fn refresh(&mut self) -> Result<(), StoreError> {
         self.command(TodoistCommand::Refresh)
     }
 
@@ -215,140 +377,117 @@ impl DataTaskStore for TodoistDataStore {
         parent: Option<TaskID>,
         order: usize,
         global: Option<()>,
-    ) {
-        let previous_parent = self.find_parent(id);
-        let subtasks = if let Some(FindParentResult {
-            parent_id: Some(p), ..
-        }) = &previous_parent
-        {
-            self.subtasks.entry(p.to_string()).or_default()
-        } else {
-            &mut self.root
-        };
-        subtasks.retain(|f| f != id);
-        let mutable_subtasks = if let Some(p) = &parent {
-            self.subtasks.entry(p.to_string()).or_default()
-        } else if global.is_some() {
-            &mut self.root
-        } else {
-            subtasks
-        };
-
-        mutable_subtasks.insert(order, id.to_string());
+    ) -> Result<(), StoreError> {
+        let (from, to) = self.tree().move_task(id, parent, order, global)?;
+        self.emit(StoreEvent::TaskMoved {
+            id: id.to_string(),
+            from,
+            to,
+        });
+        Ok(())
+    }
 
-        let mut items = Vec::new();
-        for i in 0..self.find_tasks_draw_size() {
-            let task_id = cursor_to_task(self, i).unwrap();
-            items.push(TodoistItemReorder {
-                id: task_id,
-                child_order: i,
-            });
-        }
+    fn visible_rows(&self) -> &VisibleRows {
+        self.visible_rows.get_or_init(|| VisibleRows::new(self))
+    }
 
-        if let Some(parent_id) = parent {
-            self.send_command(TodoistSendCommand::Move {
-                uuid: uuid::Uuid::new_v4().to_string(),
-                args: TodoistItemMoveCommand {
-                    id: id.to_string(),
-                    parent_id: Some(parent_id.clone()),
-                    section_id: None,
-                    project_id: None,
-                },
-            });
-        } else if let Some(inbox) = &self.inbox_project {
-            if global.is_some() {
-                self.send_command(TodoistSendCommand::Move {
-                    uuid: uuid::Uuid::new_v4().to_string(),
-                    args: TodoistItemMoveCommand {
-                        id: id.to_string(),
-                        parent_id: None,
-                        section_id: None,
-                        project_id: Some(inbox.to_string()),
-                    },
-                });
-                tracing::debug!("Sent move to the inbox: {}", inbox);
-            }
-        }
+    fn complete_task(
+        &mut self,
+        id: TaskIDRef,
+        time_completed: NaiveDateTime,
+    ) -> Result<(), StoreError> {
+        self.tree().complete(id, time_completed)?;
+        self.emit(StoreEvent::TaskCompleted(id.to_string()));
+        Ok(())
+    }
 
-        self.send_command(TodoistSendCommand::Reorder {
-            uuid: uuid::Uuid::new_v4().to_string(),
-            args: TodoistItemReorderCommand { items },
-        });
+    fn restore(&mut self, id: TaskIDRef) -> Result<(), StoreError> {
+        self.tree().restore(id)?;
+        self.emit(StoreEvent::TaskRestored(id.to_string()));
+        Ok(())
     }
 
-    fn find_task_draw_size(&self, task_id: TaskIDRef) -> usize {
-        if let Some(task) = self.task(task_id) {
-            return if !task.opened {
-                0
-            } else {
-                self.subtasks(task_id)
-                    .map_or(0, |f| f.iter().map(|k| self.find_task_draw_size(k)).sum())
-            } + 1;
-        }
-        0
+    fn tags(&self) -> &HashMap<String, Tag> {
+        &self.tags
     }
 
-    fn find_tasks_draw_size(&self) -> usize {
-        self.root_tasks()
-            .iter()
-            .map(|t| self.find_task_draw_size(t))
-            .sum()
+    fn set_tag(&mut self, tag_id: TaskIDRef, tag: Tag) {
+        self.tags.insert(tag_id.to_string(), tag);
+        self.emit(StoreEvent::TagChanged(tag_id.to_string()));
     }
 
-    fn complete_task(&mut self, id: TaskIDRef, time_completed: NaiveDateTime) {
-        self.root.retain(|f| f != id);
-        self.subtasks
-            .values_mut()
-            .for_each(|subtasks| subtasks.retain(|f| f != id));
-        if let Some(task) = self.tasks.remove(id) {
-            self.completed_tasks.insert(
-                id.to_string(),
-                CompletedTask::from_task(task, time_completed),
-            );
-            self.completed_root.push(id.to_string());
+    fn add_comment(
+        &mut self,
+        id: TaskIDRef,
+        content: String,
+        posted_at: NaiveDateTime,
+    ) -> Result<(), StoreError> {
+        if content.trim().is_empty() {
+            return Err(StoreError::EmptyComment);
         }
-
-        self.send_command(TodoistSendCommand::Complete {
-            uuid: uuid::Uuid::new_v4().to_string(),
-            args: TodoistItemCompleteCommand {
-                id: id.to_string(),
-                date_completed: None,
-            },
+        let task = self
+            .tasks
+            .get_mut(id)
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        task.touch(Local::now().naive_local());
+        let comment_id = uuid::Uuid::new_v4().to_string();
+        task.comments.push(Comment {
+            id: comment_id.clone(),
+            content,
+            posted_at,
         });
+        self.emit(StoreEvent::CommentAdded {
+            id: id.to_string(),
+            comment_id,
+        });
+        Ok(())
     }
 
-    fn restore(&mut self, id: TaskIDRef) {
-        self.completed_root.retain(|f| f != id);
-        if let Some(task) = self.completed_tasks.remove(id) {
-            self.tasks.insert(id.to_string(), task.task);
-            if let Some(parent_id) = self.find_parent(id).and_then(|f| f.parent_id) {
-                let subtasks = self
-                    .subtasks
-                    .get_mut(&parent_id)
-                    .expect("Find parent guarentees this exist.");
-                //FIXME: is there a move funciton?
-                subtasks.retain(|f| f != id);
-                subtasks.push(id.to_string());
-            } else {
-                self.root.push(id.to_string());
-            }
+    fn update_comment(
+        &mut self,
+        id: TaskIDRef,
+        comment_id: TaskIDRef,
+        content: String,
+    ) -> Result<(), StoreError> {
+        if content.trim().is_empty() {
+            return Err(StoreError::EmptyComment);
         }
-
-        self.send_command(TodoistSendCommand::Uncomplete {
-            uuid: uuid::Uuid::new_v4().to_string(),
-            args: TodoistItemUncompleteCommand { id: id.to_string() },
+        let task = self
+            .tasks
+            .get_mut(id)
+            .ok_or_else(|| StoreError::NotFound(id.to_string()))?;
+        let comment = task
+            .comments
+            .iter_mut()
+            .find(|f| f.id == comment_id)
+            .ok_or_else(|| StoreError::CommentNotFound(comment_id.to_string()))?;
+        comment.content = content;
+        task.touch(Local::now().naive_local());
+        self.emit(StoreEvent::CommentUpdated {
+            id: id.to_string(),
+            comment_id: comment_id.to_string(),
         });
+        Ok(())
     }
 
-    fn tags(&self) -> &HashMap<String, Tag> {
-        &self.tags
-    }
-
-    fn tags_mut(&mut self) -> &mut HashMap<String, Tag> {
-        &mut self.tags
+    fn load_completed_history(&mut self) {
+        if self.loading_completed_history {
+            return;
+        }
+        if let Some(request) = self.completed_history.clone() {
+            if let Err(err) = self.command(TodoistCommand::LoadCompleted(request)) {
+                tracing::error!("{}", err);
+                return;
+            }
+            self.loading_completed_history = true;
+        }
     }
 
     fn is_syncing(&self) -> bool {
         self.currently_syncing.lock().is_ok_and(|f| *f)
     }
+
+    fn subscribe(&mut self) -> Receiver<StoreEvent> {
+        self.subscribers.subscribe()
+    }
 }
@@ -3,38 +3,71 @@ use std::{
     collections::HashMap,
     panic,
     sync::{Arc, Mutex},
+    time::Duration,
 };
 
-use chrono::{Local, Months};
+use chrono::{Local, NaiveDate};
 use itertools::Itertools;
-use tokio::{join, sync::mpsc::Sender};
+use tokio::{join, sync::mpsc::Sender, time::Instant};
 
 use crate::{
     data::{
         data_store::DataTaskStore,
+        parent_index::ParentIndex,
         todoist::{
-            todoist_command::TodoistCommand,
+            todoist_command::{CompletedPageRequest, TodoistCommand},
             todoist_response::{SyncStatus, TodoistGetAllCompletedItemResponse, TodoistSync},
+            todoist_task::TodoistNote,
         },
     },
     task::{CompletedTask, Task},
 };
+use reqwest::Client;
 
 use super::todoist_data_store::TodoistDataStore;
 
 pub const API_GATEWAY: &str = "https://api.todoist.com/api/v1/sync";
+pub const COMPLETED_GATEWAY: &str =
+    "https://api.todoist.com/api/v1/tasks/completed/by_completion_date";
+/// The maximum number of completed tasks Todoist sends in a single page.
+const COMPLETED_PAGE_SIZE: &str = "50";
+/// Empty pages loaded in a row before waiting for the user to scroll again,
+/// so a long gap in the history isn't requested all at once.
+const MAX_EMPTY_COMPLETED_PAGES: usize = 4;
+
+/// Gets a single page of the tasks that were completed in the requested period.
+pub async fn fetch_completed_page(
+    client: &Client,
+    token: &str,
+    request: &CompletedPageRequest,
+) -> reqwest::Result<TodoistGetAllCompletedItemResponse> {
+    let mut query = vec![
+        ("since", request.since.to_string()),
+        ("until", request.until.to_string()),
+        ("limit", COMPLETED_PAGE_SIZE.to_string()),
+    ];
+    if let Some(cursor) = &request.cursor {
+        query.push(("cursor", cursor.clone()));
+    }
+    client
+        .get(COMPLETED_GATEWAY)
+        .header("Authorization", format!("Bearer {}", token))
+        .query(&query)
+        .send()
+        .await?
+        .json()
+        .await
+}
 
-pub async fn get_initial_tasks<T: Into<String>>(
-    todoist_auth: T,
-) -> (
-    Vec<String>,
-    HashMap<String, Task>,
-    Vec<String>,
-    HashMap<String, CompletedTask>,
-    HashMap<String, Vec<String>>,
-    String,
-    Option<String>,
-) {
+/// Parses the date the user joined Todoist at.
+fn joined_at(sync: &TodoistSync) -> Option<NaiveDate> {
+    let joined_at = sync.user.as_ref()?.joined_at.as_deref()?;
+    chrono::DateTime::parse_from_rfc3339(joined_at)
+        .ok()
+        .map(|f| f.date_naive())
+}
+
+pub async fn get_initial_tasks<T: Into<String>>(todoist_auth: T) -> InitialTasks {
     let token = todoist_auth.into();
     let client = reqwest::Client::new();
     let mut params = HashMap::new();
@@ -45,22 +78,10 @@ pub async fn get_initial_tasks<T: Into<String>>(
         .header("Authorization", format!("Bearer {}", &token))
         .form(&params);
 
-    let completed_items = client
-        .get("https://api.todoist.com/api/v1/tasks/completed/by_completion_date")
-        .header("Authorization", format!("Bearer {}", &token))
-        .query(&[
-            (
-                "since",
-                Local::now()
-                    .date_naive()
-                    .checked_sub_months(Months::new(3))
-                    .unwrap()
-                    .to_string(),
-            ),
-            ("until", Local::now().date_naive().to_string()),
-        ]);
-
-    let (Ok(completed_items), Ok(sync)) = join!(completed_items.send(), sync.send()) else {
+    let first_page = CompletedPageRequest::ending_at(Local::now().date_naive());
+    let completed_items = fetch_completed_page(&client, &token, &first_page);
+
+    let (Ok(completed_items), Ok(sync)) = join!(completed_items, sync.send()) else {
         panic!("A connection error occured");
     };
 
@@ -71,11 +92,12 @@ pub async fn get_initial_tasks<T: Into<String>>(
             panic!("Could not deserialise: {:?} \n because {:?}", s, e);
         }
     };
-    let completed_items: TodoistGetAllCompletedItemResponse = completed_items.json().await.unwrap();
+    let joined_at = joined_at(&sync);
+    let completed_history = first_page.next(completed_items.next_cursor.clone(), joined_at);
 
     let mut subtasks: HashMap<String, Vec<(usize, String)>> = HashMap::new();
     let mut root_tasks = Vec::new();
-    let tasks: HashMap<String, Task> = sync
+    let mut tasks: HashMap<String, Task> = sync
         .items
         .unwrap_or_default()
         .into_iter()
@@ -89,6 +111,7 @@ pub async fn get_initial_tasks<T: Into<String>>(
             (f.id.clone(), f.into())
         })
         .collect();
+    apply_notes(&mut tasks, sync.notes.unwrap_or_default());
 
     let subtasks = subtasks
         .into_iter()
@@ -111,27 +134,131 @@ pub async fn get_initial_tasks<T: Into<String>>(
     let completed_tasks: HashMap<String, CompletedTask> = completed_items
         .items
         .into_iter()
-        .map(|f| (f.id.clone(), f.into()))
+        .filter_map(|f| Some((f.id.clone(), f.into_completed_task()?)))
         .collect();
 
-    let completed_root: Vec<String> = completed_tasks.keys().cloned().collect_vec();
+    let completed_root: Vec<String> = completed_tasks
+        .iter()
+        .sorted_by_key(|(_, f)| f.time_completed)
+        .map(|(id, _)| id.clone())
+        .collect_vec();
 
-    (
-        root_tasks,
+    InitialTasks {
+        root: root_tasks,
         tasks,
         completed_root,
         completed_tasks,
         subtasks,
-        sync.sync_token,
-        sync.projects
+        sync_token: sync.sync_token,
+        inbox_project: sync
+            .projects
             .and_then(|f| f.into_iter().find_or_first(|f| f.name == "Inbox"))
             .map(|f| f.id),
-    )
+        completed_history,
+        joined_at,
+    }
+}
+
+pub struct InitialTasks {
+    pub root: Vec<String>,
+    pub tasks: HashMap<String, Task>,
+    pub completed_root: Vec<String>,
+    pub completed_tasks: HashMap<String, CompletedTask>,
+    pub subtasks: HashMap<String, Vec<String>>,
+    pub sync_token: String,
+    pub inbox_project: Option<String>,
+    pub completed_history: Option<CompletedPageRequest>,
+    pub joined_at: Option<NaiveDate>,
+}
+
+pub enum TaskSync {
+    /// The response to the commands that were sent.
+    Sync(TodoistSync, HashMap<String, String>),
+    /// A page of older completed tasks.
+    CompletedPage(CompletedPageRequest, TodoistGetAllCompletedItemResponse),
+    /// The completed page could not be loaded.
+    CompletedPageFailed,
+}
+
+pub fn handle_sync(data_store: &mut TodoistDataStore, task_sync: TaskSync) {
+    match task_sync {
+        TaskSync::Sync(todoist_sync, temp_id_mapping) => {
+            handle_item_sync(data_store, todoist_sync, temp_id_mapping)
+        }
+        TaskSync::CompletedPage(request, response) => {
+            handle_completed_page(data_store, request, response)
+        }
+        TaskSync::CompletedPageFailed => data_store.loading_completed_history = false,
+    }
+}
+
+/// Adds, updates or removes the comments that were changed on Todoist.
+fn apply_notes(tasks: &mut HashMap<String, Task>, notes: Vec<TodoistNote>) {
+    for note in notes {
+        let Some(task) = tasks.get_mut(&note.item_id) else {
+            continue;
+        };
+        if note.is_deleted {
+            task.comments.retain(|f| f.id != note.id);
+        } else if let Some(comment) = task.comments.iter_mut().find(|f| f.id == note.id) {
+            comment.content = note.content;
+        } else {
+            task.comments.push(note.into());
+            task.comments.sort_by_key(|f| f.posted_at);
+        }
+    }
 }
 
-pub type TaskSync = (TodoistSync, HashMap<String, String>);
+/// Adds the older completed tasks before the ones that are already loaded.
+fn handle_completed_page(
+    data_store: &mut TodoistDataStore,
+    request: CompletedPageRequest,
+    response: TodoistGetAllCompletedItemResponse,
+) {
+    data_store.loading_completed_history = false;
+    data_store.completed_history = request.next(response.next_cursor, data_store.joined_at);
+
+    let mut page = Vec::new();
+    for item in response.items {
+        if data_store.completed_tasks.contains_key(&item.id)
+            || data_store.tasks.contains_key(&item.id)
+        {
+            continue;
+        }
+        data_store.register_labels(&item.labels);
+        let id = item.id.clone();
+        let Some(completed_task) = item.into_completed_task() else {
+            continue;
+        };
+        page.push((completed_task.time_completed, id.clone()));
+        data_store.completed_tasks.insert(id, completed_task);
+    }
+
+    // Nothing was completed in this period, so there is nothing new to show
+    // that would load the next page.
+    if page.is_empty() {
+        data_store.empty_completed_pages += 1;
+    } else {
+        data_store.empty_completed_pages = 0;
+    }
+    let load_next = data_store.empty_completed_pages > 0
+        && data_store.empty_completed_pages < MAX_EMPTY_COMPLETED_PAGES;
+    page.sort();
+    data_store
+        .completed_root
+        .splice(0..0, page.into_iter().map(|(_, id)| id));
+    // The progress of the open tasks counts the tasks completed below them
+    data_store.visible_rows.take();
+    if load_next {
+        data_store.load_completed_history();
+    }
+}
 
-pub fn handle_sync(data_store: &mut TodoistDataStore, (todoist_sync, temp_id_mapping): TaskSync) {
+fn handle_item_sync(
+    data_store: &mut TodoistDataStore,
+    todoist_sync: TodoistSync,
+    temp_id_mapping: HashMap<String, String>,
+) {
     for (temp_id, actual_id) in temp_id_mapping.iter() {
         data_store
             .temporary_mappings
@@ -149,6 +276,15 @@ pub fn handle_sync(data_store: &mut TodoistDataStore, (todoist_sync, temp_id_map
         if let Some(position) = data_store.root.iter().position(|a| temp_id == a) {
             data_store.root[position] = actual_id.to_string();
         }
+
+        for comment in data_store
+            .tasks
+            .values_mut()
+            .flat_map(|task| task.comments.iter_mut())
+            .filter(|comment| comment.id == *temp_id)
+        {
+            comment.id = actual_id.to_string();
+        }
     }
 
     if let Some(mut items) = todoist_sync.items {
@@ -158,53 +294,80 @@ pub fn handle_sync(data_store: &mut TodoistDataStore, (todoist_sync, temp_id_map
         let mut curr_child_order = 0;
         let copy = items.clone();
         for item in items.into_iter() {
-            if item.completed_at.is_some() {
+            if item.is_deleted || "" == item.content.as_str() {
+                data_store.remove_internal(&item.id);
                 continue;
             }
-            if "" == item.content.as_str() {
-                data_store.root.retain(|f| *f != item.id);
-                data_store
-                    .subtasks
-                    .values_mut()
-                    .for_each(|val| val.retain(|f| *f != item.id));
 
-                data_store.tasks.remove(&item.id);
+            data_store.register_labels(&item.labels);
 
+            if let Some(time_completed) = item.time_completed() {
+                // Completed elsewhere (eg: on another device)
+                if data_store.tasks.contains_key(&item.id) {
+                    data_store.complete_internal(&item.id, time_completed);
+                } else if !data_store.completed_tasks.contains_key(&item.id) {
+                    data_store.completed_root.push(item.id.clone());
+                    let parent_id = item.parent_id.clone();
+                    data_store.completed_tasks.insert(
+                        item.id.clone(),
+                        CompletedTask {
+                            parent_id,
+                            ..CompletedTask::from_task(item.into(), time_completed)
+                        },
+                    );
+                }
                 continue;
             }
 
+            if let Some(completed_task) = data_store.completed_tasks.remove(&item.id) {
+                // Uncompleted elsewhere, the task is placed back below.
+                data_store.completed_root.retain(|f| *f != item.id);
+                data_store
+                    .completed_subtasks
+                    .values_mut()
+                    .for_each(|subtasks| subtasks.retain(|f| *f != item.id));
+                // Its subtasks stay completed until they are uncompleted as well.
+                if let Some(subtasks) = data_store.completed_subtasks.remove(&item.id) {
+                    data_store.completed_root.extend(subtasks);
+                }
+                data_store
+                    .tasks
+                    .insert(item.id.clone(), Task::from_completed_task(completed_task));
+            }
+
             if item.child_order == curr_child_order {
                 curr_child_order += 1;
             }
 
-            if data_store.tasks.get_mut(item.id.as_str()).is_some() {
-                let parent_id = item.parent_id.clone();
-
+            let parent_id = item.parent_id.clone();
+            if data_store.tasks.contains_key(item.id.as_str())
+                && data_store.find_parent(&item.id).is_some()
+            {
                 if parent_id != data_store.find_parent(&item.id).and_then(|f| f.parent_id) {
                     data_store.append_internal(&item.id, parent_id, Some(()));
                 }
-
-                let task = data_store.tasks.get_mut(item.id.as_str()).unwrap();
-                *task = item.into();
             } else {
-                let parent_id = item.parent_id.clone();
                 let subtasks = if let Some(parent_id) = parent_id {
                     data_store.subtasks.entry(parent_id).or_default()
                 } else {
                     &mut data_store.root
                 };
-
                 subtasks.push(item.id.clone());
+            }
 
+            if let Some(task) = data_store.tasks.get_mut(item.id.as_str()) {
+                item.update_task(task);
+            } else {
                 data_store.tasks.insert(item.id.clone(), item.into());
             }
         }
 
+        data_store.rebuild_indexes();
         if curr_child_order >= data_store.find_tasks_draw_size() {
             // we assume they will send every task in the case of a move.
             data_store.root.clear();
             for item in copy.into_iter() {
-                if item.completed_at.is_some() || "" == item.content.as_str() {
+                if item.completed_at.is_some() || item.is_deleted || "" == item.content.as_str() {
                     continue;
                 }
                 if let Some(task) = data_store.tasks.get_mut(item.id.as_str()) {
@@ -219,34 +382,88 @@ pub fn handle_sync(data_store: &mut TodoistDataStore, (todoist_sync, temp_id_map
             }
         }
     }
+
+    // The lists were changed directly above
+    data_store.rebuild_indexes();
+
+    if let Some(notes) = todoist_sync.notes {
+        apply_notes(&mut data_store.tasks, notes);
+    }
+}
+
+/// Periodically asks the sync loop for changes made elsewhere, this uses the
+/// stored `sync_token` so only the changes since the last sync are received.
+fn spawn_poller(command_sender: Sender<TodoistCommand>, poll_interval: u64) {
+    if poll_interval == 0 {
+        return;
+    }
+    tokio::spawn(async move {
+        let period = Duration::from_secs(poll_interval);
+        let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+        loop {
+            interval.tick().await;
+            if command_sender.send(TodoistCommand::Refresh).await.is_err() {
+                break;
+            }
+        }
+    });
 }
 
+/// Connects to Todoist and spawns the background sync loop.
+///
+/// * `poll_interval` - How often in seconds to poll for remote changes, 0 disables polling.
 pub async fn sync<T: Into<String>>(
     todoist_auth: T,
     sync_send: Sender<TaskSync>,
+    poll_interval: u64,
 ) -> TodoistDataStore {
     println!("Attempting to connect to Todoist");
 
     let token = todoist_auth.into();
 
-    let (root_tasks, tasks, completed_root, completed_tasks, subtasks, sync_token, inbox_project) =
-        get_initial_tasks(&token).await;
+    let initial_tasks = get_initial_tasks(&token).await;
 
     let (send, mut recv) = tokio::sync::mpsc::channel::<TodoistCommand>(100);
     let mutex = Arc::new(Mutex::new(false));
     let curr_syncing = mutex.clone();
 
-    let mut previous_token = sync_token;
+    let mut previous_token = initial_tasks.sync_token.clone();
+
+    spawn_poller(send.clone(), poll_interval);
 
     tokio::spawn(async move {
         let mut temp_id_mapping = HashMap::new();
         let mut buffer = Vec::with_capacity(100);
         let mut send_time = Local::now();
-        let mut buf_size = 0;
+
+        let client = reqwest::Client::new();
 
         while !recv.is_closed() {
-            let size = recv.recv_many(&mut buffer, 100).await;
-            buf_size += size;
+            recv.recv_many(&mut buffer, 100).await;
+
+            // Completed pages don't go through the sync endpoint, so fetch them separately.
+            let (pages, commands): (Vec<_>, Vec<_>) = buffer
+                .drain(..)
+                .partition(|f| matches!(f, TodoistCommand::LoadCompleted(_)));
+            buffer = commands;
+            let buf_size = buffer.len();
+            for page in pages {
+                let TodoistCommand::LoadCompleted(request) = page else {
+                    continue;
+                };
+                let task_sync = match fetch_completed_page(&client, &token, &request).await {
+                    Ok(response) => TaskSync::CompletedPage(request, response),
+                    Err(err) => {
+                        tracing::error!("Could not load the completed tasks because: {}", err);
+                        TaskSync::CompletedPageFailed
+                    }
+                };
+                let _ = sync_send.send(task_sync).await;
+            }
+            if buffer.is_empty() {
+                continue;
+            }
+
             if let Ok(mut currently_syncing) = curr_syncing.lock() {
                 *currently_syncing = true;
             }
@@ -274,7 +491,6 @@ pub async fn sync<T: Into<String>>(
             }
 
             if !buffer.is_empty() {
-                let client = reqwest::Client::new();
                 let mut params = HashMap::new();
                 params.insert("sync_token", previous_token.clone());
                 params.insert("resource_types", "[\"all\"]".to_string());
@@ -313,13 +529,8 @@ pub async fn sync<T: Into<String>>(
 
                 match serde_json::from_str::<TodoistSync>(&response) {
                     Ok(todoist_response) => {
-                        temp_id_mapping.extend(
-                            todoist_response
-                                .temp_id_mapping
-                                .clone()
-                                .unwrap()
-                                .into_iter(),
-                        );
+                        temp_id_mapping
+                            .extend(todoist_response.temp_id_mapping.clone().unwrap_or_default());
 
                         if let Some(status) = &todoist_response.sync_status {
                             for (sync_status_id, response) in status.iter() {
@@ -338,13 +549,12 @@ pub async fn sync<T: Into<String>>(
                         if recv.is_empty() {
                             previous_token = todoist_response.sync_token.clone();
                             buffer.clear();
-                            buf_size = 0;
                             tracing::info!(
                                 "Updated using the sync request: {:#?}",
                                 todoist_response
                             );
                             let _ = sync_send
-                                .send((todoist_response, temp_id_mapping.clone()))
+                                .send(TaskSync::Sync(todoist_response, temp_id_mapping.clone()))
                                 .await;
                         }
                     }
@@ -360,17 +570,36 @@ pub async fn sync<T: Into<String>>(
         }
     });
 
-    TodoistDataStore {
-        tasks,
-        completed_tasks,
-        subtasks,
-        root: root_tasks,
-        completed_root,
+    let mut data_store = TodoistDataStore {
+        tasks: initial_tasks.tasks,
+        completed_tasks: initial_tasks.completed_tasks,
+        subtasks: initial_tasks.subtasks,
+        root: initial_tasks.root,
+        completed_root: initial_tasks.completed_root,
+        completed_subtasks: HashMap::new(),
         tags: HashMap::new(),
         task_count: 0,
+        parent_index: ParentIndex::default(),
+        visible_rows: Default::default(),
+        subscribers: Default::default(),
         currently_syncing: mutex,
         command_sender: send,
-        inbox_project,
+        inbox_project: initial_tasks.inbox_project,
         temporary_mappings: HashMap::new(),
-    }
+        completed_history: initial_tasks.completed_history,
+        loading_completed_history: false,
+        empty_completed_pages: 0,
+        joined_at: initial_tasks.joined_at,
+    };
+    let labels = data_store
+        .tasks
+        .values()
+        .map(|f| &f.tags)
+        .chain(data_store.completed_tasks.values().map(|f| &f.task.tags))
+        .flatten()
+        .cloned()
+        .collect_vec();
+    data_store.register_labels(&labels);
+    data_store.rebuild_indexes();
+    data_store
 }
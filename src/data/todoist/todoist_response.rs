@@ -4,7 +4,8 @@ use serde::Deserialize;
 
 use super::{
     todoist_project::TodoistProject,
-    todoist_task::{TodoistCompletedItem, TodoistItem},
+    todoist_task::{TodoistCompletedItem, TodoistItem, TodoistNote},
+    todoist_user::TodoistUser,
 };
 
 #[derive(Deserialize, Debug)]
@@ -24,7 +25,9 @@ pub struct TodoistError {
 #[derive(serde::Deserialize, Debug)]
 pub struct TodoistSync {
     pub items: Option<Vec<TodoistItem>>,
+    pub notes: Option<Vec<TodoistNote>>,
     pub projects: Option<Vec<TodoistProject>>,
+    pub user: Option<TodoistUser>,
     pub sync_token: String,
     pub temp_id_mapping: Option<HashMap<String, String>>,
     pub sync_status: Option<HashMap<String, SyncStatus>>,
@@ -33,4 +36,5 @@ pub struct TodoistSync {
 #[derive(serde::Deserialize, Debug)]
 pub struct TodoistGetAllCompletedItemResponse {
     pub items: Vec<TodoistCompletedItem>,
+    pub next_cursor: Option<String>,
 }
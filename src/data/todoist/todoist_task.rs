@@ -1,6 +1,6 @@
-use chrono::DateTime;
+use chrono::{DateTime, NaiveDateTime};
 
-use crate::task::{CompletedTask, Priority, Task};
+use crate::task::{Comment, CompletedTask, Priority, Task};
 
 use super::todoist_command::TodoistDue;
 
@@ -14,7 +14,33 @@ pub struct TodoistItem {
     is_collapsed: bool,
     priority: usize,
     due: Option<TodoistDue>,
+    #[serde(default)]
+    pub labels: Vec<String>,
     pub completed_at: Option<String>,
+    pub added_at: Option<String>,
+    pub updated_at: Option<String>,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+impl TodoistItem {
+    /// Returns when this item was completed, if it has been completed.
+    pub fn time_completed(&self) -> Option<NaiveDateTime> {
+        self.completed_at.as_deref().and_then(parse_time)
+    }
+
+    /// Overwrites the fields of `task` that are synced with Todoist, keeping
+    /// any state that only exists locally.
+    pub fn update_task(self, task: &mut Task) {
+        let remote: Task = self.into();
+        task.title = remote.title;
+        task.priority = remote.priority;
+        task.tags = remote.tags;
+        task.due_date = remote.due_date;
+        task.opened = remote.opened;
+        task.created_at = remote.created_at.or(task.created_at);
+        task.updated_at = remote.updated_at.or(task.updated_at);
+    }
 }
 
 impl From<TodoistItem> for Task {
@@ -23,41 +49,101 @@ impl From<TodoistItem> for Task {
             progress: false,
             title: value.content + "\n" + &value.description,
             priority: todoist_to_priority(value.priority),
-            tags: Vec::new(),
+            tags: value.labels,
             due_date: value.due.map(|d| d.date),
+            comments: Vec::new(),
+            time_entries: Vec::new(),
+            pomodoros: 0,
+            estimate: None,
+            created_at: value.added_at.as_deref().and_then(parse_time),
+            updated_at: value.updated_at.as_deref().and_then(parse_time),
             opened: !value.is_collapsed,
         }
     }
 }
 
+/// A task returned when fetching the completed tasks.
 #[derive(serde::Deserialize, Debug)]
 pub struct TodoistCompletedItem {
     pub id: String,
-    pub task_id: Option<String>,
+    pub parent_id: Option<String>,
     pub project_id: Option<String>,
     pub section_id: Option<String>,
     content: Option<String>,
+    #[serde(default)]
+    description: String,
+    #[serde(default)]
+    priority: usize,
+    due: Option<TodoistDue>,
+    #[serde(default)]
+    pub labels: Vec<String>,
     completed_at: String,
+    added_at: Option<String>,
+    updated_at: Option<String>,
 }
 
-impl From<TodoistCompletedItem> for CompletedTask {
-    fn from(value: TodoistCompletedItem) -> Self {
-        CompletedTask {
-            task: Task {
-                progress: false,
-                title: value.content.unwrap_or_else(|| String::from("")),
-                priority: Priority::None,
-                tags: Vec::new(),
-                due_date: None,
-                opened: false,
-            },
-            time_completed: DateTime::parse_from_rfc3339(&value.completed_at)
-                .unwrap()
-                .naive_utc(),
+impl TodoistCompletedItem {
+    /// Returns the completed task, or None if the completion date could not be
+    /// parsed.
+    pub fn into_completed_task(self) -> Option<CompletedTask> {
+        let Some(time_completed) = parse_time(&self.completed_at) else {
+            tracing::warn!(
+                "Skipping the completed task {} as its completion date {} is invalid",
+                self.id,
+                self.completed_at
+            );
+            return None;
+        };
+        let parent_id = self.parent_id.clone();
+        let item = TodoistItem {
+            id: self.id,
+            content: self.content.unwrap_or_default(),
+            parent_id: self.parent_id,
+            child_order: 0,
+            description: self.description,
+            is_collapsed: true,
+            priority: self.priority,
+            due: self.due,
+            labels: self.labels,
+            completed_at: Some(self.completed_at),
+            added_at: self.added_at,
+            updated_at: self.updated_at,
+            is_deleted: false,
+        };
+        Some(CompletedTask {
+            parent_id,
+            ..CompletedTask::from_task(item.into(), time_completed)
+        })
+    }
+}
+
+/// A comment on a task.
+#[derive(serde::Deserialize, Debug, Clone)]
+pub struct TodoistNote {
+    pub id: String,
+    pub item_id: String,
+    pub content: String,
+    pub posted_at: String,
+    #[serde(default)]
+    pub is_deleted: bool,
+}
+
+impl From<TodoistNote> for Comment {
+    fn from(value: TodoistNote) -> Self {
+        Comment {
+            posted_at: parse_time(&value.posted_at).unwrap_or_default(),
+            id: value.id,
+            content: value.content,
         }
     }
 }
 
+fn parse_time(time: &str) -> Option<NaiveDateTime> {
+    DateTime::parse_from_rfc3339(time)
+        .ok()
+        .map(|time| time.naive_utc())
+}
+
 fn todoist_to_priority(priority: usize) -> Priority {
     match priority {
         2 => Priority::Low,
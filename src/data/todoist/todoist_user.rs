@@ -0,0 +1,4 @@
+#[derive(serde::Deserialize, Debug)]
+pub struct TodoistUser {
+    pub joined_at: Option<String>,
+}
@@ -0,0 +1,90 @@
+use std::collections::HashMap;
+
+use super::data_store::{DataTaskStore, TaskID, TaskIDRef};
+
+/// The open tasks in the order they are drawn, skipping the subtasks of
+/// closed tasks. Each row is a task id along with how deeply it is nested.
+///
+/// The stores build this when it is first needed and drop it when the tree
+/// changes or a task is opened or closed, so the task list, the cursor and the
+/// mouse all share one walk of the tree.
+#[derive(Default, Clone)]
+pub struct VisibleRows {
+    rows: Vec<(TaskID, usize)>,
+    positions: HashMap<TaskID, usize>,
+    /// The completed tasks that were completed on their own, by the parent
+    /// they had, which the progress of the parent counts.
+    completed_by_parent: HashMap<TaskID, Vec<TaskID>>,
+}
+
+impl VisibleRows {
+    pub fn new<T: DataTaskStore>(store: &T) -> Self {
+        let mut rows = Vec::new();
+        let mut to_visit = store
+            .root_tasks()
+            .iter()
+            .rev()
+            .map(|id| (id, 0))
+            .collect::<Vec<_>>();
+        while let Some((id, depth)) = to_visit.pop() {
+            let Some(task) = store.task(id) else {
+                continue;
+            };
+            if task.opened {
+                if let Some(subtasks) = store.subtasks(id) {
+                    to_visit.extend(subtasks.iter().rev().map(|id| (id, depth + 1)));
+                }
+            }
+            rows.push((id.clone(), depth));
+        }
+        let positions = rows
+            .iter()
+            .enumerate()
+            .map(|(row, (id, _))| (id.clone(), row))
+            .collect();
+        let mut completed_by_parent: HashMap<TaskID, Vec<TaskID>> = HashMap::new();
+        for id in store.completed_root_tasks() {
+            if let Some(parent_id) = store
+                .completed_task(id)
+                .and_then(|task| task.parent_id.as_ref())
+            {
+                completed_by_parent
+                    .entry(parent_id.clone())
+                    .or_default()
+                    .push(id.clone());
+            }
+        }
+        Self {
+            rows,
+            positions,
+            completed_by_parent,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.rows.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.rows.is_empty()
+    }
+
+    /// The id and depth of the task drawn at this row.
+    pub fn get(&self, row: usize) -> Option<(&str, usize)> {
+        self.rows.get(row).map(|(id, depth)| (id.as_str(), *depth))
+    }
+
+    /// The row the task with this id is drawn at.
+    pub fn row_of(&self, id: TaskIDRef) -> Option<usize> {
+        self.positions.get(id).copied()
+    }
+
+    /// The completed root tasks that had this task as their parent.
+    pub fn completed_children(&self, id: TaskIDRef) -> &[TaskID] {
+        self.completed_by_parent.get(id).map_or(&[], Vec::as_slice)
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (&str, usize)> {
+        self.rows.iter().map(|(id, depth)| (id.as_str(), *depth))
+    }
+}
@@ -9,17 +9,19 @@ use std::{
 // - Create a custom error type and return it from functions to handle it
 // outside of them
 
+use chrono::Local;
 use tokio::sync::mpsc::Receiver;
 
 use crate::{
     config::{Config, DataSource},
+    credentials::{self, TokenSource},
     data::{
         data_store::{DataTaskStore, DataTaskStoreKind},
         json_data_store::JsonDataStore,
         todoist::todoist_main::{sync, TaskSync},
     },
     error::AppError,
-    storage::json::{legacy::legacy_task::LegacyTaskStore, version::JSONVersion},
+    storage::json::{migration, validate, version::JSONVersion},
     utils,
 };
 
@@ -27,6 +29,7 @@ const DIR: &str = "dotodo";
 
 const CONFIG_FILE: &str = "config.yml";
 const DATA_FILE: &str = "data.json";
+const CREDENTIALS_FILE: &str = "credentials";
 
 fn should_overwrite(message: String) -> std::io::Result<bool> {
     println!("{}", message);
@@ -92,6 +95,50 @@ where
     Default::default()
 }
 
+/// Prints any problems with the loaded task data without stopping, for the
+/// commands that are not interactive. They are left to `dotodo doctor`.
+pub fn report_task_data(task_store: &DataTaskStoreKind) {
+    let DataTaskStoreKind::Json(task_store) = task_store else {
+        return;
+    };
+    let problems = validate::validate(task_store);
+    if problems.is_empty() {
+        return;
+    }
+
+    eprintln!("Found problems with the task data:");
+    for problem in &problems {
+        eprintln!("  {}", problem);
+    }
+    eprintln!("Run `dotodo doctor` to repair them.");
+}
+
+/// Checks the loaded task data for problems, offering to repair them.
+pub fn check_task_data(task_store: &mut DataTaskStoreKind) {
+    let DataTaskStoreKind::Json(task_store) = task_store else {
+        return;
+    };
+    let problems = validate::validate(task_store);
+    if problems.is_empty() {
+        return;
+    }
+
+    let mut message = String::from("Found problems with the task data:\n");
+    for problem in &problems {
+        message += &format!("  {}: {}\n", problem, problem.repair_description());
+    }
+    message += "If you continue, these will be repaired.";
+    match should_overwrite(message) {
+        Ok(true) => {
+            for problem in &problems {
+                problem.repair(task_store);
+            }
+            task_store.save();
+        }
+        Ok(false) | Err(_) => exit(0),
+    }
+}
+
 // FIXME: make the receiver optional
 pub async fn get_data(is_debug: bool) -> (Config, DataTaskStoreKind, Receiver<TaskSync>) {
     let (data_local_dir, config_local_dir) = if is_debug {
@@ -103,42 +150,76 @@ pub async fn get_data(is_debug: bool) -> (Config, DataTaskStoreKind, Receiver<Ta
         (dirs::data_local_dir(), dirs::config_local_dir())
     };
 
-    let config = load_from_file(
-        config_local_dir,
+    let mut config = load_from_file(
+        config_local_dir.clone(),
         CONFIG_FILE,
         serde_yaml::from_str::<Config>,
         "config",
     );
+    if let Err(err) = migrate_plain_token(&mut config, config_local_dir) {
+        eprintln!(
+            "Failed to move the Todoist token out of the config, {err}. The config is left unchanged, consider setting {} instead.",
+            credentials::TOKEN_ENV
+        );
+    }
     let (send, recv) = tokio::sync::mpsc::channel::<TaskSync>(100);
 
     // let tasks = sync();
     let task_store: DataTaskStoreKind = match &config.data_source {
-        DataSource::Json => {
-            DataTaskStoreKind::Json(load_from_file(
-                data_local_dir,
-                DATA_FILE,
-                // NOTE: This doesn't work:
-                // serde_json::from_str::<TaskStore>,
-                |x| {
-                    serde_json::from_str::<JSONVersion>(x)
-                        .map(Into::<JsonDataStore>::into) // NOTE: we might do something similar as below
-                        // if/when we introduce integers as ids again
-                        // This is because for some reason, serde tags
-                        // don't like int strings as keys
-                        // See: https://github.com/serde-rs/serde/issues/2672
-                        .or_else(|_| serde_json::from_str::<LegacyTaskStore>(x).map(|x| x.into()))
-                },
-                "task data",
-            ))
-        }
-        DataSource::Todoist(todoist_auth) => {
-            DataTaskStoreKind::Todoist(sync(todoist_auth, send).await)
+        DataSource::Json => DataTaskStoreKind::Json(load_from_file(
+            data_local_dir,
+            DATA_FILE,
+            |x| migration::load(x, Local::now().naive_local()),
+            "task data",
+        )),
+        DataSource::Todoist(token_source) => {
+            let todoist_auth = match token_source.token() {
+                Ok(token) => token,
+                Err(err) => {
+                    eprintln!("{err}");
+                    exit(1);
+                }
+            };
+            DataTaskStoreKind::Todoist(sync(todoist_auth, send, config.sync_interval).await)
         }
     };
 
     (config, task_store, recv)
 }
 
+/// Moves a token that was written directly in the config into a credentials
+/// file that only the user can read, then saves the config straight away so
+/// the token does not stay in it. The config is left alone if this fails.
+pub(crate) fn migrate_plain_token(
+    config: &mut Config,
+    config_local_dir: Option<PathBuf>,
+) -> Result<(), AppError> {
+    let DataSource::Todoist(TokenSource::Plain(token)) = &config.data_source else {
+        return Ok(());
+    };
+
+    let path = config_local_dir
+        .as_ref()
+        .map(|dir| dir.join(DIR).join(CREDENTIALS_FILE))
+        .ok_or_else(|| {
+            AppError::InvalidState(String::from("Failed to determine the config directory"))
+        })?;
+
+    credentials::write_credentials(&path, token)?;
+    eprintln!(
+        "Moved the Todoist token from the config into '{}'",
+        path.display()
+    );
+    config.data_source = DataSource::Todoist(TokenSource::File(path));
+    save_to_file(
+        config_local_dir,
+        CONFIG_FILE,
+        || serde_yaml::to_string(config),
+        "config",
+    );
+    Ok(())
+}
+
 fn save_to_file<T, F, E>(local_dir: Option<PathBuf>, file_name: &str, ser_f: F, kind: &str)
 where
     T: AsRef<[u8]>,
@@ -178,6 +259,15 @@ where
 pub fn save_config(config: &Config, task_store: DataTaskStoreKind) {
     task_store.save();
 
+    // The token could not be moved out of the config, so the config is left
+    // as the user wrote it.
+    if matches!(
+        config.data_source,
+        DataSource::Todoist(TokenSource::Plain(_))
+    ) {
+        return;
+    }
+
     save_to_file(
         if utils::IS_DEBUG {
             Some(std::env::current_dir().unwrap())
@@ -191,7 +281,7 @@ pub fn save_config(config: &Config, task_store: DataTaskStoreKind) {
 }
 
 pub fn save_task_json(task_store: &JsonDataStore, is_debug: bool) {
-    let json = JSONVersion::V1(task_store.clone());
+    let json = JSONVersion::V3(task_store.clone());
 
     save_to_file(
         if is_debug {
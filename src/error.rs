@@ -6,6 +6,8 @@ use serde_json::Error as JsonError;
 use serde_yaml::Error as YamlError;
 use thiserror::Error;
 
+use crate::data::data_store::TaskID;
+
 #[derive(Error, Debug)]
 pub enum AppError {
     #[error("Please use a valid colour")]
@@ -28,12 +30,60 @@ pub enum AppError {
 
     #[error("Invalid state: {0}")]
     InvalidState(String),
+
+    #[error("Could not read the token: {0}")]
+    InvalidToken(String),
+
+    #[error("Invalid duration: {0}, use hours and minutes (eg: 1h30m)")]
+    InvalidDuration(String),
+
+    #[error("Invalid argument: {0}")]
+    InvalidArgument(String),
+
+    #[error("Unsupported data version: {0}, it may have been saved by a newer version of dotodo")]
+    UnsupportedVersion(String),
+
+    #[error("Could not migrate the data from version {0}: {1}")]
+    MigrationFailed(usize, Box<AppError>),
+
+    #[error("{0}")]
+    Store(StoreError),
+}
+
+/// Why a change to the tasks could not be made, the store is left unchanged.
+#[derive(Error, Debug, PartialEq, Eq)]
+pub enum StoreError {
+    #[error("Could not find the task {0}")]
+    NotFound(TaskID),
+
+    #[error("Could not find the comment {0}")]
+    CommentNotFound(String),
+
+    #[error("A comment can not be empty")]
+    EmptyComment,
+
+    #[error("Invalid move: {0}")]
+    InvalidMove(String),
+
+    #[error("Can not move the task {0} below itself")]
+    CycleDetected(TaskID),
+
+    #[error("{0}")]
+    Backend(String),
 }
 
 impl AppError {
     pub fn invalid_state<T: Into<String>>(msg: T) -> AppError {
         AppError::InvalidState(msg.into())
     }
+
+    /// The message shown to the user when an action fails.
+    pub fn message(self) -> String {
+        match self {
+            AppError::InvalidState(msg) => msg,
+            err => err.to_string(),
+        }
+    }
 }
 
 impl From<ParseIntError> for AppError {
@@ -60,6 +110,12 @@ impl From<IoError> for AppError {
     }
 }
 
+impl From<StoreError> for AppError {
+    fn from(err: StoreError) -> AppError {
+        AppError::Store(err)
+    }
+}
+
 impl From<ParseError> for AppError {
     fn from(err: ParseError) -> AppError {
         AppError::InvalidDate(err)
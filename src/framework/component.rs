@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use tui::{
     layout::Rect,
     widgets::{StatefulWidget, Widget},
@@ -8,6 +10,11 @@ use crate::app::App;
 
 use super::event::{AppEvent, PostEvent};
 
+/// How often a component that animates is drawn again (eg: the syncing spinner).
+pub const ANIMATION_TICK: Duration = Duration::from_millis(100);
+/// How often a component that counts in seconds is drawn again (eg: a timer).
+pub const SECOND_TICK: Duration = Duration::from_secs(1);
+
 /// A component that is able to be drawn on the screen.
 pub trait Component {
     /// Draws the component onto the [[Drawer]]
@@ -27,6 +34,15 @@ pub trait Component {
 
     fn update_layout(&mut self, draw_area: Rect);
 
+    /// Called on every tick of the app, regardless of which layer is focused.
+    fn tick(&mut self, _app: &mut App) {}
+
+    /// How often the component changes without any input (eg: a countdown), the
+    /// app only ticks and redraws by itself while a component needs it.
+    fn tick_rate(&self, _app: &App) -> Option<Duration> {
+        None
+    }
+
     fn mount(&mut self, _app: &mut App) {}
 
     /// This is called before the pop_overlay callback.
@@ -1,3 +1,5 @@
+use std::time::Duration;
+
 use crate::app::App;
 
 use super::{
@@ -24,6 +26,16 @@ impl ScreenManager {
         self.overlays.pop()
     }
 
+    /// How often the status line or any of the overlays change by themselves,
+    /// the fastest of them is used.
+    pub fn tick_rate(&self) -> Option<Duration> {
+        self.overlays
+            .iter()
+            .filter_map(|overlay| overlay.tick_rate(&self.app))
+            .chain(self.app.status_line.tick_rate(&self.app))
+            .min()
+    }
+
     pub(crate) fn handle_post_event(&mut self, post_event: PostEvent) {
         match post_event.action {
             Action::PushLayer(mut overlay) => {
@@ -80,11 +80,56 @@ fn task_list_help_entry(config: &Config) -> Vec<KeyBinding<'static>> {
             "Make the selected task not a subtask of the parent",
             App::move_subtask_level_down,
         ),
+        KeyBinding::register_key(
+            config.visual_mode_key,
+            "Starts/ends selecting a range of tasks",
+            App::toggle_visual_mode,
+        ),
+        KeyBinding::register_key(
+            config.visual_line_mode_key,
+            "Starts/ends selecting a range of tasks",
+            App::toggle_visual_mode,
+        ),
+        KeyBinding::register_key(
+            config.select_all_key,
+            "Selects/deselects all the tasks",
+            App::toggle_select_all,
+        ),
+        KeyBinding::register_key(
+            config.mark_key,
+            "Selects/deselects the task",
+            App::toggle_mark,
+        ),
+        KeyBinding::register_key(
+            config.clear_selection_key,
+            "Clears the selected tasks",
+            App::clear_selection,
+        ),
         KeyBinding::register_key(
             config.add_date,
             "Adds a date to the selected task",
             App::create_due_date_dialog,
         ),
+        KeyBinding::register_key(
+            config.comments_key,
+            "Shows the comments of the selected task",
+            App::create_comments_menu,
+        ),
+        KeyBinding::register_key(
+            config.timer_key,
+            "Starts/stops the timer on the selected task",
+            App::toggle_timer,
+        ),
+        KeyBinding::register_key(
+            config.pomodoro_key,
+            "Starts a pomodoro on the selected task",
+            App::create_pomodoro,
+        ),
+        KeyBinding::register_key(
+            config.estimate_key,
+            "Sets the estimate of the selected task",
+            App::create_estimate_dialog,
+        ),
         KeyBinding::register_key(
             config.refresh_key,
             "Refresh data from data store",
@@ -94,20 +139,27 @@ fn task_list_help_entry(config: &Config) -> Vec<KeyBinding<'static>> {
 }
 
 fn completed_list_input(app: &mut App, key_event: KeyEvent) -> Result<PostEvent, AppError> {
+    let rows = app.completed_list.rows(&app.task_store).len();
     let result = utils::handle_key_movement(
         &app.config,
         key_event,
         &mut app.completed_list.selected_index,
-        app.task_store.completed_root_tasks().len(),
+        rows,
     );
 
     if !result.propegate_further {
+        CompletedList::load_history_if_needed(app);
         return Ok(result);
     }
 
     if app.config.restore_key.is_pressed(key_event) {
-        CompletedList::restore_task(app);
+        CompletedList::restore_task(app)?;
         Ok(PostEvent::noop(false))
+    } else if app.config.flip_subtask_key.is_pressed(key_event) {
+        CompletedList::flip_day(app);
+        Ok(PostEvent::noop(false))
+    } else if app.config.tag_menu.is_pressed(key_event) {
+        app.create_completed_tag_filter()
     } else {
         Ok(PostEvent::noop(true))
     }
@@ -127,6 +179,11 @@ pub fn universal_input_keys(config: &Config) -> Vec<KeyBinding<'static>> {
             "Goes to the completed task menu",
             App::go_to_completed_list,
         ),
+        KeyBinding::register_key(
+            config.stats_menu_key,
+            "Goes to the statistics",
+            App::go_to_stats,
+        ),
         KeyBinding::register_key(
             config.open_help_key,
             "Opens the help menu",
@@ -143,10 +200,11 @@ pub fn universal_input_keys(config: &Config) -> Vec<KeyBinding<'static>> {
 }
 
 fn completed_list_help_entries(config: &Config) -> Vec<KeyBinding<'static>> {
-    vec![KeyBinding::new(
-        config.restore_key,
-        "Restores the selected task",
-    )]
+    vec![
+        KeyBinding::new(config.restore_key, "Restores the selected task"),
+        KeyBinding::new(config.flip_subtask_key, "Collapses/expands the day"),
+        KeyBinding::new(config.tag_menu, "Only shows the tasks with a tag"),
+    ]
 }
 
 pub fn key_event(
@@ -154,7 +212,7 @@ pub fn key_event(
     key_event: KeyEvent,
 ) -> Result<PostEvent, AppError> {
     let event = match screen_manager.app.mode {
-        Mode::Overlay => Overlay::key_event(screen_manager, key_event),
+        Mode::Overlay | Mode::Stats => Overlay::key_event(screen_manager, key_event),
         Mode::CurrentTasks => task_list_input(&mut screen_manager.app, key_event),
         Mode::CompletedTasks => completed_list_input(&mut screen_manager.app, key_event),
     };
@@ -191,7 +249,7 @@ fn task_list_input(app: &mut App, key_event: KeyEvent) -> Result<PostEvent, AppE
         &app.config,
         key_event,
         &mut app.task_list.selected_index,
-        app.task_store.find_tasks_draw_size(),
+        app.task_store.visible_rows().len(),
     );
 
     if !result.propegate_further {
@@ -224,7 +282,7 @@ impl Mode {
         match self {
             Mode::CurrentTasks => task_list_help_entry(config),
             Mode::CompletedTasks => completed_list_help_entries(config),
-            Mode::Overlay => vec![],
+            Mode::Stats | Mode::Overlay => vec![],
         }
     }
 }
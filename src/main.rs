@@ -1,19 +1,19 @@
-use data::{
-    data_store::DataTaskStoreKind,
-    todoist::todoist_main::{handle_sync, TaskSync},
-};
+use data::todoist::todoist_main::TaskSync;
 use tracing_subscriber::{fmt::Layer, prelude::__tracing_subscriber_SubscriberExt};
 
 mod actions;
 mod app;
+mod cli;
 mod component;
 mod config;
+mod credentials;
 mod data;
 mod data_io;
 mod error;
 mod framework;
 mod input;
 mod screens;
+mod stats;
 mod storage;
 mod task;
 mod tests;
@@ -25,27 +25,25 @@ use crossterm::{
     execute,
     terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
 };
-use error::AppError;
 use framework::{
-    component::{Component, Drawer},
+    component::{Component, Drawer, ANIMATION_TICK},
     event::PostEvent,
     screen_manager::ScreenManager,
 };
-use futures::{FutureExt, TryStreamExt};
-use tokio::{sync::mpsc::Receiver, time::Instant};
+use futures::{FutureExt, Stream, TryStreamExt};
+use tokio::{
+    sync::mpsc::Receiver,
+    time::{Instant, Interval, MissedTickBehavior},
+};
 use tracing_subscriber::Registry;
 use tui::{
-    backend::CrosstermBackend,
+    backend::{Backend, CrosstermBackend},
     layout::{Constraint, Layout},
     style::Color,
     Terminal,
 };
 
-use std::{
-    error::Error,
-    io::{self, Stdout},
-    time::Duration,
-};
+use std::{error::Error, io, time::Duration};
 
 use crate::{app::App, screens::main_screen::MainScreen};
 
@@ -57,7 +55,28 @@ async fn main() -> Result<(), Box<dyn Error>> {
     #[cfg(not(debug_assertions))]
     let is_debug = false;
 
-    let (config, tasks, rx) = data_io::get_data(is_debug).await;
+    let command = match cli::parse_args(std::env::args().skip(1)) {
+        Ok(command) => command,
+        Err(err) => {
+            eprintln!("{}", err);
+            std::process::exit(1);
+        }
+    };
+    if command == Some(cli::Command::Help) {
+        println!("{}", cli::USAGE);
+        return Ok(());
+    }
+
+    let (config, mut tasks, rx) = data_io::get_data(is_debug).await;
+
+    if let Some(command) = command {
+        if !matches!(command, cli::Command::Doctor { .. }) {
+            data_io::report_task_data(&tasks);
+        }
+        cli::run(command, &mut tasks);
+        return Ok(());
+    }
+    data_io::check_task_data(&mut tasks);
 
     enable_raw_mode()?;
 
@@ -72,7 +91,27 @@ async fn main() -> Result<(), Box<dyn Error>> {
         overlays: vec![],
     };
 
-    let result = start_app(&mut screen_manager, &mut terminal, rx).await;
+    let logger = Logger::default();
+
+    let logfile = tracing_appender::rolling::hourly(
+        dirs::data_dir().unwrap().join("dotodo").to_str().unwrap(),
+        "logs",
+    );
+    let logfile_layer = Layer::default().with_writer(logfile);
+
+    let subscriber = Registry::default().with(logger.clone()).with(logfile_layer);
+    tracing::subscriber::set_global_default(subscriber).expect("Failed to set subscriber");
+
+    tracing::info!("Todoist Logger is active");
+
+    let result = start_app(
+        &mut screen_manager,
+        &mut terminal,
+        rx,
+        EventStream::new(),
+        logger,
+    )
+    .await;
 
     // Shutting down application
 
@@ -96,73 +135,71 @@ async fn main() -> Result<(), Box<dyn Error>> {
     Ok(())
 }
 
-pub async fn start_app(
+/// Ticks every `period`, starting one period from now.
+fn tick_interval(period: Duration) -> Interval {
+    let mut interval = tokio::time::interval_at(Instant::now() + period, period);
+    interval.set_missed_tick_behavior(MissedTickBehavior::Delay);
+    interval
+}
+
+/// Runs the app until it is shut down. The screen is only drawn again after an
+/// event or a sync, or on each tick while a component changes by itself (eg: the
+/// syncing spinner or a running timer), so an idle app does not wake up.
+pub async fn start_app<B: Backend, S: Stream<Item = io::Result<Event>> + Unpin>(
     screen_manager: &mut ScreenManager,
-    terminal: &mut Terminal<CrosstermBackend<Stdout>>,
+    terminal: &mut Terminal<B>,
     mut rx: Receiver<TaskSync>,
+    mut event_stream: S,
+    mut logger: Logger,
 ) -> io::Result<()> {
     let mut main_screen = MainScreen::new();
 
-    let mut logger = Logger::default();
-
-    let logfile = tracing_appender::rolling::hourly(
-        dirs::data_dir().unwrap().join("dotodo").to_str().unwrap(),
-        "logs",
-    );
-    let logfile_layer = Layer::default().with_writer(logfile);
-
-    let subscriber = Registry::default().with(logger.clone()).with(logfile_layer);
-    tracing::subscriber::set_global_default(subscriber).expect("Failed to set subscriber");
-
-    tracing::info!("Todoist Logger is active");
-
-    let mut interval = tokio::time::interval_at(Instant::now(), Duration::from_millis(100));
-    let mut event_stream = EventStream::new();
+    let mut interval = tick_interval(ANIMATION_TICK);
+    let mut dirty = true;
+    let mut ticking = None;
 
     while !screen_manager.app.should_shutdown() {
-        terminal.draw(|f| {
-            let draw_size = f.area();
-
-            let mut drawer = Drawer::new(f);
-
-            let chunk = Layout::default()
-                .direction(tui::layout::Direction::Vertical)
-                .constraints([Constraint::Min(1), Constraint::Length(1)])
-                .split(draw_size);
-
-            main_screen.update_layout(chunk[0]);
-            main_screen.draw(&screen_manager.app, &mut drawer);
+        if dirty {
+            dirty = false;
+            draw(terminal, screen_manager, &mut main_screen, &mut logger)?;
+        }
 
-            for overlay in screen_manager.overlays.iter_mut() {
-                overlay.update_layout(chunk[0])
-            }
-            for overlay in screen_manager.overlays.iter() {
-                overlay.draw(&screen_manager.app, &mut drawer)
+        let tick_rate = screen_manager
+            .tick_rate()
+            .into_iter()
+            .chain(logger.tick_rate(&screen_manager.app))
+            .min();
+        if tick_rate != ticking {
+            if let Some(tick_rate) = tick_rate {
+                interval = tick_interval(tick_rate);
             }
-
-            screen_manager.app.status_line.update_layout(chunk[1]);
-            screen_manager
-                .app
-                .status_line
-                .draw(&screen_manager.app, &mut drawer);
-
-            logger.update_layout(draw_size);
-            logger.draw(&screen_manager.app, &mut drawer);
-        })?;
-
-        let tick = interval.tick();
+        }
+        ticking = tick_rate;
         let crossterm = &mut event_stream.try_next().fuse();
 
         tokio::select! {
-            _ = tick => {
+            _ = interval.tick(), if ticking.is_some() => {
+                dirty = true;
                 screen_manager.app.tick += 1;
+                for overlay in screen_manager.overlays.iter_mut() {
+                    overlay.tick(&mut screen_manager.app);
+                }
             }
             Some(sync) = rx.recv() => {
-                if let DataTaskStoreKind::Todoist(todoist) = &mut screen_manager.app.task_store {
-                    handle_sync(todoist, sync);
-                }
+                dirty = true;
+                screen_manager.app.handle_sync(sync);
             }
-            Ok(Some(event)) = crossterm => {
+            event = crossterm => {
+                let event = match event {
+                    Ok(Some(event)) => event,
+                    // Nothing more can be read from the terminal.
+                    Ok(None) => return Ok(()),
+                    Err(err) => {
+                        tracing::error!("Could not read the terminal event because: {}", err);
+                        continue;
+                    }
+                };
+                dirty = true;
                 match event {
                     Event::Key(key_event) => {
                         if key_event.code == KeyCode::Char('c')
@@ -178,7 +215,8 @@ pub async fn start_app(
                             let result = input::key_event(screen_manager, key_event);
                             match result {
                                 Ok(post_event) => screen_manager.handle_post_event(post_event),
-                                Err(AppError::InvalidState(msg)) => {
+                                Err(err) => {
+                                    let msg = err.message();
                                     let prev_mode = screen_manager.app.mode;
                                     screen_manager.push_layer(MessageBox::new(
                                         "An error occured".to_string(),
@@ -191,7 +229,6 @@ pub async fn start_app(
                                         0,
                                     ));
                                 }
-                                _ => {}
                             }
                         }
                     }
@@ -215,3 +252,41 @@ pub async fn start_app(
     }
     Ok(())
 }
+
+fn draw<B: Backend>(
+    terminal: &mut Terminal<B>,
+    screen_manager: &mut ScreenManager,
+    main_screen: &mut MainScreen,
+    logger: &mut Logger,
+) -> io::Result<()> {
+    terminal.draw(|f| {
+        let draw_size = f.area();
+
+        let mut drawer = Drawer::new(f);
+
+        let chunk = Layout::default()
+            .direction(tui::layout::Direction::Vertical)
+            .constraints([Constraint::Min(1), Constraint::Length(1)])
+            .split(draw_size);
+
+        main_screen.update_layout(chunk[0]);
+        main_screen.draw(&screen_manager.app, &mut drawer);
+
+        for overlay in screen_manager.overlays.iter_mut() {
+            overlay.update_layout(chunk[0])
+        }
+        for overlay in screen_manager.overlays.iter() {
+            overlay.draw(&screen_manager.app, &mut drawer)
+        }
+
+        screen_manager.app.status_line.update_layout(chunk[1]);
+        screen_manager
+            .app
+            .status_line
+            .draw(&screen_manager.app, &mut drawer);
+
+        logger.update_layout(draw_size);
+        logger.draw(&screen_manager.app, &mut drawer);
+    })?;
+    Ok(())
+}
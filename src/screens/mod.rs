@@ -1 +1,2 @@
 pub mod main_screen;
+pub mod stats_screen;
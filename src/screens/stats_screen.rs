@@ -0,0 +1,223 @@
+use chrono::{Duration, Local};
+use tui::{
+    layout::{Constraint, Direction, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{BarChart, Paragraph},
+};
+
+use crate::{
+    app::{App, Mode},
+    framework::{
+        component::{Component, Drawer},
+        event::{AppEvent, PostEvent},
+    },
+    stats::Stats,
+    utils,
+};
+
+const COMPONENT_TYPE: Mode = Mode::Stats;
+
+/// Shows how many tasks have been completed along with a summary of the
+/// open tasks. This is pushed over the main screen, and is popped to go to
+/// one of the lists again.
+pub struct StatsScreen {
+    by_day_area: Rect,
+    by_week_area: Rect,
+    open_area: Rect,
+    summary_area: Rect,
+    /// The mode to go to once the statistics are closed.
+    next_mode: Mode,
+}
+
+impl StatsScreen {
+    pub fn new() -> StatsScreen {
+        StatsScreen {
+            by_day_area: Rect::default(),
+            by_week_area: Rect::default(),
+            open_area: Rect::default(),
+            summary_area: Rect::default(),
+            next_mode: Mode::CurrentTasks,
+        }
+    }
+
+    fn draw_open_tasks(&self, app: &App, stats: &Stats, drawer: &mut Drawer) {
+        let mut lines = vec![Line::from(Span::styled(
+            "By priority",
+            Style::default().add_modifier(Modifier::BOLD),
+        ))];
+        for (priority, count) in &stats.open_by_priority {
+            lines.push(Line::from(vec![
+                Span::styled(
+                    format!("{:<8}", priority.display_string()),
+                    Style::default().fg(priority.colour(&app.config)),
+                ),
+                Span::raw(count.to_string()),
+            ]));
+        }
+        lines.push(Line::default());
+        lines.push(Line::from(Span::styled(
+            "By tag",
+            Style::default().add_modifier(Modifier::BOLD),
+        )));
+        if stats.open_by_tag.is_empty() {
+            lines.push(Line::from("None"));
+        }
+        for (name, count) in &stats.open_by_tag {
+            lines.push(Line::from(format!("{:<8} {}", name, count)));
+        }
+
+        let paragraph = Paragraph::new(lines).block(utils::ui::generate_default_block(
+            app,
+            "Open tasks",
+            COMPONENT_TYPE,
+        ));
+        drawer.draw_widget(paragraph, self.open_area);
+    }
+
+    fn draw_summary(&self, app: &App, stats: &Stats, drawer: &mut Drawer) {
+        let overdue_style = if stats.overdue > 0 {
+            Style::default().fg(app.config.high_priority_colour)
+        } else {
+            Style::default()
+        };
+        let lines = vec![
+            Line::from(vec![
+                Span::raw("Overdue                  "),
+                Span::styled(stats.overdue.to_string(), overdue_style),
+            ]),
+            Line::from(format!(
+                "Average time to complete {}",
+                stats
+                    .average_time_to_complete
+                    .map_or_else(|| String::from("Unknown"), format_age)
+            )),
+            Line::from(format!(
+                "Streak                   {} day{}",
+                stats.streak,
+                if stats.streak == 1 { "" } else { "s" }
+            )),
+        ];
+
+        let paragraph = Paragraph::new(lines).block(utils::ui::generate_default_block(
+            app,
+            "Summary",
+            COMPONENT_TYPE,
+        ));
+        drawer.draw_widget(paragraph, self.summary_area);
+    }
+}
+
+/// Formats long durations in days and hours, eg: "3d 4h".
+fn format_age(duration: Duration) -> String {
+    if duration.num_days() > 0 {
+        format!("{}d {}h", duration.num_days(), duration.num_hours() % 24)
+    } else {
+        utils::format_duration(duration)
+    }
+}
+
+impl Component for StatsScreen {
+    fn draw(&self, app: &App, drawer: &mut Drawer) {
+        let stats = Stats::new(&app.task_store, Local::now().date_naive());
+
+        let labels = stats
+            .completed_by_day
+            .iter()
+            .map(|(day, count)| (day.format("%d").to_string(), *count))
+            .collect::<Vec<_>>();
+        let data = labels
+            .iter()
+            .map(|(label, count)| (label.as_str(), *count))
+            .collect::<Vec<_>>();
+        let by_day = BarChart::default()
+            .block(utils::ui::generate_default_block(
+                app,
+                "Completed per day",
+                COMPONENT_TYPE,
+            ))
+            .bar_width(3)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(app.config.selected_task_colour))
+            .data(data.as_slice());
+        drawer.draw_widget(by_day, self.by_day_area);
+
+        let labels = stats
+            .completed_by_week
+            .iter()
+            .map(|(week, count)| (week.format("%d/%m").to_string(), *count))
+            .collect::<Vec<_>>();
+        let data = labels
+            .iter()
+            .map(|(label, count)| (label.as_str(), *count))
+            .collect::<Vec<_>>();
+        let by_week = BarChart::default()
+            .block(utils::ui::generate_default_block(
+                app,
+                "Completed per week",
+                COMPONENT_TYPE,
+            ))
+            .bar_width(5)
+            .bar_gap(1)
+            .bar_style(Style::default().fg(app.config.selected_task_colour))
+            .data(data.as_slice());
+        drawer.draw_widget(by_week, self.by_week_area);
+
+        self.draw_open_tasks(app, &stats, drawer);
+        self.draw_summary(app, &stats, drawer);
+    }
+
+    fn key_event(&mut self, app: &mut App, key_event: crossterm::event::KeyEvent) -> PostEvent {
+        let config = &app.config;
+        let next_mode = if config.tasks_menu_key.is_pressed(key_event) {
+            Mode::CurrentTasks
+        } else if config.completed_tasks_menu_key.is_pressed(key_event) {
+            Mode::CompletedTasks
+        } else if key_event.code == crossterm::event::KeyCode::Esc {
+            self.next_mode
+        } else {
+            return PostEvent::noop(true);
+        };
+        self.next_mode = next_mode;
+        PostEvent::pop_layer(None)
+    }
+
+    /// The lists underneath are hidden, so they are not clicked through.
+    fn mouse_event(
+        &mut self,
+        _app: &mut App,
+        _mouse_event: crossterm::event::MouseEvent,
+    ) -> PostEvent {
+        PostEvent::noop(false)
+    }
+
+    fn mount(&mut self, app: &mut App) {
+        self.next_mode = app.mode;
+        app.mode = Mode::Stats;
+    }
+
+    fn unmount(&mut self, app: &mut App, _event: Option<AppEvent>) -> PostEvent {
+        app.mode = self.next_mode;
+        PostEvent::noop(false)
+    }
+
+    fn update_layout(&mut self, area: Rect) {
+        let rows = Layout::default()
+            .direction(Direction::Vertical)
+            .constraints([Constraint::Percentage(60), Constraint::Percentage(40)])
+            .split(area);
+        let charts = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(55), Constraint::Percentage(45)])
+            .split(rows[0]);
+        let lists = Layout::default()
+            .direction(Direction::Horizontal)
+            .constraints([Constraint::Percentage(50), Constraint::Percentage(50)])
+            .split(rows[1]);
+
+        self.by_day_area = charts[0];
+        self.by_week_area = charts[1];
+        self.open_area = lists[0];
+        self.summary_area = lists[1];
+    }
+}
@@ -0,0 +1,134 @@
+use std::collections::HashMap;
+
+use chrono::{Datelike, Duration, NaiveDate};
+use itertools::Itertools;
+
+use crate::{
+    data::data_store::DataTaskStore,
+    task::Priority,
+    utils::task_position::{completed_tasks, open_tasks},
+};
+
+/// The number of days shown in the completions per day.
+const DAYS: i64 = 14;
+/// The number of weeks shown in the completions per week.
+const WEEKS: i64 = 8;
+
+/// Statistics about the open and completed tasks in a store.
+pub struct Stats {
+    /// The number of tasks completed on each of the last days, oldest first.
+    pub completed_by_day: Vec<(NaiveDate, u64)>,
+    /// The number of tasks completed in each of the last weeks, by the Monday
+    /// starting the week, oldest first.
+    pub completed_by_week: Vec<(NaiveDate, u64)>,
+    pub open_by_priority: Vec<(Priority, usize)>,
+    /// The number of open tasks with each tag by the tag name, the most used first.
+    pub open_by_tag: Vec<(String, usize)>,
+    /// The number of open tasks that are past their due date.
+    pub overdue: usize,
+    /// The average time from adding a task to completing it, only counting
+    /// the tasks that know when they were added.
+    pub average_time_to_complete: Option<Duration>,
+    /// The number of days in a row with a completed task, up until today (or
+    /// yesterday if nothing has been completed today yet).
+    pub streak: usize,
+}
+
+impl Stats {
+    pub fn new<T: DataTaskStore>(store: &T, today: NaiveDate) -> Stats {
+        let completed = completed_tasks(store);
+        let completed_on = completed
+            .iter()
+            .map(|(_, completed_task)| completed_task.time_completed.date())
+            .counts();
+        let count_on = |day: NaiveDate| completed_on.get(&day).copied().unwrap_or(0) as u64;
+
+        let completed_by_day = (0..DAYS)
+            .rev()
+            .map(|days| today - Duration::days(days))
+            .map(|day| (day, count_on(day)))
+            .collect();
+
+        let week_start = today - Duration::days(today.weekday().num_days_from_monday() as i64);
+        let completed_by_week = (0..WEEKS)
+            .rev()
+            .map(|weeks| week_start - Duration::weeks(weeks))
+            .map(|week| {
+                let count = (0..7).map(|day| count_on(week + Duration::days(day))).sum();
+                (week, count)
+            })
+            .collect();
+
+        let open = open_tasks(store);
+        let open_by_priority = [
+            Priority::High,
+            Priority::Normal,
+            Priority::Low,
+            Priority::None,
+        ]
+        .into_iter()
+        .map(|priority| {
+            let count = open
+                .iter()
+                .filter(|(_, task)| task.priority == priority)
+                .count();
+            (priority, count)
+        })
+        .collect();
+
+        let mut open_by_tag: HashMap<String, usize> = HashMap::new();
+        for tag in open.iter().flat_map(|(_, task)| &task.tags) {
+            let name = store
+                .tags()
+                .get(tag)
+                .map_or_else(|| tag.clone(), |tag| tag.name.clone());
+            *open_by_tag.entry(name).or_default() += 1;
+        }
+        let open_by_tag = open_by_tag
+            .into_iter()
+            .sorted_by(|a, b| b.1.cmp(&a.1).then_with(|| a.0.cmp(&b.0)))
+            .collect();
+
+        let overdue = open
+            .iter()
+            .filter(|(_, task)| task.due_date.is_some_and(|due_date| due_date < today))
+            .count();
+
+        let times_to_complete = completed
+            .iter()
+            .filter_map(|(_, completed_task)| {
+                Some(completed_task.time_completed - completed_task.task.created_at?)
+            })
+            .filter(|duration| *duration >= Duration::zero())
+            .collect_vec();
+        let average_time_to_complete = if times_to_complete.is_empty() {
+            None
+        } else {
+            let total = times_to_complete
+                .iter()
+                .fold(Duration::zero(), |acc, f| acc + *f);
+            Some(total / times_to_complete.len() as i32)
+        };
+
+        let mut streak = 0;
+        let mut day = if completed_on.contains_key(&today) {
+            Some(today)
+        } else {
+            today.pred_opt()
+        };
+        while let Some(current) = day.filter(|day| completed_on.contains_key(day)) {
+            streak += 1;
+            day = current.pred_opt();
+        }
+
+        Stats {
+            completed_by_day,
+            completed_by_week,
+            open_by_priority,
+            open_by_tag,
+            overdue,
+            average_time_to_complete,
+            streak,
+        }
+    }
+}
@@ -1,14 +1,12 @@
 use chrono::{NaiveDate, NaiveDateTime};
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use serde_json::{json, Value};
 use serde_with::skip_serializing_none;
 
 use std::collections::HashMap;
 
-use crate::{
-    data::json_data_store::JsonDataStore,
-    task::{CompletedTask, Priority, Tag, Task},
-};
+use crate::task::{Priority, Tag};
 
 #[skip_serializing_none]
 #[derive(Clone, PartialEq, Default, Deserialize, Serialize)]
@@ -40,30 +38,22 @@ pub struct LegacyTaskStore {
     pub auto_sort: bool,
 }
 
-impl From<LegacyTask> for Task {
-    fn from(t: LegacyTask) -> Self {
-        Task {
-            progress: t.progress,
-            title: t.title,
-            priority: t.priority,
-            tags: t.tags.into_iter().map(|f| f.to_string()).collect(),
-            due_date: t.due_date,
-            opened: t.opened,
-        }
-    }
-}
-
-impl From<LegacyCompletedTask> for CompletedTask {
-    fn from(t: LegacyCompletedTask) -> Self {
-        CompletedTask {
-            task: t.task.into(),
-            time_completed: t.time_completed,
-        }
+impl LegacyTask {
+    /// The task as version 1 saved it, without its subtasks.
+    fn to_v1(&self) -> Value {
+        json!({
+            "progress": self.progress,
+            "title": self.title,
+            "priority": self.priority,
+            "tags": self.tags.iter().map(usize::to_string).collect_vec(),
+            "due_date": self.due_date,
+            "opened": self.opened,
+        })
     }
 }
 
 fn add_to_task(
-    tasks: &mut HashMap<String, Task>,
+    tasks: &mut HashMap<String, Value>,
     subtasks: &mut HashMap<String, Vec<String>>,
     name: &LegacyTask,
     parent_id: usize,
@@ -75,56 +65,66 @@ fn add_to_task(
     } else {
         subtasks.insert(parent_id.to_string(), vec![(*id_gen).to_string()]);
     }
-    tasks.insert(id_gen.to_string(), (*name).clone().into());
+    tasks.insert(id_gen.to_string(), name.to_v1());
     let curr = *id_gen;
     for subtask in &name.sub_tasks {
         add_to_task(tasks, subtasks, subtask, curr, id_gen);
     }
 }
 
-impl From<LegacyTaskStore> for JsonDataStore {
-    fn from(value: LegacyTaskStore) -> Self {
+impl LegacyTaskStore {
+    /// The store as version 1 saved it, where the tasks are stored by their id
+    /// rather than nested inside each other.
+    pub fn into_v1(self) -> Value {
         let mut tasks = HashMap::new();
-        let mut completed_tasks: HashMap<String, CompletedTask> = HashMap::new();
+        let mut completed_tasks = HashMap::new();
         let mut subtasks = HashMap::new();
         let mut id_gen = 0;
 
-        let roots = value
+        let roots = self
             .tasks
-            .into_iter()
+            .iter()
             .map(|f| {
                 let curr_id = id_gen;
                 for subtask in &f.sub_tasks {
                     add_to_task(&mut tasks, &mut subtasks, subtask, curr_id, &mut id_gen);
                 }
                 id_gen += 1;
-                tasks.insert(curr_id.to_string(), f.into());
+                tasks.insert(curr_id.to_string(), f.to_v1());
                 curr_id.to_string()
             })
             .collect_vec();
 
-        let completed_root = value
+        let completed_root = self
             .completed_tasks
-            .into_iter()
+            .iter()
             .map(|f| {
                 id_gen += 1;
-                completed_tasks.insert(id_gen.to_string(), (f).into());
+                completed_tasks.insert(
+                    id_gen.to_string(),
+                    json!({
+                        "task": f.task.to_v1(),
+                        "time_completed": f.time_completed,
+                    }),
+                );
                 id_gen.to_string()
             })
             .collect_vec();
 
-        JsonDataStore {
-            tags: value
-                .tags
-                .into_iter()
-                .map(|(key, value)| (key.to_string(), value))
-                .collect(),
-            tasks,
-            completed_tasks,
-            subtasks,
-            root: roots,
-            completed_root,
-            task_count: id_gen,
-        }
+        let tags = self
+            .tags
+            .into_iter()
+            .map(|(key, value)| (key.to_string(), value))
+            .collect::<HashMap<_, _>>();
+
+        json!({
+            "version": "1",
+            "tasks": tasks,
+            "completed_tasks": completed_tasks,
+            "subtasks": subtasks,
+            "root": roots,
+            "completed_root": completed_root,
+            "tags": tags,
+        })
     }
 }
@@ -0,0 +1,144 @@
+use chrono::NaiveDateTime;
+use serde_json::{Map, Value};
+
+use crate::{
+    data::json_data_store::JsonDataStore, error::AppError,
+    storage::json::legacy::legacy_task::LegacyTaskStore,
+};
+
+use super::version::JSONVersion;
+
+/// The version of the data that is saved.
+pub const CURRENT_VERSION: usize = 3;
+
+type Migration = fn(Value, NaiveDateTime) -> Result<Value, AppError>;
+
+/// The migration at each index brings the data from that version to the next.
+const MIGRATIONS: [Migration; CURRENT_VERSION] = [v0_to_v1, v1_to_v2, v2_to_v3];
+
+/// Reads the data, migrating it if it was saved by an older version.
+pub fn load(contents: &str, now: NaiveDateTime) -> Result<JsonDataStore, AppError> {
+    let value = serde_json::from_str(contents)?;
+    let value = migrate(value, now)?;
+    Ok(serde_json::from_value::<JSONVersion>(value)?.into())
+}
+
+/// Returns the version of the data, the data from before versions were
+/// recorded is version 0.
+pub fn version(value: &Value) -> Result<usize, AppError> {
+    match value.get("version") {
+        None => Ok(0),
+        Some(Value::String(version)) => version
+            .parse()
+            .ok()
+            .filter(|version| *version <= CURRENT_VERSION)
+            .ok_or_else(|| AppError::UnsupportedVersion(version.clone())),
+        Some(version) => Err(AppError::UnsupportedVersion(version.to_string())),
+    }
+}
+
+/// Runs each migration from the version of the data up to the current version.
+pub fn migrate(mut value: Value, now: NaiveDateTime) -> Result<Value, AppError> {
+    let from = version(&value)?;
+    for (version, migration) in MIGRATIONS.iter().enumerate().skip(from) {
+        value = migration(value, now)
+            .map_err(|err| AppError::MigrationFailed(version, Box::new(err)))?;
+    }
+    Ok(value)
+}
+
+/// The tasks were nested inside each other, they are now stored by their id.
+fn v0_to_v1(value: Value, _: NaiveDateTime) -> Result<Value, AppError> {
+    Ok(serde_json::from_value::<LegacyTaskStore>(value)?.into_v1())
+}
+
+/// Fills in when the tasks were added and last changed. The earliest and
+/// latest times known from the comments, time entries and completion are
+/// used, otherwise the time of the migration.
+fn v1_to_v2(mut value: Value, now: NaiveDateTime) -> Result<Value, AppError> {
+    for task in object_mut(&mut value, "tasks")?.values_mut() {
+        fill_timestamps(task, now)?;
+    }
+    for completed_task in object_mut(&mut value, "completed_tasks")?.values_mut() {
+        let time_completed = time(&completed_task["time_completed"])?;
+        let task = completed_task
+            .get_mut("task")
+            .ok_or_else(|| AppError::invalid_state("A completed task has no task"))?;
+        if let Value::Object(task) = task {
+            if task.get("updated_at").is_none_or(Value::is_null) {
+                task.insert(
+                    "updated_at".to_string(),
+                    serde_json::to_value(time_completed)?,
+                );
+            }
+        }
+        fill_timestamps(task, time_completed)?;
+    }
+    value["version"] = Value::String("2".to_string());
+    Ok(value)
+}
+
+/// The object stored under the key, an empty object if it is missing.
+fn object_mut<'a>(value: &'a mut Value, key: &str) -> Result<&'a mut Map<String, Value>, AppError> {
+    let Value::Object(store) = value else {
+        return Err(AppError::invalid_state("The task data is not an object"));
+    };
+    match store
+        .entry(key)
+        .or_insert_with(|| Value::Object(Map::new()))
+    {
+        Value::Object(object) => Ok(object),
+        _ => Err(AppError::invalid_state(format!(
+            "The {key} are not an object"
+        ))),
+    }
+}
+
+fn time(value: &Value) -> Result<NaiveDateTime, AppError> {
+    Ok(serde_json::from_value(value.clone())?)
+}
+
+fn fill_timestamps(task: &mut Value, fallback: NaiveDateTime) -> Result<(), AppError> {
+    let Value::Object(task) = task else {
+        return Err(AppError::invalid_state("A task is not an object"));
+    };
+    let list = |key: &str| {
+        task.get(key)
+            .and_then(Value::as_array)
+            .into_iter()
+            .flatten()
+    };
+    let times = list("comments")
+        .filter_map(|comment| comment.get("posted_at"))
+        .chain(list("time_entries").filter_map(|entry| entry.get("start")))
+        .chain(list("time_entries").filter_map(|entry| entry.get("end")))
+        .filter(|time| !time.is_null())
+        .map(time)
+        .collect::<Result<Vec<_>, _>>()?;
+    let earliest = times.iter().min().copied();
+    let latest = times.iter().max().copied();
+    let existing = |key: &str| {
+        task.get(key)
+            .filter(|time| !time.is_null())
+            .map(time)
+            .transpose()
+    };
+
+    let created_at = existing("created_at")?.unwrap_or(earliest.unwrap_or(fallback).min(fallback));
+    let updated_at =
+        existing("updated_at")?.unwrap_or(latest.unwrap_or(created_at).max(created_at));
+    task.insert("created_at".to_string(), serde_json::to_value(created_at)?);
+    task.insert("updated_at".to_string(), serde_json::to_value(updated_at)?);
+    Ok(())
+}
+
+/// The ids counted up from `task_count`, new tasks are given UUIDs instead.
+/// The existing ids are kept as they are.
+fn v2_to_v3(mut value: Value, _: NaiveDateTime) -> Result<Value, AppError> {
+    let Value::Object(store) = &mut value else {
+        return Err(AppError::invalid_state("The task data is not an object"));
+    };
+    store.remove("task_count");
+    store.insert("version".to_string(), Value::String("3".to_string()));
+    Ok(value)
+}
@@ -1,2 +1,4 @@
 pub mod legacy;
+pub mod migration;
+pub mod validate;
 pub mod version;
@@ -0,0 +1,405 @@
+use std::{
+    collections::{HashMap, HashSet},
+    fmt::{self, Display},
+};
+
+use itertools::Itertools;
+
+use crate::data::{
+    data_store::{TaskID, TaskIDRef},
+    json_data_store::JsonDataStore,
+};
+
+/// A list of task ids in the store.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Location {
+    Root,
+    Subtasks(TaskID),
+    CompletedRoot,
+    CompletedSubtasks(TaskID),
+}
+
+impl Display for Location {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Location::Root => write!(f, "the root tasks"),
+            Location::Subtasks(parent) => write!(f, "the subtasks of {}", parent),
+            Location::CompletedRoot => write!(f, "the completed tasks"),
+            Location::CompletedSubtasks(parent) => {
+                write!(f, "the completed subtasks of {}", parent)
+            }
+        }
+    }
+}
+
+/// A broken invariant of the store along with how it is repaired.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Problem {
+    /// The id is listed, but there is no task with it.
+    Dangling { id: TaskID, location: Location },
+    /// The subtasks of a task that does not exist, they are moved to the root
+    /// tasks.
+    OrphanedSubtasks {
+        parent: TaskID,
+        subtasks: Vec<TaskID>,
+    },
+    /// The task is listed more than once, only the first is kept.
+    DuplicateParent {
+        id: TaskID,
+        locations: Vec<Location>,
+    },
+    /// Each task is the parent of the next and the last is the parent of the
+    /// first, the first is moved to the root tasks.
+    Cycle { ids: Vec<TaskID> },
+    /// The task is not listed anywhere, it is moved to the root tasks.
+    OrphanedTask { id: TaskID },
+    /// The task is both open and completed, the completed one is removed and
+    /// its completed subtasks are moved to the completed tasks.
+    OpenAndCompleted { id: TaskID },
+    /// The completed subtasks of a completed task that does not exist, they
+    /// are moved to the completed tasks.
+    OrphanedCompletedSubtasks {
+        parent: TaskID,
+        subtasks: Vec<TaskID>,
+    },
+}
+
+impl Display for Problem {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Problem::Dangling { id, location } => {
+                write!(f, "{} is in {} but does not exist", id, location)
+            }
+            Problem::OrphanedSubtasks { parent, subtasks } => write!(
+                f,
+                "{} has the subtasks {} but does not exist",
+                parent,
+                subtasks.join(", ")
+            ),
+            Problem::DuplicateParent { id, locations } => write!(
+                f,
+                "{} is in {}",
+                id,
+                locations.iter().map(Location::to_string).join(" and ")
+            ),
+            Problem::Cycle { ids } => write!(
+                f,
+                "{} -> {} are subtasks of each other",
+                ids.join(" -> "),
+                ids[0]
+            ),
+            Problem::OrphanedTask { id } => write!(f, "{} is not in any list of tasks", id),
+            Problem::OpenAndCompleted { id } => write!(f, "{} is both open and completed", id),
+            Problem::OrphanedCompletedSubtasks { parent, subtasks } => write!(
+                f,
+                "{} has the completed subtasks {} but is not completed",
+                parent,
+                subtasks.join(", ")
+            ),
+        }
+    }
+}
+
+impl Problem {
+    /// Describes what `repair` will do.
+    pub fn repair_description(&self) -> String {
+        match self {
+            Problem::Dangling { id, location } => format!("Remove {} from {}", id, location),
+            Problem::OrphanedSubtasks { subtasks, .. } => {
+                format!("Move {} to the root tasks", subtasks.join(", "))
+            }
+            Problem::DuplicateParent { id, locations } => {
+                format!("Keep {} only in {}", id, locations[0])
+            }
+            Problem::Cycle { ids } => format!("Move {} to the root tasks", ids[0]),
+            Problem::OrphanedTask { id } => format!("Move {} to the root tasks", id),
+            Problem::OpenAndCompleted { id } => format!("Keep {} only as an open task", id),
+            Problem::OrphanedCompletedSubtasks { subtasks, .. } => {
+                format!("Move {} to the completed tasks", subtasks.join(", "))
+            }
+        }
+    }
+
+    pub fn repair(&self, store: &mut JsonDataStore) {
+        match self {
+            Problem::Dangling { id, location } => {
+                if let Some(list) = list_mut(store, location) {
+                    list.retain(|f| f != id);
+                }
+            }
+            Problem::OrphanedSubtasks { parent, subtasks } => {
+                store.subtasks.remove(parent);
+                for id in subtasks {
+                    move_to_root(store, id);
+                }
+            }
+            Problem::DuplicateParent { id, .. } => {
+                let mut seen = false;
+                for location in locations(store) {
+                    if let Some(list) = list_mut(store, &location) {
+                        list.retain(|f| f != id || !std::mem::replace(&mut seen, true));
+                    }
+                }
+            }
+            Problem::Cycle { ids } => {
+                let (first, parent) = (&ids[0], &ids[ids.len() - 1]);
+                if let Some(subtasks) = store.subtasks.get_mut(parent) {
+                    subtasks.retain(|f| f != first);
+                }
+                move_to_root(store, first);
+            }
+            Problem::OrphanedTask { id } => move_to_root(store, id),
+            Problem::OpenAndCompleted { id } => {
+                store.completed_tasks.remove(id);
+                store.completed_root.retain(|f| f != id);
+                for subtasks in store.completed_subtasks.values_mut() {
+                    subtasks.retain(|f| f != id);
+                }
+                for subtask in store.completed_subtasks.remove(id).unwrap_or_default() {
+                    move_to_completed_root(store, &subtask);
+                }
+            }
+            Problem::OrphanedCompletedSubtasks { parent, subtasks } => {
+                store.completed_subtasks.remove(parent);
+                for id in subtasks {
+                    move_to_completed_root(store, id);
+                }
+            }
+        }
+        store.rebuild_indexes();
+    }
+}
+
+/// Adds the task to the root tasks, unless it is already listed somewhere.
+fn move_to_root(store: &mut JsonDataStore, id: TaskIDRef) {
+    let listed = store
+        .root
+        .iter()
+        .chain(store.subtasks.values().flatten())
+        .any(|f| f == id);
+    if !listed {
+        store.root.push(id.to_string());
+    }
+}
+
+/// Adds the task to the completed tasks, unless it is already listed
+/// somewhere.
+fn move_to_completed_root(store: &mut JsonDataStore, id: TaskIDRef) {
+    let listed = store
+        .completed_root
+        .iter()
+        .chain(store.completed_subtasks.values().flatten())
+        .any(|f| f == id);
+    if !listed {
+        store.completed_root.push(id.to_string());
+    }
+}
+
+fn list<'a>(store: &'a JsonDataStore, location: &Location) -> Option<&'a Vec<TaskID>> {
+    match location {
+        Location::Root => Some(&store.root),
+        Location::Subtasks(parent) => store.subtasks.get(parent),
+        Location::CompletedRoot => Some(&store.completed_root),
+        Location::CompletedSubtasks(parent) => store.completed_subtasks.get(parent),
+    }
+}
+
+fn list_mut<'a>(store: &'a mut JsonDataStore, location: &Location) -> Option<&'a mut Vec<TaskID>> {
+    match location {
+        Location::Root => Some(&mut store.root),
+        Location::Subtasks(parent) => store.subtasks.get_mut(parent),
+        Location::CompletedRoot => Some(&mut store.completed_root),
+        Location::CompletedSubtasks(parent) => store.completed_subtasks.get_mut(parent),
+    }
+}
+
+/// The lists of open tasks, in the order used to decide which duplicate is kept.
+fn locations(store: &JsonDataStore) -> Vec<Location> {
+    std::iter::once(Location::Root)
+        .chain(
+            store
+                .subtasks
+                .keys()
+                .sorted()
+                .cloned()
+                .map(Location::Subtasks),
+        )
+        .collect()
+}
+
+/// The lists of completed tasks.
+fn completed_locations(store: &JsonDataStore) -> Vec<Location> {
+    std::iter::once(Location::CompletedRoot)
+        .chain(
+            store
+                .completed_subtasks
+                .keys()
+                .sorted()
+                .cloned()
+                .map(Location::CompletedSubtasks),
+        )
+        .collect()
+}
+
+type Check = fn(&JsonDataStore) -> Vec<Problem>;
+
+/// The checks in the order they are run, each one runs on the store with the
+/// problems found by the previous checks repaired.
+const CHECKS: [Check; 7] = [
+    open_and_completed,
+    dangling_ids,
+    orphaned_subtasks,
+    orphaned_completed_subtasks,
+    duplicate_parents,
+    cycles,
+    orphaned_tasks,
+];
+
+/// Finds the problems with the store, repairing them in this order leaves a
+/// valid store.
+pub fn validate(store: &JsonDataStore) -> Vec<Problem> {
+    let mut store = store.clone();
+    let mut problems = Vec::new();
+    for check in CHECKS {
+        let found = check(&store);
+        for problem in &found {
+            problem.repair(&mut store);
+        }
+        problems.extend(found);
+    }
+    problems
+}
+
+/// Repairs every problem with the store, returning the problems.
+pub fn repair(store: &mut JsonDataStore) -> Vec<Problem> {
+    let problems = validate(store);
+    for problem in &problems {
+        problem.repair(store);
+    }
+    problems
+}
+
+fn dangling_ids(store: &JsonDataStore) -> Vec<Problem> {
+    let mut problems = Vec::new();
+    for location in locations(store).iter().chain(&completed_locations(store)) {
+        let exists = |id: TaskIDRef| match location {
+            Location::CompletedRoot | Location::CompletedSubtasks(_) => {
+                store.completed_tasks.contains_key(id)
+            }
+            _ => store.tasks.contains_key(id),
+        };
+        for id in list(store, location).into_iter().flatten() {
+            if !exists(id) {
+                problems.push(Problem::Dangling {
+                    id: id.clone(),
+                    location: location.clone(),
+                });
+            }
+        }
+    }
+    problems
+}
+
+fn orphaned_subtasks(store: &JsonDataStore) -> Vec<Problem> {
+    store
+        .subtasks
+        .iter()
+        .filter(|(parent, _)| !store.tasks.contains_key(*parent))
+        .sorted_by_key(|(parent, _)| *parent)
+        .map(|(parent, subtasks)| Problem::OrphanedSubtasks {
+            parent: parent.clone(),
+            subtasks: subtasks.clone(),
+        })
+        .collect()
+}
+
+fn open_and_completed(store: &JsonDataStore) -> Vec<Problem> {
+    store
+        .completed_tasks
+        .keys()
+        .filter(|id| store.tasks.contains_key(*id))
+        .sorted()
+        .map(|id| Problem::OpenAndCompleted { id: id.clone() })
+        .collect()
+}
+
+fn orphaned_completed_subtasks(store: &JsonDataStore) -> Vec<Problem> {
+    store
+        .completed_subtasks
+        .iter()
+        .filter(|(parent, _)| !store.completed_tasks.contains_key(*parent))
+        .sorted_by_key(|(parent, _)| *parent)
+        .map(|(parent, subtasks)| Problem::OrphanedCompletedSubtasks {
+            parent: parent.clone(),
+            subtasks: subtasks.clone(),
+        })
+        .collect()
+}
+
+fn duplicate_parents(store: &JsonDataStore) -> Vec<Problem> {
+    let mut found_in: HashMap<&TaskID, Vec<Location>> = HashMap::new();
+    let mut order = Vec::new();
+    for location in locations(store) {
+        for id in list(store, &location).into_iter().flatten() {
+            let locations = found_in.entry(id).or_default();
+            if locations.is_empty() {
+                order.push(id);
+            }
+            locations.push(location.clone());
+        }
+    }
+    order
+        .into_iter()
+        .filter(|id| found_in[id].len() > 1)
+        .map(|id| Problem::DuplicateParent {
+            id: id.clone(),
+            locations: found_in[id].clone(),
+        })
+        .collect()
+}
+
+fn cycles(store: &JsonDataStore) -> Vec<Problem> {
+    let parent_of: HashMap<&TaskID, &TaskID> = store
+        .subtasks
+        .iter()
+        .flat_map(|(parent, subtasks)| subtasks.iter().map(move |id| (id, parent)))
+        .collect();
+
+    let mut problems = Vec::new();
+    let mut found_cycles = HashSet::new();
+    for start in parent_of.keys().sorted() {
+        let mut path = vec![*start];
+        while let Some(parent) = parent_of.get(path[path.len() - 1]) {
+            if let Some(index) = path.iter().position(|id| id == parent) {
+                let mut ids = path[index..]
+                    .iter()
+                    .rev()
+                    .map(|f| f.to_string())
+                    .collect_vec();
+                let min = ids.iter().position_min().unwrap_or_default();
+                ids.rotate_left(min);
+                if found_cycles.insert(ids.clone()) {
+                    problems.push(Problem::Cycle { ids });
+                }
+                break;
+            }
+            path.push(parent);
+        }
+    }
+    problems
+}
+
+fn orphaned_tasks(store: &JsonDataStore) -> Vec<Problem> {
+    let listed: HashSet<&TaskID> = store
+        .root
+        .iter()
+        .chain(store.subtasks.values().flatten())
+        .collect();
+    store
+        .tasks
+        .keys()
+        .filter(|id| !listed.contains(id))
+        .sorted()
+        .map(|id| Problem::OrphanedTask { id: id.clone() })
+        .collect()
+}
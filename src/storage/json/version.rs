@@ -2,28 +2,29 @@ use serde::{Deserialize, Serialize};
 
 use crate::data::json_data_store::JsonDataStore;
 
+/// The data that is saved, older versions are brought up to date by the
+/// migrations before being read.
 #[derive(Deserialize, Serialize)]
 #[serde(tag = "version")]
 pub enum JSONVersion {
-    // #[serde(rename = "0")]
-    // V0(TaskStore),
-    #[serde(rename = "1")]
-    V1(JsonDataStore),
-    // #[serde(other)]
-    // Unknown,
+    /// New tasks are given UUIDs rather than counting up from the last id.
+    #[serde(rename = "3")]
+    V3(JsonDataStore),
 }
 
 impl Default for JSONVersion {
     fn default() -> Self {
-        JSONVersion::V1(JsonDataStore::default())
+        JSONVersion::V3(JsonDataStore::default())
     }
 }
 
 impl From<JSONVersion> for JsonDataStore {
     fn from(value: JSONVersion) -> Self {
         match value {
-            // JSONVersion::V0(_store) => todo!(),
-            JSONVersion::V1(store) => store,
+            JSONVersion::V3(mut store) => {
+                store.rebuild_indexes();
+                store
+            }
         }
     }
 }
@@ -1,15 +1,17 @@
 use crate::data::data_store::DataTaskStore;
-use chrono::{NaiveDate, NaiveDateTime};
+use chrono::{Duration, NaiveDate, NaiveDateTime};
 use serde::{Deserialize, Serialize};
 use serde_with::skip_serializing_none;
 use tui::style::Color;
 
-use std::fmt::Display;
+use std::{collections::BTreeMap, fmt::Display};
 
 use crate::{
     app::App,
     config::{color_parser, Config},
     data::data_store::TaskID,
+    error::AppError,
+    utils,
 };
 
 #[derive(Deserialize, Serialize, Clone)]
@@ -28,11 +30,64 @@ pub struct Task {
     pub priority: Priority,
     pub tags: Vec<String>,
     pub due_date: Option<NaiveDate>,
+    pub comments: Vec<Comment>,
+    pub time_entries: Vec<TimeEntry>,
+    /// The number of pomodoros that were completed while focusing on this task.
+    pub pomodoros: usize,
+    /// How long this task is expected to take.
+    #[serde(with = "estimate_parser")]
+    pub estimate: Option<Duration>,
+    /// When the task was added, None for tasks added before this was recorded.
+    pub created_at: Option<NaiveDateTime>,
+    /// When the task was last changed.
+    pub updated_at: Option<NaiveDateTime>,
 
     // Ignored if sub_tasks is empty
     pub opened: bool,
 }
 
+/// A period of time spent on a task, `end` is None while the timer is running.
+#[skip_serializing_none]
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct TimeEntry {
+    pub start: NaiveDateTime,
+    pub end: Option<NaiveDateTime>,
+}
+
+impl TimeEntry {
+    /// The time spent, counting a running timer up until `now`.
+    pub fn duration(&self, now: NaiveDateTime) -> Duration {
+        self.end.unwrap_or(now) - self.start
+    }
+
+    /// Splits this entry at each midnight, returning the time spent on each day.
+    pub fn split_by_day(&self, now: NaiveDateTime) -> Vec<(NaiveDate, Duration)> {
+        let end = self.end.unwrap_or(now);
+        let mut days = Vec::new();
+        let mut start = self.start;
+        while start < end {
+            let next_day = start
+                .date()
+                .succ_opt()
+                .expect("Date should be in range")
+                .and_hms_opt(0, 0, 0)
+                .expect("Midnight is a valid time");
+            let day_end = next_day.min(end);
+            days.push((start.date(), day_end - start));
+            start = day_end;
+        }
+        days
+    }
+}
+
+/// A timestamped note attached to a task, separate from the description.
+#[derive(Clone, Debug, PartialEq, Deserialize, Serialize)]
+pub struct Comment {
+    pub id: String,
+    pub content: String,
+    pub posted_at: NaiveDateTime,
+}
+
 impl Task {
     pub fn from_string<T: Into<String>>(content: T) -> Self {
         Task {
@@ -41,10 +96,90 @@ impl Task {
             priority: Priority::None,
             tags: Vec::new(),
             due_date: None,
+            comments: Vec::new(),
+            time_entries: Vec::new(),
+            pomodoros: 0,
+            estimate: None,
+            created_at: None,
+            updated_at: None,
             opened: true,
         }
     }
 
+    /// Creates a task from the text entered when adding a task, a word such
+    /// as `~1h30m` is used as the estimate.
+    pub fn from_quick_add(content: &str) -> Result<Self, AppError> {
+        let mut estimate = None;
+        let mut words = Vec::new();
+        for word in content.split(' ') {
+            match word.strip_prefix('~') {
+                Some(duration) if !duration.is_empty() => {
+                    estimate = Some(utils::parse_duration(duration)?)
+                }
+                _ => words.push(word),
+            }
+        }
+        Ok(Task {
+            estimate,
+            ..Task::from_string(words.join(" ").trim())
+        })
+    }
+
+    /// Records that the task was changed at this time.
+    pub fn touch(&mut self, now: NaiveDateTime) {
+        self.created_at.get_or_insert(now);
+        self.updated_at = Some(now);
+    }
+
+    /// Returns the entry of the timer, if it is running.
+    pub fn running_timer(&self) -> Option<&TimeEntry> {
+        self.time_entries.iter().find(|entry| entry.end.is_none())
+    }
+
+    pub fn start_timer(&mut self, now: NaiveDateTime) {
+        if self.running_timer().is_none() {
+            self.time_entries.push(TimeEntry {
+                start: now,
+                end: None,
+            });
+        }
+    }
+
+    pub fn stop_timer(&mut self, now: NaiveDateTime) {
+        for entry in self.time_entries.iter_mut() {
+            if entry.end.is_none() {
+                entry.end = Some(now);
+            }
+        }
+    }
+
+    /// The total time tracked on this task.
+    pub fn tracked_time(&self, now: NaiveDateTime) -> Duration {
+        self.time_entries
+            .iter()
+            .map(|entry| entry.duration(now))
+            .fold(Duration::zero(), |acc, f| acc + f)
+    }
+
+    /// The time tracked on this task for each day between `from` and `to` (inclusive).
+    pub fn tracked_time_by_day(
+        &self,
+        now: NaiveDateTime,
+        from: NaiveDate,
+        to: NaiveDate,
+    ) -> BTreeMap<NaiveDate, Duration> {
+        let mut days = BTreeMap::new();
+        for (day, duration) in self
+            .time_entries
+            .iter()
+            .flat_map(|entry| entry.split_by_day(now))
+            .filter(|(day, _)| (from..=to).contains(day))
+        {
+            *days.entry(day).or_insert_with(Duration::zero) += duration;
+        }
+        days
+    }
+
     pub fn first_tag<'a>(&self, app: &'a App) -> Option<&'a Tag> {
         app.task_store.tags().get(self.tags.first().unwrap())
     }
@@ -68,10 +203,18 @@ impl Task {
     }
 }
 
+#[skip_serializing_none]
 #[derive(Deserialize, Clone, Serialize)]
 pub struct CompletedTask {
     pub task: Task,
     pub time_completed: NaiveDateTime,
+    /// The parent the task had when it was completed.
+    #[serde(default)]
+    pub parent_id: Option<TaskID>,
+    /// The index of the task within its parent (or the root tasks) when it was
+    /// completed.
+    #[serde(default)]
+    pub position: Option<usize>,
 }
 
 impl CompletedTask {
@@ -79,6 +222,8 @@ impl CompletedTask {
         CompletedTask {
             task,
             time_completed,
+            parent_id: None,
+            position: None,
         }
     }
 
@@ -90,9 +235,17 @@ impl CompletedTask {
                 priority: Priority::None,
                 due_date: None,
                 tags: Vec::new(),
+                comments: Vec::new(),
+                time_entries: Vec::new(),
+                pomodoros: 0,
+                estimate: None,
+                created_at: None,
+                updated_at: None,
                 opened: true,
             },
             time_completed,
+            parent_id: None,
+            position: None,
         }
     }
 }
@@ -157,6 +310,29 @@ impl Priority {
     }
 }
 
+/// Stores estimates as a number of minutes.
+pub mod estimate_parser {
+    use chrono::Duration;
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S>(estimate: &Option<Duration>, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match estimate {
+            Some(estimate) => serializer.serialize_some(&estimate.num_minutes()),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D>(deserializer: D) -> Result<Option<Duration>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Ok(Option::<i64>::deserialize(deserializer)?.map(Duration::minutes))
+    }
+}
+
 pub struct FindParentResult {
     /// If there is a parent, the index of the parent within it's task.
     pub parent_id: Option<TaskID>,
@@ -61,7 +61,10 @@ fn test_edit_task() {
         subtasks: HashMap::new(),
         root: vec!["0".to_string()],
         completed_root: vec![],
-        task_count: 1,
+        completed_subtasks: HashMap::new(),
+        parent_index: Default::default(),
+        visible_rows: Default::default(),
+        subscribers: Default::default(),
     });
     input_char('e', &mut main_app);
     input_char('r', &mut main_app);
@@ -76,7 +79,9 @@ fn test_edit_task() {
 #[test]
 fn test_edit_delete_task() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string(TEST_TASK_NAME), None);
+    json_data_store
+        .add_task(Task::from_string(TEST_TASK_NAME), None)
+        .unwrap();
     let mut main_app = setup(json_data_store);
 
     input_char('e', &mut main_app);
@@ -92,7 +97,9 @@ fn test_edit_delete_task() {
 #[test]
 fn test_cancel_edit_task() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
 
     let mut main_app = setup(json_data_store);
     input_char('e', &mut main_app);
@@ -106,7 +113,9 @@ fn test_cancel_edit_task() {
 #[test]
 fn test_delete_task() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
 
     let mut main_app = setup(json_data_store);
     input_char('d', &mut main_app);
@@ -117,7 +126,9 @@ fn test_delete_task() {
 #[test]
 fn test_cancel_delete_task() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
     let mut main_app = setup(json_data_store);
 
     input_char('d', &mut main_app);
@@ -129,8 +140,12 @@ fn test_cancel_delete_task() {
 #[test]
 fn test_priority() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
-    json_data_store.add_task(Task::from_string("oof"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
+    json_data_store
+        .add_task(Task::from_string("oof"), None)
+        .unwrap();
     let mut main_app = setup(json_data_store);
 
     input_char('p', &mut main_app);
@@ -196,7 +211,9 @@ fn test_priority() {
 #[test]
 fn test_complete_task() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
     let mut main_app = setup(json_data_store);
 
     input_char('c', &mut main_app);
@@ -207,12 +224,18 @@ fn test_complete_task() {
 #[test]
 fn test_restore_task() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
     let first_task = json_data_store.root_tasks()[0].clone();
-    json_data_store.complete_task(&first_task, Local::now().naive_local());
+    json_data_store
+        .complete_task(&first_task, Local::now().naive_local())
+        .unwrap();
     let mut main_app = setup(json_data_store);
 
+    // The first row is the header of the day
     input_char('2', &mut main_app);
+    input_char('j', &mut main_app);
     input_char('r', &mut main_app);
     assert_eq!(main_app.app.task_store.root_tasks().len(), 1);
     assert_eq!(main_app.app.task_store.completed_root_tasks().len(), 0);
@@ -221,26 +244,30 @@ fn test_restore_task() {
 #[test]
 fn sort() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(
-        Task {
-            progress: false,
-            title: String::from("Toaj"),
-            priority: Priority::Low,
-            tags: Vec::new(),
-            ..Default::default()
-        },
-        None,
-    );
-    json_data_store.add_task(
-        Task {
-            progress: false,
-            title: String::from("Toajeoifj"),
-            priority: Priority::High,
-            tags: Vec::new(),
-            ..Default::default()
-        },
-        None,
-    );
+    json_data_store
+        .add_task(
+            Task {
+                progress: false,
+                title: String::from("Toaj"),
+                priority: Priority::Low,
+                tags: Vec::new(),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+    json_data_store
+        .add_task(
+            Task {
+                progress: false,
+                title: String::from("Toajeoifj"),
+                priority: Priority::High,
+                tags: Vec::new(),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
     let mut main_app = setup(json_data_store);
     input_char('s', &mut main_app);
     assert!(main_app
@@ -255,26 +282,30 @@ fn sort() {
 #[test]
 fn test_autosort() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(
-        Task {
-            progress: false,
-            title: String::from("Toaj"),
-            priority: Priority::Low,
-            tags: Vec::new(),
-            ..Default::default()
-        },
-        None,
-    );
-    json_data_store.add_task(
-        Task {
-            progress: false,
-            title: String::from("Toajeoifj"),
-            priority: Priority::High,
-            tags: Vec::new(),
-            ..Default::default()
-        },
-        None,
-    );
+    json_data_store
+        .add_task(
+            Task {
+                progress: false,
+                title: String::from("Toaj"),
+                priority: Priority::Low,
+                tags: Vec::new(),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
+    json_data_store
+        .add_task(
+            Task {
+                progress: false,
+                title: String::from("Toajeoifj"),
+                priority: Priority::High,
+                tags: Vec::new(),
+                ..Default::default()
+            },
+            None,
+        )
+        .unwrap();
     let mut main_app = setup(json_data_store);
 
     input_char('S', &mut main_app);
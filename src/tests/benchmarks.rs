@@ -0,0 +1,198 @@
+use std::time::{Duration, Instant};
+
+use chrono::Local;
+
+use crate::{
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+    },
+    task::Task,
+    utils::{
+        task_position::{cursor_to_task, task_to_cursor},
+        test::{input_char, setup, todoist_store},
+    },
+};
+
+const ROOT_TASKS: usize = 100;
+const SUBTASKS: usize = 99;
+
+/// Runs the operation, failing if it takes longer than the limit. The limits
+/// leave room for a debug build on a slow machine, while searching every list
+/// of subtasks or walking the tree for each task goes over them. The time is
+/// printed, run with `--nocapture` to see it.
+fn bench<F: FnOnce() -> R, R>(name: &str, limit_ms: u64, operation: F) -> R {
+    let start = Instant::now();
+    let result = operation();
+    let elapsed = start.elapsed();
+    println!("{name}: {elapsed:?}");
+    assert!(
+        elapsed < Duration::from_millis(limit_ms),
+        "{name} took {elapsed:?}"
+    );
+    result
+}
+
+/// Adds 10,000 tasks, each root task has the same number of subtasks.
+/// Returns the ids of the subtasks of each root task.
+fn fill<T: DataTaskStore>(store: &mut T) -> Vec<(TaskID, Vec<TaskID>)> {
+    (0..ROOT_TASKS)
+        .map(|i| {
+            let parent = store
+                .add_task(Task::from_string(format!("task {i}")), None)
+                .unwrap();
+            let subtasks = (0..SUBTASKS)
+                .map(|j| {
+                    store
+                        .add_task(Task::from_string(format!("task {i}.{j}")), Some(&parent))
+                        .unwrap()
+                })
+                .collect();
+            (parent, subtasks)
+        })
+        .collect()
+}
+
+fn check_parents<T: DataTaskStore>(store: &T, tree: &[(TaskID, Vec<TaskID>)]) {
+    for (position, (parent, subtasks)) in tree.iter().enumerate() {
+        let found = store.find_parent(parent).unwrap();
+        assert_eq!(found.parent_id, None);
+        assert_eq!(found.task_local_offset, position);
+        for (position, subtask) in subtasks.iter().enumerate() {
+            let found = store.find_parent(subtask).unwrap();
+            assert_eq!(found.parent_id.as_ref(), Some(parent));
+            assert_eq!(found.task_local_offset, position);
+        }
+    }
+}
+
+#[test]
+fn bench_find_parent() {
+    let mut store = JsonDataStore::default();
+    let tree = fill(&mut store);
+    bench("find_parent of 10k json tasks", 250, || {
+        check_parents(&store, &tree)
+    });
+
+    // Still found after the lists are changed directly
+    let (parent, subtasks) = &tree[0];
+    store
+        .subtasks
+        .get_mut(parent)
+        .unwrap()
+        .retain(|f| *f != subtasks[0]);
+    store.root.push(subtasks[0].clone());
+    let found = store.find_parent(&subtasks[0]).unwrap();
+    assert_eq!(found.parent_id, None);
+    assert_eq!(found.task_local_offset, ROOT_TASKS);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn bench_find_parent_todoist() {
+    let mut store = todoist_store();
+    let tree = fill(&mut store);
+    bench("find_parent of 10k todoist tasks", 250, || {
+        check_parents(&store, &tree)
+    });
+}
+
+#[test]
+fn bench_move_tasks() {
+    let mut store = JsonDataStore::default();
+    let tree = fill(&mut store);
+    bench("move 10k tasks", 400, || {
+        for (parent, subtasks) in &tree {
+            for subtask in subtasks {
+                store.move_task(subtask, None, 0, Some(())).unwrap();
+            }
+            if *parent != tree[0].0 {
+                store
+                    .move_task(parent, Some(tree[0].0.clone()), 0, None)
+                    .unwrap();
+            }
+        }
+    });
+    assert_eq!(store.root_tasks().len(), ROOT_TASKS * SUBTASKS + 1);
+    assert_eq!(store.subtasks(&tree[0].0).unwrap().len(), ROOT_TASKS - 1);
+    let found = store.find_parent(&tree[1].0).unwrap();
+    assert_eq!(found.parent_id.as_ref(), Some(&tree[0].0));
+}
+
+#[test]
+fn bench_complete_and_delete() {
+    let mut store = JsonDataStore::default();
+    let tree = fill(&mut store);
+    let now = Local::now().naive_local();
+    bench("complete and delete 10k tasks", 500, || {
+        for (parent, subtasks) in &tree {
+            for subtask in &subtasks[1..] {
+                store.complete_task(subtask, now).unwrap();
+            }
+            store.delete_task(&subtasks[0]).unwrap();
+            store.complete_task(parent, now).unwrap();
+        }
+    });
+    assert!(store.root_tasks().is_empty());
+    assert_eq!(store.completed_root_tasks().len(), ROOT_TASKS * SUBTASKS);
+    assert_eq!(store.completed_tasks.len(), ROOT_TASKS * SUBTASKS);
+
+    store.restore(&tree[0].1[1]).unwrap();
+    let found = store.find_parent(&tree[0].1[1]).unwrap();
+    assert_eq!(found.parent_id, None);
+}
+
+#[test]
+fn bench_navigation() {
+    let mut store = JsonDataStore::default();
+    let tree = fill(&mut store);
+    let mut main_app = setup(store);
+    let last = ROOT_TASKS * (SUBTASKS + 1) - 1;
+
+    bench("navigate and move within 10k tasks", 2000, || {
+        input_char('G', &mut main_app);
+        for _ in 0..5 {
+            input_char('K', &mut main_app);
+            input_char('k', &mut main_app);
+            input_char('J', &mut main_app);
+            input_char('j', &mut main_app);
+        }
+        // Out of the last root task and back in
+        for _ in 0..5 {
+            input_char('H', &mut main_app);
+            input_char('L', &mut main_app);
+        }
+    });
+
+    let app = &main_app.app;
+    assert_eq!(app.task_store.find_tasks_draw_size(), last + 1);
+    let (parent, subtasks) = &tree[ROOT_TASKS - 1];
+    let selected = cursor_to_task(&app.task_store, app.task_list.selected_index).unwrap();
+    assert!(subtasks.contains(&selected));
+    assert_eq!(
+        app.task_store
+            .find_parent(&selected)
+            .unwrap()
+            .parent_id
+            .as_ref(),
+        Some(parent)
+    );
+}
+
+#[test]
+fn bench_cursor() {
+    let mut store = JsonDataStore::default();
+    fill(&mut store);
+    let mut main_app = setup(store);
+
+    bench("move the cursor through 10k tasks", 500, || {
+        for _ in 0..1000 {
+            input_char('j', &mut main_app);
+        }
+        let store = &main_app.app.task_store;
+        for row in 0..store.find_tasks_draw_size() {
+            let id = cursor_to_task(store, row).unwrap();
+            assert_eq!(task_to_cursor(store, &id), Some(row));
+        }
+    });
+    assert_eq!(main_app.app.task_list.selected_index, 1000);
+}
@@ -0,0 +1,137 @@
+use crossterm::event::KeyCode;
+
+use crate::{
+    data::{data_store::DataTaskStore, json_data_store::JsonDataStore},
+    error::StoreError,
+    framework::screen_manager::ScreenManager,
+    task::Task,
+    utils::test::{
+        get_task_from_pos, input_char, input_code, input_str, setup, todoist_store_with_sink,
+    },
+};
+
+fn setup_with_task() -> ScreenManager {
+    let mut task_store = JsonDataStore::default();
+    task_store
+        .add_task(Task::from_string("vendor contract"), None)
+        .unwrap();
+    setup(task_store)
+}
+
+#[test]
+fn test_add_comment() {
+    let mut main_app = setup_with_task();
+    input_char('C', &mut main_app);
+    assert_eq!(main_app.overlays.len(), 1);
+
+    input_char('a', &mut main_app);
+    input_str("called vendor, waiting on reply", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+    input_char('a', &mut main_app);
+    input_str("vendor replied", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+
+    // Still within the comments
+    assert_eq!(main_app.overlays.len(), 1);
+    let comments = &get_task_from_pos(&main_app.app.task_store, 0).comments;
+    assert_eq!(
+        comments
+            .iter()
+            .map(|f| f.content.as_str())
+            .collect::<Vec<_>>(),
+        vec!["called vendor, waiting on reply", "vendor replied"]
+    );
+
+    input_code(KeyCode::Esc, &mut main_app);
+    assert!(main_app.overlays.is_empty());
+}
+
+#[test]
+fn test_edit_comment() {
+    let mut main_app = setup_with_task();
+    input_char('C', &mut main_app);
+    input_char('a', &mut main_app);
+    input_str("first", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+    input_char('a', &mut main_app);
+    input_str("second", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+
+    input_char('k', &mut main_app);
+    input_char('e', &mut main_app);
+    input_str(" edited", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+
+    let comments = &get_task_from_pos(&main_app.app.task_store, 0).comments;
+    assert_eq!(comments[0].content, "first edited");
+    assert_eq!(comments[1].content, "second");
+}
+
+#[test]
+fn test_comments_saved() {
+    let mut task_store = JsonDataStore::default();
+    let id = task_store
+        .add_task(Task::from_string("task"), None)
+        .unwrap();
+    task_store
+        .add_comment(&id, "a comment".to_string(), Default::default())
+        .unwrap();
+
+    let json = serde_json::to_string(&task_store).unwrap();
+    let loaded: JsonDataStore = serde_json::from_str(&json).unwrap();
+    assert_eq!(loaded.task(&id).unwrap().comments[0].content, "a comment");
+
+    // Older files without comments are still loaded
+    let old = r#"{"title": "old task", "progress": false, "priority": "None", "tags": [], "opened": true}"#;
+    assert!(serde_json::from_str::<Task>(old)
+        .unwrap()
+        .comments
+        .is_empty());
+}
+
+#[test]
+fn test_empty_comment_rejected() {
+    let mut task_store = JsonDataStore::default();
+    let id = task_store
+        .add_task(Task::from_string("task"), None)
+        .unwrap();
+    let now = Default::default();
+
+    assert_eq!(
+        task_store.add_comment(&id, " \n ".to_string(), now),
+        Err(StoreError::EmptyComment)
+    );
+    assert!(task_store.task(&id).unwrap().comments.is_empty());
+
+    task_store
+        .add_comment(&id, "first".to_string(), now)
+        .unwrap();
+    task_store
+        .add_comment(&id, "second".to_string(), now)
+        .unwrap();
+    let comments = &task_store.task(&id).unwrap().comments;
+    assert_ne!(comments[0].id, comments[1].id);
+
+    let comment_id = comments[0].id.clone();
+    assert_eq!(
+        task_store.update_comment(&id, &comment_id, String::new()),
+        Err(StoreError::EmptyComment)
+    );
+    assert_eq!(task_store.task(&id).unwrap().comments[0].content, "first");
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_empty_todoist_comment_not_sent() {
+    let (mut task_store, mut commands) = todoist_store_with_sink();
+    let id = task_store
+        .add_task(Task::from_string("task"), None)
+        .unwrap();
+    while commands.try_recv().is_ok() {}
+
+    assert_eq!(
+        task_store.add_comment(&id, "  ".to_string(), Default::default()),
+        Err(StoreError::EmptyComment)
+    );
+    assert!(task_store.task(&id).unwrap().comments.is_empty());
+    assert!(commands.try_recv().is_err());
+}
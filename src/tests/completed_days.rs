@@ -0,0 +1,122 @@
+use crossterm::event::KeyCode;
+
+use crate::{
+    component::completed_list::{day_label, weekly_counts, CompletedListContext, CompletedRow},
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+    },
+    task::Task,
+    utils::test::{at, date, input_char, input_code, setup},
+};
+
+fn task_row(id: &str) -> CompletedRow {
+    CompletedRow::Task(id.to_string(), 0)
+}
+
+/// The first task is completed on the 12th, the second and third on the 13th.
+fn completed_store() -> (JsonDataStore, [TaskID; 3]) {
+    let mut json_data_store = JsonDataStore::default();
+    let first = json_data_store
+        .add_task(Task::from_string("first"), None)
+        .unwrap();
+    let second = json_data_store
+        .add_task(
+            Task {
+                tags: vec!["0".to_string()],
+                ..Task::from_string("second")
+            },
+            None,
+        )
+        .unwrap();
+    let third = json_data_store
+        .add_task(Task::from_string("third"), None)
+        .unwrap();
+    json_data_store
+        .complete_task(&first, at(date(2024, 10, 12), 10))
+        .unwrap();
+    json_data_store
+        .complete_task(&second, at(date(2024, 10, 13), 9))
+        .unwrap();
+    json_data_store
+        .complete_task(&third, at(date(2024, 10, 13), 15))
+        .unwrap();
+    (json_data_store, [first, second, third])
+}
+
+#[test]
+fn test_day_label() {
+    let today = date(2024, 10, 14);
+    assert_eq!(day_label(today, today), "Today");
+    assert_eq!(day_label(date(2024, 10, 13), today), "Yesterday");
+    assert_eq!(day_label(date(2024, 10, 12), today), "Sat 12 Oct");
+    assert_eq!(day_label(date(2023, 10, 12), today), "Thu 12 Oct 2023");
+}
+
+#[test]
+fn test_weekly_counts() {
+    let counts = vec![
+        (date(2024, 10, 16), 2),
+        (date(2024, 10, 14), 1),
+        (date(2024, 10, 13), 4),
+        (date(2024, 10, 7), 3),
+        (date(2024, 10, 6), 5),
+    ];
+    // Wednesday, the week started on Monday the 14th
+    assert_eq!(weekly_counts(&counts, date(2024, 10, 16)), (3, 7));
+}
+
+#[test]
+fn test_group_by_day() {
+    let (json_data_store, [first, second, third]) = completed_store();
+    let mut context = CompletedListContext::default();
+    assert_eq!(
+        context.rows(&json_data_store),
+        vec![
+            CompletedRow::Day(date(2024, 10, 13)),
+            task_row(&third),
+            task_row(&second),
+            CompletedRow::Day(date(2024, 10, 12)),
+            task_row(&first),
+        ]
+    );
+    assert_eq!(
+        context.count_by_day(&json_data_store),
+        vec![(date(2024, 10, 13), 2), (date(2024, 10, 12), 1)]
+    );
+
+    context.tag_filter = Some("0".to_string());
+    assert_eq!(
+        context.rows(&json_data_store),
+        vec![CompletedRow::Day(date(2024, 10, 13)), task_row(&second)]
+    );
+}
+
+#[test]
+fn test_collapse_day() {
+    let (json_data_store, [first, ..]) = completed_store();
+    let mut main_app = setup(json_data_store);
+    input_char('2', &mut main_app);
+    input_char('j', &mut main_app);
+
+    // Collapsing from a task selects the header
+    input_code(KeyCode::Enter, &mut main_app);
+    let app = &main_app.app;
+    assert_eq!(app.completed_list.selected_index, 0);
+    assert_eq!(
+        app.completed_list.rows(&app.task_store),
+        vec![
+            CompletedRow::Day(date(2024, 10, 13)),
+            CompletedRow::Day(date(2024, 10, 12)),
+            task_row(&first),
+        ]
+    );
+
+    // Restoring does nothing on a header
+    input_char('r', &mut main_app);
+    assert_eq!(main_app.app.task_store.completed_root_tasks().len(), 3);
+
+    input_code(KeyCode::Enter, &mut main_app);
+    let app = &main_app.app;
+    assert_eq!(app.completed_list.rows(&app.task_store).len(), 5);
+}
@@ -0,0 +1,127 @@
+use chrono::Local;
+
+use crate::{
+    component::completed_list::{CompletedListContext, CompletedRow},
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+    },
+    utils::test::{input_char, setup, task_tree},
+};
+
+/// The ids are first, parent, child, nested and second child.
+fn nested_store() -> (JsonDataStore, [TaskID; 5]) {
+    task_tree([
+        (0, "first"),
+        (0, "parent"),
+        (1, "child"),
+        (2, "nested"),
+        (1, "second child"),
+    ])
+}
+
+#[test]
+fn test_complete_subtree() {
+    let (mut json_data_store, [first, parent, child, nested, second]) = nested_store();
+    let now = Local::now().naive_local();
+    json_data_store.complete_task(&parent, now).unwrap();
+
+    assert_eq!(json_data_store.root_tasks(), &vec![first]);
+    assert_eq!(
+        json_data_store.completed_root_tasks(),
+        &vec![parent.clone()]
+    );
+    assert_eq!(
+        json_data_store.completed_subtasks(&parent).unwrap(),
+        &vec![child.clone(), second.clone()]
+    );
+    assert_eq!(
+        json_data_store.completed_subtasks(&child).unwrap(),
+        &vec![nested.clone()]
+    );
+    assert!(json_data_store.task(&nested).is_none());
+    assert!(json_data_store.subtasks(&parent).is_none());
+
+    let nested_task = json_data_store.completed_task(&nested).unwrap();
+    assert_eq!(nested_task.parent_id.as_ref(), Some(&child));
+    assert_eq!(nested_task.position, Some(0));
+
+    // The subtasks are drawn below their parent
+    assert_eq!(json_data_store.find_completed_tasks_draw_size(), 4);
+    assert_eq!(
+        CompletedListContext::default().rows(&json_data_store),
+        vec![
+            CompletedRow::Day(now.date()),
+            CompletedRow::Task(parent, 0),
+            CompletedRow::Task(child, 1),
+            CompletedRow::Task(nested, 2),
+            CompletedRow::Task(second, 1),
+        ]
+    );
+}
+
+#[test]
+fn test_restore_subtree_to_position() {
+    let (mut json_data_store, [first, parent, child, nested, second]) = nested_store();
+    json_data_store
+        .complete_task(&parent, Local::now().naive_local())
+        .unwrap();
+    json_data_store
+        .complete_task(&first, Local::now().naive_local())
+        .unwrap();
+
+    json_data_store.restore(&parent).unwrap();
+    assert_eq!(json_data_store.root_tasks(), &vec![parent.clone()]);
+    assert_eq!(
+        json_data_store.subtasks(&parent).unwrap(),
+        &vec![child.clone(), second.clone()]
+    );
+    assert_eq!(json_data_store.subtasks(&child).unwrap(), &vec![nested]);
+    assert!(json_data_store.completed_subtasks(&parent).is_none());
+    assert_eq!(json_data_store.completed_root_tasks(), &vec![first.clone()]);
+
+    // Goes back to being the first task
+    json_data_store.restore(&first).unwrap();
+    assert_eq!(
+        json_data_store.root_tasks(),
+        &vec![first.clone(), parent.clone()]
+    );
+
+    // A subtask goes back to its place within its parent
+    json_data_store
+        .complete_task(&child, Local::now().naive_local())
+        .unwrap();
+    assert_eq!(
+        json_data_store.subtasks(&parent).unwrap(),
+        &vec![second.clone()]
+    );
+    json_data_store.restore(&child).unwrap();
+    assert_eq!(
+        json_data_store.subtasks(&parent).unwrap(),
+        &vec![child, second]
+    );
+}
+
+#[test]
+fn test_restore_subtask_of_completed_task() {
+    let (mut json_data_store, [first, parent, child, nested, second]) = nested_store();
+    json_data_store
+        .complete_task(&parent, Local::now().naive_local())
+        .unwrap();
+    let mut main_app = setup(json_data_store);
+
+    // Restores "child", as its parent is still completed it is added to the end.
+    input_char('2', &mut main_app);
+    input_char('j', &mut main_app);
+    input_char('j', &mut main_app);
+    input_char('r', &mut main_app);
+
+    let task_store = &main_app.app.task_store;
+    assert_eq!(task_store.root_tasks(), &vec![first, child.clone()]);
+    assert_eq!(task_store.subtasks(&child).unwrap(), &vec![nested]);
+    assert_eq!(
+        task_store.completed_subtasks(&parent).unwrap(),
+        &vec![second]
+    );
+    assert_eq!(main_app.app.completed_list.selected_index, 2);
+}
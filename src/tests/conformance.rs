@@ -0,0 +1,318 @@
+//! The contract of [`DataTaskStore`], each backend is run through the same
+//! checks so they can not drift apart.
+
+use chrono::Local;
+use tui::style::Color;
+
+use crate::{
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+        todoist::todoist_command::TodoistCommand,
+    },
+    error::StoreError,
+    task::{Priority, Tag, Task},
+    utils::test::todoist_store_with_sink,
+};
+
+fn task(title: &str) -> Task {
+    Task::from_string(title)
+}
+
+fn titles<T: DataTaskStore>(store: &T, ids: &[TaskID]) -> Vec<String> {
+    ids.iter()
+        .map(|id| store.task(id).unwrap().title.clone())
+        .collect()
+}
+
+fn root_titles<T: DataTaskStore>(store: &T) -> Vec<String> {
+    titles(store, store.root_tasks())
+}
+
+fn subtask_titles<T: DataTaskStore>(store: &T, id: &str) -> Vec<String> {
+    titles(store, store.subtasks(id).map_or(&[][..], |f| f))
+}
+
+fn check_add<T: DataTaskStore>(mut store: T) {
+    let first = store.add_task(task("first"), None).unwrap();
+    let second = store.add_task(task("second"), None).unwrap();
+    let child = store.add_task(task("child"), Some(&first)).unwrap();
+    assert_ne!(first, second);
+
+    // Tasks are added last
+    assert_eq!(root_titles(&store), ["first", "second"]);
+    assert_eq!(subtask_titles(&store, &first), ["child"]);
+    let parent = store.find_parent(&child).unwrap();
+    assert_eq!(parent.parent_id, Some(first.clone()));
+    assert_eq!(parent.task_local_offset, 0);
+    assert_eq!(store.find_parent(&second).unwrap().task_local_offset, 1);
+    assert!(store.task(&child).unwrap().created_at.is_some());
+
+    assert_eq!(
+        store.add_task(task("orphan"), Some("missing")),
+        Err(StoreError::NotFound("missing".to_string()))
+    );
+    assert_eq!(root_titles(&store), ["first", "second"]);
+}
+
+fn check_move<T: DataTaskStore>(mut store: T) {
+    let first = store.add_task(task("first"), None).unwrap();
+    let second = store.add_task(task("second"), None).unwrap();
+    let child = store.add_task(task("child"), Some(&first)).unwrap();
+
+    // Without a parent the task stays in its list
+    store.move_task(&second, None, 0, None).unwrap();
+    assert_eq!(root_titles(&store), ["second", "first"]);
+    store.move_task(&child, None, 5, None).unwrap();
+    assert_eq!(subtask_titles(&store, &first), ["child"]);
+
+    store
+        .move_task(&second, Some(first.clone()), 0, None)
+        .unwrap();
+    assert_eq!(root_titles(&store), ["first"]);
+    assert_eq!(subtask_titles(&store, &first), ["second", "child"]);
+    assert_eq!(
+        store.find_parent(&second).unwrap().parent_id,
+        Some(first.clone())
+    );
+
+    // A global move without a parent goes to the root tasks
+    store.move_task(&child, None, 100, Some(())).unwrap();
+    assert_eq!(root_titles(&store), ["first", "child"]);
+    assert_eq!(subtask_titles(&store, &first), ["second"]);
+
+    assert_eq!(
+        store.move_task(&first, Some(second.clone()), 0, None),
+        Err(StoreError::CycleDetected(first.clone()))
+    );
+    store
+        .complete_task(&child, Local::now().naive_local())
+        .unwrap();
+    assert!(matches!(
+        store.move_task(&second, Some(child), 0, None),
+        Err(StoreError::InvalidMove(_))
+    ));
+    assert_eq!(subtask_titles(&store, &first), ["second"]);
+}
+
+fn check_sort<T: DataTaskStore>(mut store: T) {
+    let add = |store: &mut T, title: &str, priority: Priority, parent: Option<&str>| {
+        store
+            .add_task(
+                Task {
+                    priority,
+                    ..task(title)
+                },
+                parent,
+            )
+            .unwrap()
+    };
+    add(&mut store, "low", Priority::Low, None);
+    let parent = add(&mut store, "none", Priority::None, None);
+    add(&mut store, "high", Priority::High, None);
+    add(&mut store, "other low", Priority::Low, None);
+    add(&mut store, "normal", Priority::Normal, Some(&parent));
+    add(&mut store, "high", Priority::High, Some(&parent));
+    store
+        .modify_task(&parent, |task| task.opened = true)
+        .unwrap();
+
+    // Highest first, keeping the order of equal priorities
+    store.sort();
+    assert_eq!(root_titles(&store), ["high", "low", "other low", "none"]);
+    assert_eq!(subtask_titles(&store, &parent), ["high", "normal"]);
+    assert_eq!(
+        store.visible_rows().len(),
+        6,
+        "The visible rows are built again after sorting"
+    );
+    assert_eq!(store.visible_rows().get(4).unwrap().1, 1);
+}
+
+fn check_complete<T: DataTaskStore>(mut store: T) {
+    let now = Local::now().naive_local();
+    let first = store.add_task(task("first"), None).unwrap();
+    let parent = store.add_task(task("parent"), None).unwrap();
+    let child = store.add_task(task("child"), Some(&parent)).unwrap();
+    let nested = store.add_task(task("nested"), Some(&child)).unwrap();
+
+    store.complete_task(&parent, now).unwrap();
+    assert_eq!(root_titles(&store), ["first"]);
+    assert!(store.task(&parent).is_none());
+    assert!(store.task(&nested).is_none());
+    assert!(store.find_parent(&child).is_none());
+    assert_eq!(store.completed_root_tasks(), &vec![parent.clone()]);
+    assert_eq!(
+        store.completed_subtasks(&parent),
+        Some(&vec![child.clone()])
+    );
+    assert_eq!(
+        store.completed_subtasks(&child),
+        Some(&vec![nested.clone()])
+    );
+
+    let completed = store.completed_task(&parent).unwrap();
+    assert_eq!(completed.time_completed, now);
+    assert_eq!(completed.parent_id, None);
+    assert_eq!(completed.position, Some(1));
+    assert_eq!(
+        store.completed_task(&nested).unwrap().parent_id,
+        Some(child.clone())
+    );
+    assert_eq!(store.find_completed_task_draw_size(&parent), 3);
+    assert_eq!(store.find_completed_tasks_draw_size(), 3);
+
+    assert_eq!(
+        store.complete_task(&parent, now),
+        Err(StoreError::NotFound(parent.clone()))
+    );
+    store.complete_task(&first, now).unwrap();
+    assert_eq!(store.completed_root_tasks(), &vec![parent, first]);
+    assert_eq!(store.find_completed_tasks_draw_size(), 4);
+}
+
+fn check_restore<T: DataTaskStore>(mut store: T) {
+    let now = Local::now().naive_local();
+    let first = store.add_task(task("first"), None).unwrap();
+    let parent = store.add_task(task("parent"), None).unwrap();
+    let child = store.add_task(task("child"), Some(&parent)).unwrap();
+    store.add_task(task("nested"), Some(&child)).unwrap();
+    let last = store.add_task(task("last"), Some(&parent)).unwrap();
+
+    // Back to the same parent and position, with its subtasks
+    store.complete_task(&child, now).unwrap();
+    store.restore(&child).unwrap();
+    assert_eq!(subtask_titles(&store, &parent), ["child", "last"]);
+    assert_eq!(subtask_titles(&store, &child), ["nested"]);
+    assert!(store.completed_root_tasks().is_empty());
+    assert!(store.completed_task(&child).is_none());
+
+    store.complete_task(&parent, now).unwrap();
+    store.restore(&parent).unwrap();
+    assert_eq!(root_titles(&store), ["first", "parent"]);
+    assert_eq!(subtask_titles(&store, &parent), ["child", "last"]);
+
+    // The position is clamped once the list is shorter
+    store.complete_task(&parent, now).unwrap();
+    store.delete_task(&first).unwrap();
+    store.restore(&parent).unwrap();
+    assert_eq!(root_titles(&store), ["parent"]);
+
+    // Restored to the root tasks once the parent is gone
+    store.complete_task(&last, now).unwrap();
+    store.delete_task(&parent).unwrap();
+    store.restore(&last).unwrap();
+    assert_eq!(root_titles(&store), ["last"]);
+    assert_eq!(store.find_parent(&last).unwrap().parent_id, None);
+
+    assert_eq!(
+        store.restore(&last),
+        Err(StoreError::NotFound(last.clone()))
+    );
+}
+
+fn check_delete_tag<T: DataTaskStore>(mut store: T) {
+    let tag = |name: &str| Tag {
+        name: name.to_string(),
+        colour: Color::Reset,
+    };
+    store.set_tag("work", tag("work"));
+    store.set_tag("home", tag("home"));
+    let tags = vec!["work".to_string(), "home".to_string()];
+    let open = store
+        .add_task(
+            Task {
+                tags: tags.clone(),
+                ..task("open")
+            },
+            None,
+        )
+        .unwrap();
+    let completed = store
+        .add_task(
+            Task {
+                tags,
+                ..task("done")
+            },
+            None,
+        )
+        .unwrap();
+    store
+        .complete_task(&completed, Local::now().naive_local())
+        .unwrap();
+
+    store.delete_tag("work");
+    assert!(!store.tags().contains_key("work"));
+    assert!(store.tags().contains_key("home"));
+    assert_eq!(store.task(&open).unwrap().tags, ["home"]);
+    assert_eq!(
+        store.completed_task(&completed).unwrap().task.tags,
+        ["home"]
+    );
+}
+
+fn check_draw_sizes<T: DataTaskStore>(mut store: T) {
+    let parent = store.add_task(task("parent"), None).unwrap();
+    let child = store.add_task(task("child"), Some(&parent)).unwrap();
+    store.add_task(task("nested"), Some(&child)).unwrap();
+    store.add_task(task("other"), None).unwrap();
+
+    // Tasks are added opened
+    assert_eq!(store.find_task_draw_size(&parent), 3);
+    assert_eq!(store.find_tasks_draw_size(), 4);
+    assert_eq!(store.find_task_draw_size("missing"), 0);
+
+    // Closed tasks take a single row
+    store
+        .modify_task(&child, |task| task.opened = false)
+        .unwrap();
+    assert_eq!(store.find_task_draw_size(&parent), 2);
+    store
+        .modify_task(&parent, |task| task.opened = false)
+        .unwrap();
+    assert_eq!(store.find_task_draw_size(&parent), 1);
+    assert_eq!(store.find_tasks_draw_size(), 2);
+
+    // Opening a subtask of a closed task does not show it
+    store
+        .modify_task(&child, |task| task.opened = true)
+        .unwrap();
+    assert_eq!(store.find_tasks_draw_size(), 2);
+    assert_eq!(store.visible_rows().len(), 2);
+}
+
+/// Runs every check, each on a new store.
+fn run_suite<T: DataTaskStore>(mut new_store: impl FnMut() -> T) {
+    check_add(new_store());
+    check_move(new_store());
+    check_sort(new_store());
+    check_complete(new_store());
+    check_restore(new_store());
+    check_delete_tag(new_store());
+    check_draw_sizes(new_store());
+}
+
+#[test]
+fn test_json_conformance() {
+    run_suite(JsonDataStore::default);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_todoist_conformance() {
+    let mut sinks = Vec::new();
+    run_suite(|| {
+        let (store, commands) = todoist_store_with_sink();
+        sinks.push(commands);
+        store
+    });
+
+    // Every change reached the sink rather than failing to send
+    for mut commands in sinks {
+        let mut count = 0;
+        while let Ok(command) = commands.try_recv() {
+            assert!(matches!(command, TodoistCommand::Send(_)));
+            count += 1;
+        }
+        assert!(count > 0);
+    }
+}
@@ -0,0 +1,100 @@
+use crate::{
+    config::{Config, DataSource},
+    credentials::{self, TokenSource},
+    data_io::migrate_plain_token,
+};
+
+const SECRET: &str = "0123456789abcdef";
+
+/// The config with the token written directly into it.
+fn plain_config() -> Config {
+    serde_yaml::from_str(&format!("data_source:\n  Todoist: {SECRET}\n")).unwrap()
+}
+
+fn temp_dir() -> std::path::PathBuf {
+    std::env::temp_dir().join(uuid::Uuid::new_v4().to_string())
+}
+
+#[test]
+fn test_plain_token_is_moved() {
+    let mut config = plain_config();
+    assert!(matches!(
+        &config.data_source,
+        DataSource::Todoist(TokenSource::Plain(token)) if token == SECRET
+    ));
+
+    // The config is saved as soon as the token is moved
+    let dir = temp_dir();
+    migrate_plain_token(&mut config, Some(dir.clone())).unwrap();
+    let saved = std::fs::read_to_string(dir.join("dotodo").join("config.yml")).unwrap();
+    assert!(!saved.contains(SECRET));
+
+    let config: Config = serde_yaml::from_str(&saved).unwrap();
+    let DataSource::Todoist(source) = config.data_source else {
+        panic!("Expected Todoist");
+    };
+    assert!(matches!(source, TokenSource::File(_)));
+    assert_eq!(source.token().unwrap(), SECRET);
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_plain_token_kept_when_move_fails() {
+    // The credentials file can not be created inside a file
+    let dir = temp_dir();
+    std::fs::create_dir_all(&dir).unwrap();
+    std::fs::write(dir.join("dotodo"), "").unwrap();
+
+    let mut config = plain_config();
+    assert!(migrate_plain_token(&mut config, Some(dir.clone())).is_err());
+    assert!(matches!(
+        &config.data_source,
+        DataSource::Todoist(TokenSource::Plain(token)) if token == SECRET
+    ));
+    // The token is never written back
+    assert!(serde_yaml::to_string(&config).is_err());
+
+    std::fs::remove_dir_all(dir).unwrap();
+}
+
+#[test]
+fn test_token_sources_round_trip() {
+    let config: Config =
+        serde_yaml::from_str("data_source:\n  Todoist:\n    Command: echo token\n").unwrap();
+    let DataSource::Todoist(source) = &config.data_source else {
+        panic!("Expected Todoist");
+    };
+    assert_eq!(source, &TokenSource::Command(String::from("echo token")));
+    let saved: Config = serde_yaml::from_str(&serde_yaml::to_string(&config).unwrap()).unwrap();
+    assert!(matches!(saved.data_source, DataSource::Todoist(s) if &s == source));
+}
+
+#[cfg(unix)]
+#[test]
+fn test_token_from_command() {
+    let source = TokenSource::Command(format!("echo '  {SECRET}  '"));
+    assert_eq!(source.token().unwrap(), SECRET);
+
+    assert!(TokenSource::Command(String::from("exit 1"))
+        .token()
+        .is_err());
+    assert!(TokenSource::Command(String::from("true")).token().is_err());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_credentials_file_is_private() {
+    use std::os::unix::fs::PermissionsExt;
+
+    let path = std::env::temp_dir()
+        .join(uuid::Uuid::new_v4().to_string())
+        .join("credentials");
+    credentials::write_credentials(&path, SECRET).unwrap();
+
+    let mode = std::fs::metadata(&path).unwrap().permissions().mode();
+    assert_eq!(mode & 0o777, 0o600);
+    assert_eq!(TokenSource::File(path.clone()).token().unwrap(), SECRET);
+
+    std::fs::remove_dir_all(path.parent().unwrap()).unwrap();
+}
@@ -0,0 +1,165 @@
+use chrono::Duration;
+use crossterm::event::KeyCode;
+use tui::style::Color;
+
+use crate::{
+    cli,
+    data::{data_store::DataTaskStore, json_data_store::JsonDataStore},
+    task::{Tag, Task},
+    utils::{
+        self,
+        test::{get_task_from_pos, input_char, input_code, input_str, setup},
+    },
+};
+
+fn task_with_estimate(title: &str, minutes: Option<i64>) -> Task {
+    Task {
+        estimate: minutes.map(Duration::minutes),
+        ..Task::from_string(title)
+    }
+}
+
+#[test]
+fn test_parse_duration() {
+    assert_eq!(
+        utils::parse_duration("1h30m").unwrap(),
+        Duration::minutes(90)
+    );
+    assert_eq!(utils::parse_duration("2h").unwrap(), Duration::hours(2));
+    assert_eq!(utils::parse_duration("45m").unwrap(), Duration::minutes(45));
+    assert_eq!(utils::parse_duration("45").unwrap(), Duration::minutes(45));
+    assert!(utils::parse_duration("").is_err());
+    assert!(utils::parse_duration("1x").is_err());
+    assert!(utils::parse_duration("h").is_err());
+    assert!(utils::parse_duration("1h30").is_err());
+}
+
+#[test]
+fn test_quick_add_estimate() {
+    let mut main_app = setup(JsonDataStore::default());
+    input_char('a', &mut main_app);
+    input_str("write tests ~1h30m please", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+
+    let task = get_task_from_pos(&main_app.app.task_store, 0);
+    assert_eq!(task.title, "write tests please");
+    assert_eq!(task.estimate, Some(Duration::minutes(90)));
+
+    // An invalid estimate shows an error instead of adding the task
+    input_char('a', &mut main_app);
+    input_str("broken ~soon", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+    assert_eq!(main_app.app.task_store.root_tasks().len(), 1);
+    assert_eq!(main_app.overlays.len(), 1);
+}
+
+#[test]
+fn test_estimate_dialog() {
+    let mut task_store = JsonDataStore::default();
+    task_store
+        .add_task(Task::from_string("task"), None)
+        .unwrap();
+    let mut main_app = setup(task_store);
+
+    input_char('E', &mut main_app);
+    input_str("2h15m", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+    assert_eq!(
+        get_task_from_pos(&main_app.app.task_store, 0).estimate,
+        Some(Duration::minutes(135))
+    );
+
+    input_char('E', &mut main_app);
+    input_code(KeyCode::Backspace, &mut main_app);
+    input_code(KeyCode::Backspace, &mut main_app);
+    input_code(KeyCode::Backspace, &mut main_app);
+    input_code(KeyCode::Backspace, &mut main_app);
+    input_code(KeyCode::Backspace, &mut main_app);
+    input_code(KeyCode::Backspace, &mut main_app);
+    input_str("none", &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+    assert_eq!(
+        get_task_from_pos(&main_app.app.task_store, 0).estimate,
+        None
+    );
+}
+
+#[test]
+fn test_rolled_up_estimate() {
+    let mut task_store = JsonDataStore::default();
+    let parent = task_store
+        .add_task(task_with_estimate("parent", Some(30)), None)
+        .unwrap();
+    task_store
+        .add_task(task_with_estimate("child", Some(60)), Some(&parent))
+        .unwrap();
+    let child = task_store
+        .add_task(task_with_estimate("no estimate", None), Some(&parent))
+        .unwrap();
+    task_store
+        .add_task(task_with_estimate("grandchild", Some(15)), Some(&child))
+        .unwrap();
+    let nothing = task_store
+        .add_task(task_with_estimate("nothing", None), None)
+        .unwrap();
+    // Closed subtasks are still counted
+    task_store
+        .modify_task(&parent, |task| task.opened = false)
+        .unwrap();
+
+    assert_eq!(
+        task_store.rolled_up_estimate(&parent),
+        Some(Duration::minutes(105))
+    );
+    assert_eq!(
+        task_store.rolled_up_estimate(&child),
+        Some(Duration::minutes(15))
+    );
+    assert_eq!(task_store.rolled_up_estimate(&nothing), None);
+}
+
+#[test]
+fn test_estimate_saved_as_minutes() {
+    let task = task_with_estimate("task", Some(90));
+    let json = serde_json::to_value(&task).unwrap();
+    assert_eq!(json["estimate"], 90);
+    assert_eq!(serde_json::from_value::<Task>(json).unwrap(), task);
+
+    let json = serde_json::to_value(task_with_estimate("task", None)).unwrap();
+    assert!(json.get("estimate").is_none());
+}
+
+#[test]
+fn test_estimates_by_tag() {
+    let mut task_store = JsonDataStore::default();
+    for (id, name) in [("0", "backend"), ("1", "frontend")] {
+        task_store.tags.insert(
+            id.to_string(),
+            Tag {
+                name: name.to_string(),
+                colour: Color::Reset,
+            },
+        );
+    }
+    let mut task = task_with_estimate("api", Some(90));
+    task.tags = vec!["0".to_string()];
+    let parent = task_store.add_task(task, None).unwrap();
+    let mut task = task_with_estimate("form", Some(60));
+    task.tags = vec!["0".to_string(), "1".to_string()];
+    task_store.add_task(task, Some(&parent)).unwrap();
+    task_store
+        .add_task(task_with_estimate("untagged", Some(20)), None)
+        .unwrap();
+
+    assert_eq!(
+        cli::estimates(&task_store),
+        "Estimates of the open tasks by tag
+
+   2h 30m  backend
+   1h 00m  frontend
+      20m  No tag
+
+   2h 50m  Total
+"
+    );
+}
@@ -0,0 +1,139 @@
+use chrono::NaiveDate;
+use serde_json::Value;
+
+use crate::{
+    data::json_data_store::JsonDataStore,
+    error::AppError,
+    storage::json::{
+        migration::{self, CURRENT_VERSION},
+        version::JSONVersion,
+    },
+    task::Priority,
+    utils::test::{at, date},
+};
+
+const V0: &str = include_str!("fixtures/v0.json");
+const V1: &str = include_str!("fixtures/v1.json");
+const V2: &str = include_str!("fixtures/v2.json");
+const V3: &str = include_str!("fixtures/v3.json");
+
+fn migrated_version(contents: &str) -> usize {
+    let value = migration::migrate(
+        serde_json::from_str(contents).unwrap(),
+        at(date(2024, 10, 10), 9),
+    )
+    .unwrap();
+    migration::version(&value).unwrap()
+}
+
+#[test]
+fn test_versions() {
+    for (contents, version) in [(V0, 0), (V1, 1), (V2, 2), (V3, 3)] {
+        let value: Value = serde_json::from_str(contents).unwrap();
+        assert_eq!(migration::version(&value).unwrap(), version);
+        assert_eq!(migrated_version(contents), CURRENT_VERSION);
+    }
+}
+
+#[test]
+fn test_migrate_v0() {
+    let store = migration::load(V0, at(date(2024, 10, 10), 9)).unwrap();
+
+    assert_eq!(store.root, vec!["0", "2"]);
+    assert_eq!(store.subtasks["0"], vec!["1"]);
+    assert_eq!(store.completed_root, vec!["4"]);
+
+    let parent = &store.tasks["0"];
+    assert_eq!(parent.title, "Parent");
+    assert_eq!(parent.priority, Priority::High);
+    assert_eq!(parent.tags, vec!["0"]);
+    assert_eq!(store.tasks["1"].title, "Child");
+    assert_eq!(
+        store.tasks["2"].due_date,
+        NaiveDate::from_ymd_opt(2024, 10, 20)
+    );
+    assert_eq!(store.tags["0"].name, "work");
+
+    // Nothing is known about when these were added
+    assert_eq!(parent.created_at, Some(at(date(2024, 10, 10), 9)));
+    let done = &store.completed_tasks["4"];
+    assert_eq!(done.task.created_at, Some(at(date(2024, 10, 1), 9)));
+    assert_eq!(done.task.updated_at, Some(at(date(2024, 10, 1), 9)));
+}
+
+#[test]
+fn test_migrate_v1() {
+    let store = migration::load(V1, at(date(2024, 10, 10), 9)).unwrap();
+
+    let task = &store.tasks["1"];
+    assert_eq!(task.created_at, Some(at(date(2024, 10, 2), 9)));
+    assert_eq!(task.updated_at, Some(at(date(2024, 10, 5), 9)));
+    let task = &store.tasks["2"];
+    assert_eq!(task.created_at, Some(at(date(2024, 10, 10), 9)));
+    assert_eq!(task.updated_at, Some(at(date(2024, 10, 10), 9)));
+    let task = &store.completed_tasks["3"].task;
+    assert_eq!(task.created_at, Some(at(date(2024, 10, 6), 9)));
+    assert_eq!(task.updated_at, Some(at(date(2024, 10, 6), 9)));
+    assert_eq!(store.subtasks["1"], vec!["2"]);
+}
+
+#[test]
+fn test_migrate_unknown_fields() {
+    // Fields added after a version are left for the current version to read,
+    // the migrations only change what they know about
+    let extended = V1.replace(
+        r#""task_count": 3"#,
+        r#""task_count": 3, "added_later": true"#,
+    );
+    let value = migration::migrate(
+        serde_json::from_str(&extended).unwrap(),
+        at(date(2024, 10, 10), 9),
+    )
+    .unwrap();
+    assert_eq!(value["added_later"], Value::Bool(true));
+    assert_eq!(value["tasks"]["1"]["pomodoros"], 0);
+}
+
+#[test]
+fn test_load_v2() {
+    let store = migration::load(V2, at(date(2024, 10, 10), 9)).unwrap();
+    let task = &store.tasks["1"];
+    assert_eq!(task.pomodoros, 2);
+    assert_eq!(task.created_at, Some(at(date(2024, 10, 1), 9)));
+    assert_eq!(task.updated_at, Some(at(date(2024, 10, 2), 9)));
+
+    // Saving and loading again does not change anything
+    let saved = serde_json::to_string(&JSONVersion::V3(store.clone())).unwrap();
+    let reloaded: JsonDataStore = migration::load(&saved, at(date(2024, 10, 11), 9)).unwrap();
+    assert_eq!(
+        serde_json::to_value(&reloaded).unwrap(),
+        serde_json::to_value(&store).unwrap()
+    );
+}
+
+#[test]
+fn test_errors() {
+    assert!(matches!(
+        migration::load(r#"{"version": "99"}"#, at(date(2024, 10, 10), 9)),
+        Err(AppError::UnsupportedVersion(version)) if version == "99"
+    ));
+
+    // The error of the current version is reported, rather than trying the
+    // data as an older version
+    let broken = V2.replace(r#""root": ["1"]"#, r#""root": 1"#);
+    assert!(matches!(
+        migration::load(&broken, at(date(2024, 10, 10), 9)),
+        Err(AppError::JsonError(_))
+    ));
+
+    let broken = V1.replace("2024-10-03T09:00:00", "not a time");
+    assert!(matches!(
+        migration::load(&broken, at(date(2024, 10, 10), 9)),
+        Err(AppError::MigrationFailed(1, _))
+    ));
+
+    assert!(matches!(
+        migration::load("not json", at(date(2024, 10, 10), 9)),
+        Err(AppError::JsonError(_))
+    ));
+}
@@ -2,15 +2,70 @@
 use itertools::Itertools;
 
 #[cfg(test)]
-use crate::app::App;
+use std::{fs, path::Path};
+
+#[cfg(test)]
+use tui::{backend::TestBackend, buffer::Buffer, style::Style, Terminal};
+
+#[cfg(test)]
+use crate::{
+    app::App, component::logger::Logger, framework::screen_manager::ScreenManager,
+    screens::main_screen::MainScreen,
+};
 
 #[cfg(test)]
 mod actions;
 #[cfg(test)]
+mod benchmarks;
+#[cfg(test)]
+mod comments;
+#[cfg(test)]
+mod completed_days;
+#[cfg(test)]
+mod completed_hierarchy;
+#[cfg(test)]
+mod conformance;
+#[cfg(test)]
+mod credentials;
+#[cfg(test)]
+mod estimates;
+#[cfg(test)]
+mod migration;
+#[cfg(test)]
 mod movement;
 #[cfg(test)]
+mod multi_select;
+#[cfg(test)]
+mod pomodoro;
+#[cfg(test)]
+mod render_loop;
+#[cfg(test)]
+mod scripts;
+#[cfg(test)]
+mod stats;
+#[cfg(test)]
+mod store_errors;
+#[cfg(test)]
+mod store_events;
+#[cfg(test)]
+mod subtask_progress;
+#[cfg(test)]
 mod tags;
 #[cfg(test)]
+mod task_ids;
+#[cfg(test)]
+mod time_tracking;
+#[cfg(test)]
+mod timestamps;
+#[cfg(test)]
+mod todoist;
+#[cfg(test)]
+mod tree_properties;
+#[cfg(test)]
+mod validate;
+#[cfg(test)]
+mod visible_rows;
+#[cfg(test)]
 mod visual;
 
 #[cfg(test)]
@@ -27,94 +82,109 @@ fn assert_task_eq(app: &App, task_names: Vec<&str>) {
     );
 }
 
-// #[cfg(test)]
-// fn generate_buffer(buffer: &Buffer, format_styles: Vec<Style>) -> String {
-//     let mut current_style = Style::default();
-//     let mut drawn_screen = String::new();
-
-//     for cells in buffer.content().chunks(buffer.area.width as usize) {
-//         for (x, cell) in cells.iter().enumerate() {
-//             let style_index = format_styles
-//                 .iter()
-//                 .position(|f| cell.style().eq(f))
-//                 .map(|f| f.to_string())
-//                 .unwrap_or(format!("unexpected: {:?}", cell.style()));
-//             if x == 0 {
-//                 drawn_screen.push_str(&format!("{{{}:{}", style_index, cell.symbol));
-//             } else if current_style != cell.style() {
-//                 drawn_screen.push_str(&format!("}}{{{}:{}", style_index, cell.symbol));
-//             } else {
-//                 drawn_screen.push_str(&cell.symbol);
-//             }
-//             current_style = cell.style();
-//         }
-//         drawn_screen.push_str("}}");
-//         drawn_screen.push('\n');
-//     }
-//     drawn_screen
-// }
-
-// #[cfg(test)]
-// fn assert_screen(
-//     app: &mut App,
-//     stack_layout: &mut StackLayout,
-//     format_styles: Vec<Style>,
-//     expect: &str,
-// ) {
-//     let backend = TestBackend::new(100, 30);
-//     let mut terminal = Terminal::new(backend).unwrap();
-//     let expect_result = expect.trim().split('\n').collect_vec();
-
-//     terminal
-//         .draw(|f| {
-//             let draw_size = f.size();
-
-//             let mut drawer = Drawer::new(f);
-
-//             let chunk = Layout::default()
-//                 .direction(tui::layout::Direction::Vertical)
-//                 .constraints([Constraint::Min(1), Constraint::Length(1)])
-//                 .split(draw_size);
+/// Writes each line of the buffer as runs of `{style:text}`, followed by the
+/// styles used, numbered in the order they first appear.
+#[cfg(test)]
+fn generate_buffer(buffer: &Buffer) -> String {
+    let mut styles: Vec<Style> = Vec::new();
+    let mut drawn_screen = String::new();
 
-//             stack_layout.update_layout(chunk[0]);
-//             stack_layout.draw(app, &mut drawer);
+    for cells in buffer.content().chunks(buffer.area.width as usize) {
+        for run in cells.chunk_by(|a, b| a.style() == b.style()) {
+            let style = run[0].style();
+            let style_index = styles.iter().position(|f| *f == style).unwrap_or_else(|| {
+                styles.push(style);
+                styles.len() - 1
+            });
+            let text = run.iter().map(|cell| cell.symbol()).collect::<String>();
+            drawn_screen.push_str(&format!("{{{}:{}}}", style_index, text));
+        }
+        drawn_screen.push('\n');
+    }
+    drawn_screen.push('\n');
+    for (index, style) in styles.iter().enumerate() {
+        drawn_screen.push_str(&format!("{}: {}\n", index, style_name(style)));
+    }
+    drawn_screen
+}
 
-//             app.status_line.update_layout(chunk[1]);
-//             app.status_line.draw(app, &mut drawer);
-//         })
-//         .unwrap();
+#[cfg(test)]
+fn style_name(style: &Style) -> String {
+    let mut parts = Vec::new();
+    if let Some(fg) = style.fg {
+        parts.push(format!("fg {:?}", fg));
+    }
+    if let Some(bg) = style.bg {
+        parts.push(format!("bg {:?}", bg));
+    }
+    if !style.add_modifier.is_empty() {
+        parts.push(format!("{:?}", style.add_modifier));
+    }
+    if parts.is_empty() {
+        String::from("default")
+    } else {
+        parts.join(", ")
+    }
+}
 
-//     let mut failed = false;
-//     let buffer = generate_buffer(terminal.backend().buffer(), format_styles);
-//     let buffered = buffer.trim().split('\n').collect_vec();
-//     let mut expected = String::new();
+/// Draws the app at this size the same way it is drawn in the terminal, and
+/// compares it with `snapshots/{name}.snap`. Run the tests with
+/// `UPDATE_SNAPSHOTS=1` to write the snapshots again after a deliberate change.
+#[cfg(test)]
+fn assert_screen(screen_manager: &mut ScreenManager, name: &str, width: u16, height: u16) {
+    let mut terminal = Terminal::new(TestBackend::new(width, height)).unwrap();
+    crate::draw(
+        &mut terminal,
+        screen_manager,
+        &mut MainScreen::new(),
+        &mut Logger::default(),
+    )
+    .unwrap();
+    let screen = generate_buffer(terminal.backend().buffer());
 
-//     for (y, &expect) in expect_result.iter().enumerate() {
-//         if *buffered.get(y).unwrap_or(&"") != expect {
-//             failed = true;
-//             expected.push_str(&format!("*{}\n", expect));
-//         } else {
-//             expected.push_str(&format!("{}\n", expect));
-//         }
-//     }
+    let path = Path::new(env!("CARGO_MANIFEST_DIR"))
+        .join("src/tests/snapshots")
+        .join(format!("{}.snap", name));
+    if std::env::var_os("UPDATE_SNAPSHOTS").is_some() {
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, &screen).unwrap();
+        return;
+    }
+    let Ok(expected) = fs::read_to_string(&path) else {
+        panic!(
+            "There is no snapshot at {}, run with UPDATE_SNAPSHOTS=1 to write it.\n\n{}",
+            path.display(),
+            screen
+        );
+    };
+    if expected != screen {
+        panic!(
+            "The screen does not match {}, run with UPDATE_SNAPSHOTS=1 if the change is expected.\n\n{}",
+            path.display(),
+            diff_lines(&expected, &screen)
+        );
+    }
+}
 
-//     if failed {
-//         let mut debug_info = format!(
-//             "Screen does not match. {}\n\n",
-//             if buffered.len() != expect_result.len() {
-//                 format!(
-//                     "Screen size does not match, expected {}, actual {}.",
-//                     expect_result.len(),
-//                     buffered.len()
-//                 )
-//             } else {
-//                 "".to_string()
-//             }
-//         );
-//         debug_info.push_str("Expected:\n");
-//         debug_info.push_str(&expected);
-//         debug_info.push_str("\n\nFound:\n");
-//         debug_info.push_str(&buffer);
-//         panic!("{}", debug_info);
-//     }
-// }
+/// Each line of both, marking the lines that are only expected with `-` and
+/// the lines that were only found with `+`.
+#[cfg(test)]
+fn diff_lines(expected: &str, found: &str) -> String {
+    expected
+        .lines()
+        .map(Some)
+        .chain(std::iter::repeat(None))
+        .zip(found.lines().map(Some).chain(std::iter::repeat(None)))
+        .take_while(|lines| *lines != (None, None))
+        .map(|lines| match lines {
+            (Some(expected), Some(found)) if expected == found => format!("  {}", found),
+            (expected, found) => [
+                expected.map(|line| format!("- {}", line)),
+                found.map(|line| format!("+ {}", line)),
+            ]
+            .into_iter()
+            .flatten()
+            .join("\n"),
+        })
+        .join("\n")
+}
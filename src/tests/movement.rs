@@ -8,8 +8,12 @@ use crate::{
 #[test]
 fn test_rollover() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
-    json_data_store.add_task(Task::from_string("oof"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
+    json_data_store
+        .add_task(Task::from_string("oof"), None)
+        .unwrap();
     let mut screen_manager = setup(json_data_store);
 
     input_char('j', &mut screen_manager);
@@ -32,8 +36,12 @@ fn test_rollover() {
 #[test]
 fn test_shifting_tasks() {
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
-    json_data_store.add_task(Task::from_string("based"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
+    json_data_store
+        .add_task(Task::from_string("based"), None)
+        .unwrap();
     let mut screen_manager = setup(json_data_store);
 
     input_char('J', &mut screen_manager);
@@ -0,0 +1,386 @@
+use crossterm::event::KeyCode;
+use tui::style::Color;
+
+use crate::{
+    data::data_store::{DataTaskStore, TaskID},
+    framework::screen_manager::ScreenManager,
+    task::{Priority, Tag},
+    utils::test::{input_char, input_code, input_ctrl, input_str, setup, task_tree},
+};
+
+/// The root tasks `one` to `four`, where `two` has the subtask `nested`.
+fn screen_manager() -> ScreenManager {
+    let (store, _) = task_tree([
+        (0, "one"),
+        (0, "two"),
+        (1, "nested"),
+        (0, "three"),
+        (0, "four"),
+    ]);
+    setup(store)
+}
+
+fn titles(screen_manager: &ScreenManager, ids: &[TaskID]) -> Vec<String> {
+    ids.iter()
+        .map(|id| {
+            screen_manager
+                .app
+                .task_store
+                .task(id)
+                .unwrap()
+                .title
+                .clone()
+        })
+        .collect()
+}
+
+fn root_titles(screen_manager: &ScreenManager) -> Vec<String> {
+    titles(screen_manager, screen_manager.app.task_store.root_tasks())
+}
+
+/// The root titles, leaving out an id that has no task.
+fn root_titles_without(screen_manager: &ScreenManager, missing: &str) -> Vec<String> {
+    let ids = screen_manager
+        .app
+        .task_store
+        .root_tasks()
+        .iter()
+        .filter(|id| *id != missing)
+        .cloned()
+        .collect::<Vec<_>>();
+    titles(screen_manager, &ids)
+}
+
+fn selected_titles(screen_manager: &ScreenManager) -> Vec<String> {
+    let selection = screen_manager
+        .app
+        .task_list
+        .selection(&screen_manager.app.task_store);
+    titles(screen_manager, &selection)
+}
+
+fn find(screen_manager: &ScreenManager, title: &str) -> TaskID {
+    let rows = screen_manager.app.task_store.visible_rows();
+    let id = rows
+        .iter()
+        .find(|(id, _)| screen_manager.app.task_store.task(id).unwrap().title == title)
+        .map(|(id, _)| id.to_string());
+    id.unwrap()
+}
+
+#[test]
+fn test_visual_range() {
+    let mut screen_manager = screen_manager();
+    assert!(!screen_manager.app.task_list.is_selecting());
+    assert_eq!(selected_titles(&screen_manager), ["one"]);
+
+    input_char('v', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    assert!(screen_manager.app.task_list.is_selecting());
+    assert_eq!(selected_titles(&screen_manager), ["one", "two", "nested"]);
+
+    // The range is kept once visual mode ends
+    input_char('v', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    assert_eq!(selected_titles(&screen_manager), ["one", "two", "nested"]);
+
+    input_code(KeyCode::Esc, &mut screen_manager);
+    assert!(!screen_manager.app.task_list.is_selecting());
+    assert_eq!(selected_titles(&screen_manager), ["three"]);
+
+    // `V` starts a range in the same way
+    input_char('V', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    assert_eq!(selected_titles(&screen_manager), ["three", "four"]);
+}
+
+#[test]
+fn test_marks() {
+    let mut screen_manager = screen_manager();
+    input_char('j', &mut screen_manager);
+    input_char('m', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('m', &mut screen_manager);
+    assert_eq!(selected_titles(&screen_manager), ["two", "four"]);
+
+    input_char('m', &mut screen_manager);
+    assert_eq!(selected_titles(&screen_manager), ["two"]);
+
+    // Select all, then deselect all
+    input_ctrl('a', &mut screen_manager);
+    assert_eq!(
+        selected_titles(&screen_manager),
+        ["one", "two", "nested", "three", "four"]
+    );
+    input_ctrl('a', &mut screen_manager);
+    assert!(!screen_manager.app.task_list.is_selecting());
+}
+
+#[test]
+fn test_complete_selected() {
+    let mut screen_manager = screen_manager();
+    input_char('v', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('c', &mut screen_manager);
+
+    assert_eq!(root_titles(&screen_manager), ["four"]);
+    assert_eq!(
+        screen_manager.app.task_store.completed_root_tasks().len(),
+        3
+    );
+    assert!(!screen_manager.app.task_list.is_selecting());
+    assert_eq!(screen_manager.app.task_list.selected_index, 0);
+}
+
+#[test]
+fn test_delete_selected() {
+    let mut screen_manager = screen_manager();
+    input_char('m', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('m', &mut screen_manager);
+    input_char('d', &mut screen_manager);
+    assert_eq!(screen_manager.overlays.len(), 1);
+    input_code(KeyCode::Enter, &mut screen_manager);
+
+    assert_eq!(root_titles(&screen_manager), ["two", "three", "four"]);
+    assert!(screen_manager
+        .app
+        .task_store
+        .subtasks(&find(&screen_manager, "two"))
+        .unwrap()
+        .is_empty());
+    assert!(!screen_manager.app.task_list.is_selecting());
+}
+
+#[test]
+fn test_priority_selected() {
+    let mut screen_manager = screen_manager();
+    let three = find(&screen_manager, "three");
+    screen_manager
+        .app
+        .task_store
+        .modify_task(&three, |task| task.priority = Priority::High)
+        .unwrap();
+
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('v', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('p', &mut screen_manager);
+
+    // Both follow the first selected task
+    let priority = |title: &str| {
+        let id = find(&screen_manager, title);
+        screen_manager.app.task_store.task(&id).unwrap().priority
+    };
+    assert_eq!(priority("three"), Priority::High.next_priority());
+    assert_eq!(priority("four"), Priority::High.next_priority());
+    assert_eq!(priority("one"), Priority::None);
+    assert_eq!(selected_titles(&screen_manager), ["three", "four"]);
+}
+
+#[test]
+fn test_tag_selected() {
+    let mut screen_manager = screen_manager();
+    screen_manager.app.task_store.set_tag(
+        "0",
+        Tag {
+            name: "work".to_string(),
+            colour: Color::Blue,
+        },
+    );
+    let one = find(&screen_manager, "one");
+    screen_manager
+        .app
+        .task_store
+        .modify_task(&one, |task| task.tags.push("0".to_string()))
+        .unwrap();
+    let tags = |screen_manager: &ScreenManager, title: &str| {
+        let id = find(screen_manager, title);
+        screen_manager
+            .app
+            .task_store
+            .task(&id)
+            .unwrap()
+            .tags
+            .clone()
+    };
+
+    // Added to the task without it, as not every task has it
+    input_ctrl('a', &mut screen_manager);
+    input_char('t', &mut screen_manager);
+    input_code(KeyCode::Enter, &mut screen_manager);
+    for title in ["one", "two", "nested", "three", "four"] {
+        assert_eq!(tags(&screen_manager, title), ["0"], "{}", title);
+    }
+
+    input_char('t', &mut screen_manager);
+    input_code(KeyCode::Enter, &mut screen_manager);
+    for title in ["one", "two", "nested", "three", "four"] {
+        assert!(tags(&screen_manager, title).is_empty(), "{}", title);
+    }
+}
+
+#[test]
+fn test_due_date_selected() {
+    let mut screen_manager = screen_manager();
+    input_char('m', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('m', &mut screen_manager);
+    input_char('D', &mut screen_manager);
+    input_str("2020-03-01", &mut screen_manager);
+    input_code(KeyCode::Enter, &mut screen_manager);
+
+    let due_date = |title: &str| {
+        let id = find(&screen_manager, title);
+        screen_manager.app.task_store.task(&id).unwrap().due_date
+    };
+    let date = chrono::NaiveDate::from_ymd_opt(2020, 3, 1);
+    assert_eq!(due_date("one"), date);
+    assert_eq!(due_date("three"), date);
+    assert_eq!(due_date("two"), None);
+}
+
+#[test]
+fn test_move_selected() {
+    let mut screen_manager = screen_manager();
+    input_char('m', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('m', &mut screen_manager);
+
+    input_char('J', &mut screen_manager);
+    assert_eq!(
+        root_titles(&screen_manager),
+        ["two", "one", "four", "three"]
+    );
+    assert_eq!(screen_manager.app.task_list.selected_index, 4);
+
+    // The last task can not move further, so neither can the one above it
+    input_char('J', &mut screen_manager);
+    assert_eq!(
+        root_titles(&screen_manager),
+        ["two", "four", "one", "three"]
+    );
+    input_char('J', &mut screen_manager);
+    assert_eq!(
+        root_titles(&screen_manager),
+        ["two", "four", "one", "three"]
+    );
+
+    input_char('K', &mut screen_manager);
+    input_char('K', &mut screen_manager);
+    input_char('K', &mut screen_manager);
+    assert_eq!(
+        root_titles(&screen_manager),
+        ["one", "three", "two", "four"]
+    );
+    assert_eq!(selected_titles(&screen_manager), ["one", "three"]);
+}
+
+#[test]
+fn test_indent_selected() {
+    let mut screen_manager = screen_manager();
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('v', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+
+    input_char('L', &mut screen_manager);
+    let two = find(&screen_manager, "two");
+    assert_eq!(root_titles(&screen_manager), ["one", "two"]);
+    assert_eq!(
+        titles(
+            &screen_manager,
+            screen_manager.app.task_store.subtasks(&two).unwrap()
+        ),
+        ["nested", "three", "four"]
+    );
+    assert_eq!(screen_manager.app.task_list.selected_index, 4);
+
+    // Only the parent is moved when its subtasks are selected along with it
+    input_code(KeyCode::Esc, &mut screen_manager);
+    input_ctrl('a', &mut screen_manager);
+    input_char('H', &mut screen_manager);
+    assert_eq!(root_titles(&screen_manager), ["one", "two"]);
+
+    input_ctrl('a', &mut screen_manager);
+    input_char('k', &mut screen_manager);
+    input_char('k', &mut screen_manager);
+    input_char('v', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('H', &mut screen_manager);
+    assert_eq!(
+        root_titles(&screen_manager),
+        ["one", "two", "nested", "three", "four"]
+    );
+    assert_eq!(screen_manager.app.task_list.selected_index, 4);
+}
+
+#[test]
+fn test_failed_indent_is_undone() {
+    let (mut store, [one, ..]) = task_tree([(0, "one"), (0, "two"), (0, "three")]);
+    store.modify_task(&one, |task| task.opened = false).unwrap();
+    // A task that is listed but missing, so the task below it can not be
+    // moved into it
+    store.root.insert(2, "missing".to_string());
+    store.rebuild_indexes();
+    let mut screen_manager = setup(store);
+
+    input_char('j', &mut screen_manager);
+    input_char('v', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('L', &mut screen_manager);
+    assert_eq!(
+        screen_manager.app.task_store.root_tasks()[2],
+        "missing",
+        "The tasks were not put back around the missing task"
+    );
+    assert_eq!(
+        root_titles_without(&screen_manager, "missing"),
+        ["one", "two", "three"]
+    );
+    assert!(!screen_manager.app.task_store.task(&one).unwrap().opened);
+}
+
+#[test]
+fn test_missing_task_changes_nothing() {
+    let mut screen_manager = screen_manager();
+    screen_manager.app.task_store.set_tag(
+        "0",
+        Tag {
+            name: "work".to_string(),
+            colour: Color::Blue,
+        },
+    );
+    input_char('v', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    input_char('t', &mut screen_manager);
+
+    // The task is removed while the tags are being chosen
+    let two = find(&screen_manager, "two");
+    screen_manager.app.task_store.delete_task(&two).unwrap();
+    input_code(KeyCode::Enter, &mut screen_manager);
+
+    let one = find(&screen_manager, "one");
+    assert!(screen_manager
+        .app
+        .task_store
+        .task(&one)
+        .unwrap()
+        .tags
+        .is_empty());
+    assert_eq!(screen_manager.overlays.len(), 1, "The error is shown");
+}
@@ -0,0 +1,102 @@
+use std::time::Duration;
+
+use crate::{
+    component::overlay::pomodoro::{Phase, PomodoroBox},
+    data::{data_store::DataTaskStore, json_data_store::JsonDataStore},
+    framework::screen_manager::ScreenManager,
+    task::Task,
+    utils::test::{input_char, setup},
+};
+
+fn setup_pomodoro() -> ScreenManager {
+    let mut task_store = JsonDataStore::default();
+    task_store
+        .add_task(Task::from_string("focus"), None)
+        .unwrap();
+    let mut main_app = setup(task_store);
+    main_app.app.config.pomodoro_bell = false;
+    main_app.app.config.pomodoro_work_minutes = 25;
+    main_app.app.config.pomodoro_break_minutes = 5;
+    main_app
+}
+
+fn minutes(minutes: u64) -> Duration {
+    Duration::from_secs(minutes * 60)
+}
+
+#[test]
+fn test_pomodoro_phases() {
+    let mut main_app = setup_pomodoro();
+    let app = &mut main_app.app;
+    let id = app.task_store.root_tasks()[0].clone();
+    let mut pomodoro = PomodoroBox::new(app, id.clone());
+    assert_eq!(pomodoro.phase, Phase::Work);
+    assert_eq!(pomodoro.remaining, minutes(25));
+
+    pomodoro.advance(app, minutes(24));
+    assert_eq!(pomodoro.phase, Phase::Work);
+    assert_eq!(pomodoro.remaining, minutes(1));
+    assert_eq!(app.task_store.task(&id).unwrap().pomodoros, 0);
+
+    pomodoro.advance(app, minutes(1));
+    assert_eq!(pomodoro.phase, Phase::Break);
+    assert_eq!(pomodoro.remaining, minutes(5));
+    assert_eq!(app.task_store.task(&id).unwrap().pomodoros, 1);
+
+    // Breaks don't count as a pomodoro
+    pomodoro.advance(app, minutes(6));
+    assert_eq!(pomodoro.phase, Phase::Work);
+    assert_eq!(app.task_store.task(&id).unwrap().pomodoros, 1);
+}
+
+#[test]
+fn test_pomodoro_paused() {
+    let mut main_app = setup_pomodoro();
+    let app = &mut main_app.app;
+    let id = app.task_store.root_tasks()[0].clone();
+    let mut pomodoro = PomodoroBox::new(app, id.clone());
+    pomodoro.paused = true;
+    pomodoro.advance(app, minutes(30));
+    assert_eq!(pomodoro.phase, Phase::Work);
+    assert_eq!(pomodoro.remaining, minutes(25));
+}
+
+#[test]
+fn test_pomodoro_overlay() {
+    let mut main_app = setup_pomodoro();
+    let id = main_app.app.task_store.root_tasks()[0].clone();
+    input_char('P', &mut main_app);
+    assert_eq!(main_app.overlays.len(), 1);
+
+    // Skip the work phase
+    input_char('s', &mut main_app);
+    assert_eq!(main_app.app.task_store.task(&id).unwrap().pomodoros, 1);
+
+    input_char('q', &mut main_app);
+    assert!(main_app.overlays.is_empty());
+}
+
+#[cfg(unix)]
+#[test]
+fn test_pomodoro_command() {
+    let path = std::env::temp_dir().join(format!("dotodo-pomodoro-{}", std::process::id()));
+    let mut main_app = setup_pomodoro();
+    main_app.app.config.pomodoro_command =
+        Some(format!("echo \"$DOTODO_PHASE\" > {}", path.display()));
+
+    let app = &mut main_app.app;
+    let id = app.task_store.root_tasks()[0].clone();
+    let mut pomodoro = PomodoroBox::new(app, id.clone());
+    pomodoro.finish_phase(app);
+
+    let mut contents = String::new();
+    for _ in 0..50 {
+        contents = std::fs::read_to_string(&path).unwrap_or_default();
+        if !contents.is_empty() {
+            break;
+        }
+        std::thread::sleep(Duration::from_millis(20));
+    }
+    let _ = std::fs::remove_file(&path);
+    assert_eq!(contents.trim(), "work");
+}
@@ -0,0 +1,131 @@
+use std::{io, time::Duration};
+
+use crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+use futures::channel::mpsc::unbounded;
+use tui::{backend::TestBackend, Terminal};
+
+use crate::{
+    app::App,
+    component::logger::Logger,
+    config::Config,
+    data::{
+        data_store::{DataTaskStore, DataTaskStoreKind},
+        json_data_store::JsonDataStore,
+    },
+    framework::{
+        component::{ANIMATION_TICK, SECOND_TICK},
+        screen_manager::ScreenManager,
+    },
+    start_app,
+    task::Task,
+    utils::test::{input_char, setup, todoist_store_with_sink},
+};
+
+fn key(code: KeyCode, modifiers: KeyModifiers) -> io::Result<Event> {
+    Ok(Event::Key(KeyEvent::new(code, modifiers)))
+}
+
+/// Runs the app, sending the keys and then leaving it idle for the given time
+/// before quitting. Returns how many times the screen was drawn.
+///
+/// The tests run with the clock paused, so the idle time passes instantly and
+/// the ticks land at the same points each run. The idle times are kept off
+/// the ticks so quitting never races a tick.
+async fn count_draws(screen_manager: &mut ScreenManager, keys: &[char], idle_ms: u64) -> usize {
+    let mut terminal = Terminal::new(TestBackend::new(80, 20)).unwrap();
+    let (events, event_stream) = unbounded();
+    let (_sync_send, sync_recv) = tokio::sync::mpsc::channel(1);
+
+    let send_keys = async move {
+        for c in keys {
+            events
+                .unbounded_send(key(KeyCode::Char(*c), KeyModifiers::NONE))
+                .unwrap();
+        }
+        tokio::time::sleep(Duration::from_millis(idle_ms)).await;
+        events
+            .unbounded_send(key(KeyCode::Char('c'), KeyModifiers::CONTROL))
+            .unwrap();
+    };
+    let (result, _) = tokio::join!(
+        start_app(
+            screen_manager,
+            &mut terminal,
+            sync_recv,
+            event_stream,
+            Logger::default(),
+        ),
+        send_keys
+    );
+    result.unwrap();
+    terminal.get_frame().count()
+}
+
+fn task_store() -> JsonDataStore {
+    let mut store = JsonDataStore::default();
+    store.add_task(Task::from_string("first"), None).unwrap();
+    store.add_task(Task::from_string("second"), None).unwrap();
+    store
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_idle_is_not_redrawn() {
+    let mut screen_manager = setup(task_store());
+    assert_eq!(screen_manager.tick_rate(), None);
+
+    // Ticking every 100ms would draw 10 more times
+    assert_eq!(count_draws(&mut screen_manager, &[], 1050).await, 1);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_redrawn_after_input() {
+    let mut screen_manager = setup(task_store());
+
+    // Once at the start and once for the key
+    assert_eq!(count_draws(&mut screen_manager, &['j'], 550).await, 2);
+    assert_eq!(screen_manager.app.task_list.selected_index, 1);
+    assert_eq!(screen_manager.app.tick, 0);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ticks_while_timer_running() {
+    let mut screen_manager = setup(task_store());
+    input_char('T', &mut screen_manager);
+    assert_eq!(screen_manager.tick_rate(), Some(SECOND_TICK));
+
+    // Once at the start and on each of the 2 ticks, as it only shows seconds
+    assert_eq!(count_draws(&mut screen_manager, &[], 2050).await, 3);
+
+    input_char('T', &mut screen_manager);
+    assert_eq!(screen_manager.tick_rate(), None);
+}
+
+#[tokio::test(start_paused = true)]
+async fn test_ticks_while_pomodoro_counts_down() {
+    let mut screen_manager = setup(task_store());
+    input_char('P', &mut screen_manager);
+    assert_eq!(screen_manager.tick_rate(), Some(SECOND_TICK));
+
+    // Paused, so nothing changes until it is resumed
+    input_char(' ', &mut screen_manager);
+    assert_eq!(screen_manager.tick_rate(), None);
+    assert_eq!(count_draws(&mut screen_manager, &[], 550).await, 1);
+
+    // Once more for the key that resumes it
+    assert_eq!(count_draws(&mut screen_manager, &[' '], 2050).await, 4);
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_spinner_ticks_once_a_change_is_sent() {
+    let (mut task_store, _commands) = todoist_store_with_sink();
+    task_store
+        .add_task(Task::from_string("first"), None)
+        .unwrap();
+    let screen_manager = ScreenManager {
+        overlays: vec![],
+        app: App::new(Config::default(), DataTaskStoreKind::Todoist(task_store)),
+    };
+
+    // Without waiting for the sync loop to pick the command up
+    assert_eq!(screen_manager.tick_rate(), Some(ANIMATION_TICK));
+}
@@ -0,0 +1,356 @@
+//! Replays the scripts in `fixtures/scripts`, each script is a list of
+//! directives that are run in order:
+//!
+//! ```text
+//! # Comments and blank lines are skipped
+//! tasks:
+//!   Parent
+//!     Subtask
+//! keys: "a" "Buy milk" <Enter> "p" <C-n>
+//! tree:
+//!   Parent
+//!     Subtask
+//!   Buy milk
+//! completed:
+//! selected: 1
+//! overlays: 0
+//! shows: "Buy milk"
+//! ```
+//!
+//! `tasks:` can only come first and sets up the tasks the app starts with.
+//! Quoted text is typed one character at a time and `<...>` is a single key.
+//! `tree:` and `completed:` list every open or completed task, nested by two
+//! spaces per level. `shows:` draws the app and looks for the text on screen.
+
+use std::{fs, path::Path};
+
+use crossterm::event::{KeyCode, KeyEvent, KeyModifiers};
+use tui::{backend::TestBackend, Terminal};
+
+use crate::{
+    component::logger::Logger,
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+    },
+    framework::screen_manager::ScreenManager,
+    input,
+    screens::main_screen::MainScreen,
+    utils::test::{add_tasks, setup},
+};
+
+/// Each task as how deeply it is nested along with its title.
+type Tree = Vec<(usize, String)>;
+
+enum Step {
+    Keys(Vec<KeyEvent>),
+    Tree(Tree),
+    Completed(Tree),
+    Selected(usize),
+    Overlays(usize),
+    Shows(String),
+}
+
+struct Script {
+    tasks: Tree,
+    /// Each step along with the line it is on.
+    steps: Vec<(usize, Step)>,
+}
+
+fn parse_script(source: &str) -> Result<Script, String> {
+    let mut script = Script {
+        tasks: Vec::new(),
+        steps: Vec::new(),
+    };
+    let mut lines = source.lines().enumerate().peekable();
+    while let Some((index, line)) = lines.next() {
+        let line_number = index + 1;
+        let line = line.trim_end();
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        let (directive, argument) = line
+            .split_once(':')
+            .ok_or_else(|| format!("line {}: expected a directive", line_number))?;
+        let argument = argument.trim();
+        let mut block = || {
+            let mut tree = Vec::new();
+            while let Some((_, line)) = lines.next_if(|(_, line)| line.starts_with("  ")) {
+                let line = &line[2..];
+                let title = line.trim_start();
+                tree.push(((line.len() - title.len()) / 2, title.to_string()));
+            }
+            tree
+        };
+        let step = match directive {
+            "tasks" if script.steps.is_empty() => {
+                script.tasks = block();
+                continue;
+            }
+            "tasks" => return Err(format!("line {}: tasks must come first", line_number)),
+            "keys" => Step::Keys(
+                parse_keys(argument).map_err(|err| format!("line {}: {}", line_number, err))?,
+            ),
+            "tree" => Step::Tree(block()),
+            "completed" => Step::Completed(block()),
+            "selected" => Step::Selected(
+                argument
+                    .parse()
+                    .map_err(|_| format!("line {}: expected a row", line_number))?,
+            ),
+            "overlays" => Step::Overlays(
+                argument
+                    .parse()
+                    .map_err(|_| format!("line {}: expected a count", line_number))?,
+            ),
+            "shows" => Step::Shows(
+                argument
+                    .strip_prefix('"')
+                    .and_then(|f| f.strip_suffix('"'))
+                    .ok_or_else(|| format!("line {}: expected quoted text", line_number))?
+                    .to_string(),
+            ),
+            _ => {
+                return Err(format!(
+                    "line {}: unknown directive {}",
+                    line_number, directive
+                ))
+            }
+        };
+        script.steps.push((line_number, step));
+    }
+    Ok(script)
+}
+
+fn parse_keys(text: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut keys = Vec::new();
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            ' ' => {}
+            '"' => loop {
+                match chars.next() {
+                    Some('"') => break,
+                    Some('\\') => {
+                        let escaped = chars.next().ok_or("unfinished escape")?;
+                        keys.push(KeyEvent::new(KeyCode::Char(escaped), KeyModifiers::NONE));
+                    }
+                    Some(c) => keys.push(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE)),
+                    None => return Err(String::from("unfinished text")),
+                }
+            },
+            '<' => {
+                let name = chars.by_ref().take_while(|c| *c != '>').collect::<String>();
+                keys.push(parse_key(&name)?);
+            }
+            _ => return Err(format!("unexpected {}, keys are quoted or in <>", c)),
+        }
+    }
+    Ok(keys)
+}
+
+fn parse_key(name: &str) -> Result<KeyEvent, String> {
+    if let Some(c) = name.strip_prefix("C-").and_then(|f| f.chars().next()) {
+        return Ok(KeyEvent::new(KeyCode::Char(c), KeyModifiers::CONTROL));
+    }
+    let code = match name {
+        "Enter" => KeyCode::Enter,
+        "Esc" => KeyCode::Esc,
+        "Tab" => KeyCode::Tab,
+        "BackTab" => KeyCode::BackTab,
+        "Backspace" => KeyCode::Backspace,
+        "Delete" => KeyCode::Delete,
+        "Space" => KeyCode::Char(' '),
+        "Up" => KeyCode::Up,
+        "Down" => KeyCode::Down,
+        "Left" => KeyCode::Left,
+        "Right" => KeyCode::Right,
+        "Home" => KeyCode::Home,
+        "End" => KeyCode::End,
+        _ => return Err(format!("unknown key <{}>", name)),
+    };
+    Ok(KeyEvent::new(code, KeyModifiers::NONE))
+}
+
+fn build_store(tasks: &Tree) -> JsonDataStore {
+    let mut store = JsonDataStore::default();
+    let tasks = tasks
+        .iter()
+        .map(|(depth, title)| (*depth, title.as_str()))
+        .collect::<Vec<_>>();
+    add_tasks(&mut store, &tasks);
+    store
+}
+
+fn walk_tree<T: DataTaskStore>(
+    store: &T,
+    ids: &[TaskID],
+    depth: usize,
+    subtasks: &impl Fn(&T, &str) -> Option<Vec<TaskID>>,
+    title: &impl Fn(&T, &str) -> String,
+    tree: &mut Tree,
+) {
+    for id in ids {
+        tree.push((depth, title(store, id)));
+        if let Some(ids) = subtasks(store, id) {
+            walk_tree(store, &ids, depth + 1, subtasks, title, tree);
+        }
+    }
+}
+
+fn open_tree<T: DataTaskStore>(store: &T) -> Tree {
+    let mut tree = Vec::new();
+    walk_tree(
+        store,
+        store.root_tasks(),
+        0,
+        &|store, id| store.subtasks(id).cloned(),
+        &|store, id| store.task(id).map_or(String::new(), |f| f.title.clone()),
+        &mut tree,
+    );
+    tree
+}
+
+fn completed_tree<T: DataTaskStore>(store: &T) -> Tree {
+    let mut tree = Vec::new();
+    walk_tree(
+        store,
+        store.completed_root_tasks(),
+        0,
+        &|store, id| store.completed_subtasks(id).cloned(),
+        &|store, id| {
+            store
+                .completed_task(id)
+                .map_or(String::new(), |f| f.task.title.clone())
+        },
+        &mut tree,
+    );
+    tree
+}
+
+fn format_tree(tree: &Tree) -> String {
+    tree.iter()
+        .map(|(depth, title)| format!("  {}{}\n", "  ".repeat(*depth), title))
+        .collect()
+}
+
+fn screen_text(screen_manager: &mut ScreenManager) -> String {
+    let mut terminal = Terminal::new(TestBackend::new(100, 30)).unwrap();
+    crate::draw(
+        &mut terminal,
+        screen_manager,
+        &mut MainScreen::new(),
+        &mut Logger::default(),
+    )
+    .unwrap();
+    let buffer = terminal.backend().buffer();
+    buffer
+        .content()
+        .chunks(buffer.area.width as usize)
+        .map(|line| line.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+fn run_script(name: &str, script: Script) {
+    let mut screen_manager = setup(build_store(&script.tasks));
+    for (line, step) in script.steps {
+        let at = format!("{} line {}", name, line);
+        match step {
+            Step::Keys(keys) => {
+                for key in keys {
+                    if let Ok(post_event) = input::key_event(&mut screen_manager, key) {
+                        screen_manager.handle_post_event(post_event);
+                    }
+                }
+            }
+            Step::Tree(expected) => {
+                let found = open_tree(&screen_manager.app.task_store);
+                assert_eq!(
+                    format_tree(&found),
+                    format_tree(&expected),
+                    "{}: the tasks do not match",
+                    at
+                );
+            }
+            Step::Completed(expected) => {
+                let found = completed_tree(&screen_manager.app.task_store);
+                assert_eq!(
+                    format_tree(&found),
+                    format_tree(&expected),
+                    "{}: the completed tasks do not match",
+                    at
+                );
+            }
+            Step::Selected(row) => assert_eq!(
+                screen_manager.app.task_list.selected_index, row,
+                "{}: the selected row",
+                at
+            ),
+            Step::Overlays(count) => assert_eq!(
+                screen_manager.overlays.len(),
+                count,
+                "{}: the number of overlays",
+                at
+            ),
+            Step::Shows(text) => {
+                let screen = screen_text(&mut screen_manager);
+                assert!(
+                    screen.contains(&text),
+                    "{}: {} is not shown\n{}",
+                    at,
+                    text,
+                    screen
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn test_scripts() {
+    let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/fixtures/scripts");
+    let mut paths = fs::read_dir(dir)
+        .unwrap()
+        .map(|entry| entry.unwrap().path())
+        .collect::<Vec<_>>();
+    paths.sort();
+    assert!(!paths.is_empty());
+    for path in paths {
+        let name = path.file_name().unwrap().to_string_lossy().to_string();
+        let script = parse_script(&fs::read_to_string(&path).unwrap())
+            .unwrap_or_else(|err| panic!("{}: {}", name, err));
+        run_script(&name, script);
+    }
+}
+
+#[test]
+fn test_parse_errors() {
+    let error = |source: &str| parse_script(source).err().unwrap();
+    assert_eq!(error("keys: <Nope>"), "line 1: unknown key <Nope>");
+    assert_eq!(error("\nkeys: \"open"), "line 2: unfinished text");
+    assert_eq!(
+        error("keys: a"),
+        "line 1: unexpected a, keys are quoted or in <>"
+    );
+    assert_eq!(
+        error("keys: \"a\"\ntasks:"),
+        "line 2: tasks must come first"
+    );
+    assert_eq!(error("selected: first"), "line 1: expected a row");
+    assert_eq!(error("wait: 1"), "line 1: unknown directive wait");
+    assert_eq!(error("tree"), "line 1: expected a directive");
+}
+
+#[test]
+fn test_parse_keys() {
+    assert_eq!(
+        parse_keys(r#""a\"" <Enter> <C-n>"#).unwrap(),
+        vec![
+            KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('"'), KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            KeyEvent::new(KeyCode::Char('n'), KeyModifiers::CONTROL),
+        ]
+    );
+}
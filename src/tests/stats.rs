@@ -0,0 +1,203 @@
+use chrono::Duration;
+use crossterm::event::KeyCode;
+use tui::style::Color;
+
+use crate::{
+    app::Mode,
+    data::{data_store::DataTaskStore, json_data_store::JsonDataStore},
+    stats::Stats,
+    task::{Priority, Tag, Task},
+    utils::test::{at, date, input_char, input_code, setup},
+};
+
+#[test]
+fn test_completed_counts() {
+    // Wednesday
+    let today = date(2024, 10, 16);
+    let mut json_data_store = JsonDataStore::default();
+    let ids = ["1", "2", "3", "4"].map(|name| {
+        json_data_store
+            .add_task(Task::from_string(name), None)
+            .unwrap()
+    });
+    json_data_store
+        .complete_task(&ids[0], at(today, 9))
+        .unwrap();
+    json_data_store
+        .complete_task(&ids[1], at(date(2024, 10, 14), 9))
+        .unwrap();
+    json_data_store
+        .complete_task(&ids[2], at(date(2024, 10, 13), 9))
+        .unwrap();
+    json_data_store
+        .complete_task(&ids[3], at(date(2024, 10, 13), 15))
+        .unwrap();
+
+    let stats = Stats::new(&json_data_store, today);
+
+    assert_eq!(stats.completed_by_day.len(), 14);
+    assert_eq!(stats.completed_by_day.last(), Some(&(today, 1)));
+    assert_eq!(
+        &stats.completed_by_day[10..],
+        &[
+            (date(2024, 10, 13), 2),
+            (date(2024, 10, 14), 1),
+            (date(2024, 10, 15), 0),
+            (today, 1),
+        ]
+    );
+
+    assert_eq!(stats.completed_by_week.len(), 8);
+    assert_eq!(
+        &stats.completed_by_week[6..],
+        &[(date(2024, 10, 7), 2), (date(2024, 10, 14), 2)]
+    );
+}
+
+#[test]
+fn test_open_counts() {
+    let today = date(2024, 10, 16);
+    let mut json_data_store = JsonDataStore::default();
+    json_data_store.set_tag(
+        "0",
+        Tag {
+            name: "work".to_string(),
+            colour: Color::Red,
+        },
+    );
+    let overdue = json_data_store
+        .add_task(
+            Task {
+                priority: Priority::High,
+                tags: vec!["0".to_string()],
+                due_date: Some(date(2024, 10, 15)),
+                ..Task::from_string("overdue")
+            },
+            None,
+        )
+        .unwrap();
+    json_data_store
+        .add_task(
+            Task {
+                priority: Priority::High,
+                due_date: Some(today),
+                ..Task::from_string("due today")
+            },
+            None,
+        )
+        .unwrap();
+    json_data_store
+        .add_task(
+            Task {
+                tags: vec!["0".to_string()],
+                ..Task::from_string("subtask")
+            },
+            Some(&overdue),
+        )
+        .unwrap();
+
+    let stats = Stats::new(&json_data_store, today);
+
+    assert_eq!(
+        stats.open_by_priority,
+        vec![
+            (Priority::High, 2),
+            (Priority::Normal, 0),
+            (Priority::Low, 0),
+            (Priority::None, 1),
+        ]
+    );
+    assert_eq!(stats.open_by_tag, vec![("work".to_string(), 2)]);
+    assert_eq!(stats.overdue, 1);
+}
+
+#[test]
+fn test_average_time_to_complete() {
+    let today = date(2024, 10, 16);
+    let mut json_data_store = JsonDataStore::default();
+    let first = json_data_store
+        .add_task(
+            Task {
+                created_at: Some(at(today, 9)),
+                ..Task::from_string("1")
+            },
+            None,
+        )
+        .unwrap();
+    let second = json_data_store
+        .add_task(
+            Task {
+                created_at: Some(at(date(2024, 10, 14), 9)),
+                ..Task::from_string("2")
+            },
+            None,
+        )
+        .unwrap();
+    // Not known when this was added, so it is left out of the average
+    let third = json_data_store
+        .add_task(Task::from_string("3"), None)
+        .unwrap();
+    for id in [first, second, third] {
+        json_data_store.complete_task(&id, at(today, 11)).unwrap();
+    }
+
+    let stats = Stats::new(&json_data_store, today);
+    assert_eq!(stats.average_time_to_complete, Some(Duration::hours(26)));
+
+    assert_eq!(
+        Stats::new(&JsonDataStore::default(), today).average_time_to_complete,
+        None
+    );
+}
+
+#[test]
+fn test_streak() {
+    let today = date(2024, 10, 16);
+    let mut json_data_store = JsonDataStore::default();
+    let ids = ["1", "2", "3", "4"].map(|name| {
+        json_data_store
+            .add_task(Task::from_string(name), None)
+            .unwrap()
+    });
+    json_data_store
+        .complete_task(&ids[0], at(date(2024, 10, 15), 9))
+        .unwrap();
+    json_data_store
+        .complete_task(&ids[1], at(date(2024, 10, 14), 9))
+        .unwrap();
+    json_data_store
+        .complete_task(&ids[2], at(date(2024, 10, 12), 9))
+        .unwrap();
+
+    // Nothing has been completed today yet, so the streak counts from yesterday
+    assert_eq!(Stats::new(&json_data_store, today).streak, 2);
+
+    json_data_store
+        .complete_task(&ids[3], at(today, 9))
+        .unwrap();
+    assert_eq!(Stats::new(&json_data_store, today).streak, 3);
+
+    assert_eq!(Stats::new(&json_data_store, date(2024, 10, 20)).streak, 0);
+}
+
+#[test]
+fn test_go_to_stats() {
+    let mut screen_manager = setup(JsonDataStore::default());
+
+    input_char('3', &mut screen_manager);
+    assert!(screen_manager.app.mode == Mode::Stats);
+    assert_eq!(screen_manager.overlays.len(), 1);
+    input_char('3', &mut screen_manager);
+    assert_eq!(screen_manager.overlays.len(), 1);
+
+    input_char('1', &mut screen_manager);
+    assert!(screen_manager.app.mode == Mode::CurrentTasks);
+    assert!(screen_manager.overlays.is_empty());
+
+    // Closing the statistics goes back to where they were opened from
+    input_char('2', &mut screen_manager);
+    input_char('3', &mut screen_manager);
+    input_code(KeyCode::Esc, &mut screen_manager);
+    assert!(screen_manager.app.mode == Mode::CompletedTasks);
+    assert!(screen_manager.overlays.is_empty());
+}
@@ -0,0 +1,151 @@
+use chrono::Local;
+use crossterm::event::KeyCode;
+use tui::{backend::TestBackend, layout::Rect, Terminal};
+
+use crate::{
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+    },
+    error::StoreError,
+    framework::component::Drawer,
+    task::Task,
+    utils::test::{input_char, input_code, setup, todoist_store},
+};
+
+/// The ids are parent and child.
+fn store() -> (JsonDataStore, [TaskID; 2]) {
+    let mut store = JsonDataStore::default();
+    let parent = store.add_task(Task::from_string("parent"), None).unwrap();
+    let child = store
+        .add_task(Task::from_string("child"), Some(&parent))
+        .unwrap();
+    (store, [parent, child])
+}
+
+fn not_found(id: &str) -> StoreError {
+    StoreError::NotFound(id.to_string())
+}
+
+#[test]
+fn test_not_found() {
+    let (mut store, [parent, _]) = store();
+    let now = Local::now().naive_local();
+
+    assert_eq!(
+        store.modify_task("missing", |task| task.progress = true),
+        Err(not_found("missing"))
+    );
+    assert_eq!(
+        store.add_task(Task::from_string("orphan"), Some("missing")),
+        Err(not_found("missing"))
+    );
+    assert_eq!(
+        store.complete_task("missing", now),
+        Err(not_found("missing"))
+    );
+    assert_eq!(store.restore("missing"), Err(not_found("missing")));
+    assert_eq!(
+        store.delete_task("missing").map(|task| task.title),
+        Err(not_found("missing"))
+    );
+    assert_eq!(
+        store.move_task("missing", None, 0, None),
+        Err(not_found("missing"))
+    );
+    assert_eq!(
+        store.add_comment("missing", "note".to_string(), now),
+        Err(not_found("missing"))
+    );
+    assert_eq!(
+        store.update_comment(&parent, "1", "note".to_string()),
+        Err(StoreError::CommentNotFound("1".to_string()))
+    );
+
+    // The task that was not added is not left in the store
+    assert_eq!(store.tasks.len(), 2);
+    assert!(!store.subtasks.contains_key("missing"));
+}
+
+#[test]
+fn test_invalid_moves() {
+    let (mut store, [parent, child]) = store();
+    let now = Local::now().naive_local();
+    let other = store.add_task(Task::from_string("other"), None).unwrap();
+
+    assert_eq!(
+        store.move_task(&parent, Some(parent.clone()), 0, None),
+        Err(StoreError::CycleDetected(parent.clone()))
+    );
+    assert_eq!(
+        store.move_task(&child, Some("missing".to_string()), 0, None),
+        Err(not_found("missing"))
+    );
+    store.complete_task(&other, now).unwrap();
+    assert!(matches!(
+        store.move_task(&child, Some(other.clone()), 0, None),
+        Err(StoreError::InvalidMove(_))
+    ));
+
+    // Nothing was moved
+    assert_eq!(store.root, vec![parent.clone()]);
+    assert_eq!(store.subtasks[&parent], vec![child.clone()]);
+    assert_eq!(
+        store.find_parent(&child).unwrap().parent_id.as_ref(),
+        Some(&parent)
+    );
+}
+
+#[test]
+fn test_json_refresh() {
+    let mut store = JsonDataStore::default();
+    assert_eq!(store.refresh(), Ok(()));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_todoist_errors() {
+    // The sync loop is not running, so nothing receives the commands
+    let mut store = todoist_store();
+    assert!(matches!(store.refresh(), Err(StoreError::Backend(_))));
+
+    let id = store.add_task(Task::from_string("task"), None).unwrap();
+    assert_eq!(
+        store.move_task(&id, Some(id.clone()), 0, None),
+        Err(StoreError::CycleDetected(id.clone()))
+    );
+    assert_eq!(
+        store.add_task(Task::from_string("orphan"), Some("missing")),
+        Err(not_found("missing"))
+    );
+    assert_eq!(store.restore(&id), Err(not_found(&id)));
+}
+
+#[test]
+fn test_error_is_shown() {
+    let (store, [parent, _]) = store();
+    let mut main_app = setup(store);
+
+    // The task is deleted while it is being edited
+    input_char('e', &mut main_app);
+    main_app.app.task_store.delete_task(&parent).unwrap();
+    input_code(KeyCode::Enter, &mut main_app);
+    assert_eq!(main_app.overlays.len(), 1);
+
+    let mut terminal = Terminal::new(TestBackend::new(60, 20)).unwrap();
+    let overlay = &mut main_app.overlays[0];
+    overlay.update_layout(Rect::new(0, 0, 60, 20));
+    terminal
+        .draw(|f| overlay.draw(&main_app.app, &mut Drawer::new(f)))
+        .unwrap();
+    let buffer = terminal.backend().buffer();
+    let screen = buffer
+        .content()
+        .chunks(60)
+        .map(|line| line.iter().map(|cell| cell.symbol()).collect::<String>())
+        .collect::<Vec<_>>()
+        .join("\n");
+    assert!(
+        screen.contains(&format!("Could not find the task {}", &parent[..8])),
+        "{screen}"
+    );
+}
@@ -0,0 +1,258 @@
+use chrono::Local;
+use crossterm::event::KeyCode;
+use tui::style::Color;
+
+use crate::{
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+        store_event::StoreEvent,
+        todoist::todoist_command::{TodoistCommand, TodoistSendCommand},
+    },
+    task::{Tag, Task},
+    utils::test::{input_char, input_code, setup, todoist_store, todoist_store_with_sink},
+};
+
+/// The type of each command, the uuids are different each time.
+fn command_types(commands: &[TodoistSendCommand]) -> Vec<String> {
+    commands
+        .iter()
+        .map(|command| serde_json::to_value(command).unwrap()["type"].to_string())
+        .map(|command_type| command_type.trim_matches('"').to_string())
+        .collect()
+}
+
+fn tag(name: &str) -> Tag {
+    Tag {
+        name: name.to_string(),
+        colour: Color::Reset,
+    }
+}
+
+#[test]
+fn test_events() {
+    let mut store = JsonDataStore::default();
+    let events = store.subscribe();
+    let now = Local::now().naive_local();
+
+    let parent = store.add_task(Task::from_string("parent"), None).unwrap();
+    let child = store
+        .add_task(Task::from_string("child"), Some(&parent))
+        .unwrap();
+    store
+        .modify_task(&child, |task| task.title = "renamed".to_string())
+        .unwrap();
+    store.move_task(&child, None, 0, Some(())).unwrap();
+    store.complete_task(&parent, now).unwrap();
+    store.restore(&parent).unwrap();
+    store.add_comment(&child, "note".to_string(), now).unwrap();
+    let comment_id = store.task(&child).unwrap().comments[0].id.clone();
+    store
+        .update_comment(&child, &comment_id, "edited".to_string())
+        .unwrap();
+    store.set_tag("0", tag("work"));
+    store.delete_tag("0");
+    store.delete_task(&child).unwrap();
+
+    assert_eq!(
+        events.try_iter().collect::<Vec<_>>(),
+        vec![
+            StoreEvent::TaskAdded {
+                id: parent.clone(),
+                parent: None
+            },
+            StoreEvent::TaskAdded {
+                id: child.clone(),
+                parent: Some(parent.clone())
+            },
+            StoreEvent::TaskUpdated(child.clone()),
+            StoreEvent::TaskMoved {
+                id: child.clone(),
+                from: Some(parent.clone()),
+                to: None
+            },
+            StoreEvent::TaskCompleted(parent.clone()),
+            StoreEvent::TaskRestored(parent.clone()),
+            StoreEvent::CommentAdded {
+                id: child.clone(),
+                comment_id: comment_id.clone()
+            },
+            StoreEvent::CommentUpdated {
+                id: child.clone(),
+                comment_id
+            },
+            StoreEvent::TagChanged("0".to_string()),
+            StoreEvent::TagChanged("0".to_string()),
+            StoreEvent::TaskDeleted(child.clone()),
+        ]
+    );
+}
+
+#[test]
+fn test_no_event_when_failed() {
+    let mut store = JsonDataStore::default();
+    let events = store.subscribe();
+    let now = Local::now().naive_local();
+
+    store
+        .modify_task("missing", |task| task.title = "renamed".to_string())
+        .unwrap_err();
+    store.complete_task("missing", now).unwrap_err();
+    store.restore("missing").unwrap_err();
+    store.delete_task("missing").unwrap_err();
+    store
+        .update_comment("missing", "1", "edited".to_string())
+        .unwrap_err();
+    assert_eq!(events.try_iter().count(), 0);
+}
+
+#[test]
+fn test_subscribers() {
+    let mut store = JsonDataStore::default();
+    let first = store.subscribe();
+    let dropped = store.subscribe();
+    drop(dropped);
+    let second = store.subscribe();
+
+    let id = store.add_task(Task::from_string("task"), None).unwrap();
+    let added = StoreEvent::TaskAdded { id, parent: None };
+    assert_eq!(first.try_iter().collect::<Vec<_>>(), vec![added.clone()]);
+    assert_eq!(second.try_iter().collect::<Vec<_>>(), vec![added]);
+}
+
+#[test]
+fn test_actions_send_events() {
+    let mut store = JsonDataStore::default();
+    let first = store.add_task(Task::from_string("first"), None).unwrap();
+    store.add_task(Task::from_string("second"), None).unwrap();
+    let events = store.subscribe();
+    let mut main_app = setup(store);
+
+    input_char('p', &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+    input_char('J', &mut main_app);
+    let events = events.try_iter().collect::<Vec<_>>();
+    assert_eq!(
+        events,
+        vec![
+            StoreEvent::TaskUpdated(first.clone()),
+            StoreEvent::TaskUpdated(first.clone()),
+            StoreEvent::TaskMoved {
+                id: first,
+                from: None,
+                to: None
+            },
+        ]
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_todoist_commands() {
+    let (mut store, mut receiver) = todoist_store_with_sink();
+    let events = store.subscribe();
+    let mut sent = || {
+        let mut commands = Vec::new();
+        while let Ok(TodoistCommand::Send(command)) = receiver.try_recv() {
+            commands.push(command);
+        }
+        command_types(&commands)
+    };
+
+    let parent = store.add_task(Task::from_string("parent"), None).unwrap();
+    let child = store
+        .add_task(Task::from_string("child"), Some(&parent))
+        .unwrap();
+    assert_eq!(sent(), vec!["item_add", "item_add"]);
+
+    store
+        .modify_task(&child, |task| task.title = "renamed".to_string())
+        .unwrap();
+    let update = store.commands_for(&StoreEvent::TaskUpdated(child.clone()));
+    let TodoistSendCommand::Update { args, .. } = &update[0] else {
+        panic!("Expected an update");
+    };
+    assert_eq!(args.content.as_deref(), Some("renamed"));
+    assert_eq!(sent(), vec!["item_update"]);
+
+    // Changes that Todoist does not store are not sent
+    store
+        .modify_task(&child, |task| task.pomodoros += 1)
+        .unwrap();
+    assert!(sent().is_empty());
+
+    // Collapsing and tagging are kept on Todoist
+    store
+        .modify_task(&parent, |task| task.opened = false)
+        .unwrap();
+    store
+        .modify_task(&child, |task| task.tags.push("work".to_string()))
+        .unwrap();
+    assert_eq!(sent(), vec!["item_update", "item_update"]);
+    let update = store.commands_for(&StoreEvent::TaskUpdated(child.clone()));
+    let TodoistSendCommand::Update { args, .. } = &update[0] else {
+        panic!("Expected an update");
+    };
+    assert_eq!(args.labels, ["work"]);
+
+    // Moving within the same parent only reorders
+    let second = store
+        .add_task(Task::from_string("second"), Some(&parent))
+        .unwrap();
+    sent();
+    store.move_task(&second, None, 0, None).unwrap();
+    assert_eq!(sent(), vec!["item_reorder"]);
+    store.move_task(&second, None, 0, Some(())).unwrap();
+    store
+        .move_task(&second, Some(child.clone()), 0, None)
+        .unwrap();
+    assert_eq!(
+        sent(),
+        vec!["item_reorder", "item_move", "item_reorder"],
+        "There is no inbox to move to the root"
+    );
+
+    // The subtasks are uncompleted with their parent
+    let now = Local::now().naive_local();
+    store.complete_task(&parent, now).unwrap();
+    store.restore(&parent).unwrap();
+    assert_eq!(
+        sent(),
+        vec![
+            "item_complete",
+            "item_uncomplete",
+            "item_uncomplete",
+            "item_uncomplete"
+        ]
+    );
+
+    store.add_comment(&child, "note".to_string(), now).unwrap();
+    store.set_tag("work", tag("work"));
+    store.delete_task(&second).unwrap();
+    assert_eq!(sent(), vec!["note_add", "item_delete"]);
+    assert_eq!(events.try_iter().count(), 15);
+}
+
+#[test]
+fn test_todoist_restore_order() {
+    let mut store = todoist_store();
+    let parent: TaskID = "1".to_string();
+    let child: TaskID = "2".to_string();
+    store
+        .tasks
+        .insert(parent.clone(), Task::from_string("parent"));
+    store
+        .tasks
+        .insert(child.clone(), Task::from_string("child"));
+    store.root.push(parent.clone());
+    store.subtasks.insert(parent.clone(), vec![child.clone()]);
+
+    let ids = store
+        .commands_for(&StoreEvent::TaskRestored(parent.clone()))
+        .into_iter()
+        .map(|command| match command {
+            TodoistSendCommand::Uncomplete { args, .. } => args.id,
+            _ => panic!("Expected only uncompletes"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(ids, vec![parent, child]);
+}
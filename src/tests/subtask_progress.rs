@@ -0,0 +1,75 @@
+use chrono::Local;
+
+use crate::{
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+    },
+    utils::test::{input_char, setup, task_tree},
+};
+
+/// The ids are parent, first, nested and second.
+fn nested_store() -> (JsonDataStore, [TaskID; 4]) {
+    task_tree([(0, "parent"), (1, "first"), (2, "nested"), (1, "second")])
+}
+
+#[test]
+fn test_subtask_progress() {
+    let (mut json_data_store, [parent, first, nested, second]) = nested_store();
+    assert_eq!(json_data_store.subtask_progress(&parent), (0, 3));
+    assert_eq!(json_data_store.subtask_progress(&first), (0, 1));
+    assert_eq!(json_data_store.subtask_progress(&second), (0, 0));
+
+    let now = Local::now().naive_local();
+    json_data_store.complete_task(&nested, now).unwrap();
+    assert_eq!(
+        json_data_store.completed_task(&nested).unwrap().parent_id,
+        Some(first.clone())
+    );
+    assert_eq!(json_data_store.subtask_progress(&first), (1, 1));
+    assert_eq!(json_data_store.subtask_progress(&parent), (1, 3));
+
+    // Completed subtasks are still counted below a completed parent.
+    json_data_store.complete_task(&first, now).unwrap();
+    assert_eq!(json_data_store.subtask_progress(&parent), (2, 3));
+
+    json_data_store.restore(&first).unwrap();
+    assert_eq!(
+        json_data_store.subtasks(&parent).unwrap(),
+        &vec![first.clone(), second.clone()]
+    );
+    assert_eq!(json_data_store.subtask_progress(&parent), (1, 3));
+}
+
+#[test]
+fn test_auto_complete_parent() {
+    let (json_data_store, [parent, first, ..]) = nested_store();
+    let mut main_app = setup(json_data_store);
+    main_app.app.config.auto_complete_parent = true;
+
+    // Completing "nested" completes "first" as it was its only subtask.
+    input_char('j', &mut main_app);
+    input_char('j', &mut main_app);
+    input_char('c', &mut main_app);
+    assert!(main_app.app.task_store.task(&first).is_none());
+    assert_eq!(main_app.app.task_store.root_tasks(), &vec![parent]);
+
+    input_char('c', &mut main_app);
+    assert!(main_app.app.task_store.root_tasks().is_empty());
+    assert_eq!(main_app.app.task_store.completed_root_tasks().len(), 4);
+}
+
+#[test]
+fn test_no_auto_complete_parent() {
+    let (json_data_store, [_, first, nested, _]) = nested_store();
+    let mut main_app = setup(json_data_store);
+
+    input_char('j', &mut main_app);
+    input_char('j', &mut main_app);
+    input_char('c', &mut main_app);
+    assert!(main_app.app.task_store.task(&first).is_some());
+    assert_eq!(
+        main_app.app.task_store.completed_root_tasks(),
+        &vec![nested]
+    );
+}
@@ -33,8 +33,12 @@ fn test_tag_creation() {
     const TEST_TAG: &str = "WOOO TAGS!!";
 
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
-    json_data_store.add_task(Task::from_string("oof"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
+    json_data_store
+        .add_task(Task::from_string("oof"), None)
+        .unwrap();
     let mut screen_manager = setup(json_data_store);
 
     let mut tag_count = 0;
@@ -149,8 +153,12 @@ fn test_tag_cancel_and_enter() {
     const TEST_TAG: &str = "WOOO TAGS!!";
 
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
-    json_data_store.add_task(Task::from_string("oof"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
+    json_data_store
+        .add_task(Task::from_string("oof"), None)
+        .unwrap();
     let mut screen_manager = setup(json_data_store);
     add_tag(&mut screen_manager, TEST_TAG, "ewfnjaweknf");
 
@@ -170,8 +178,12 @@ fn test_tag_removal() {
     const TEST_TAG: &str = "WOOO TAGS!!";
 
     let mut json_data_store = JsonDataStore::default();
-    json_data_store.add_task(Task::from_string("meme"), None);
-    json_data_store.add_task(Task::from_string("oof"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
+    json_data_store
+        .add_task(Task::from_string("oof"), None)
+        .unwrap();
     let mut screen_manager = setup(json_data_store);
     add_tag(&mut screen_manager, TEST_TAG, "1");
     assert_eq!(screen_manager.app.task_store.tags().len(), 1);
@@ -200,8 +212,12 @@ fn test_flip_tag() {
         tags,
         ..Default::default()
     };
-    json_data_store.add_task(Task::from_string("meme"), None);
-    json_data_store.add_task(Task::from_string("oof"), None);
+    json_data_store
+        .add_task(Task::from_string("meme"), None)
+        .unwrap();
+    json_data_store
+        .add_task(Task::from_string("oof"), None)
+        .unwrap();
     let mut screen_manager = setup(json_data_store);
 
     input_char('t', &mut screen_manager);
@@ -0,0 +1,147 @@
+use crate::{
+    cli::{self, Command},
+    data::{data_store::DataTaskStore, json_data_store::JsonDataStore},
+    error::AppError,
+    storage::json::migration,
+    task::{Priority, Task},
+    utils::test::{at, date},
+};
+
+/// A parent with a subtask, and a task sharing the start of the parent's id.
+fn store() -> JsonDataStore {
+    let mut store = JsonDataStore::default();
+    for (id, title) in [
+        ("3f2a9c1d-0000", "Parent\nwith more detail"),
+        ("3f2a9c7e-0000", "Sibling"),
+        ("b41d0e22-0000", "Child"),
+    ] {
+        store.tasks.insert(
+            id.to_string(),
+            Task {
+                created_at: Some(at(date(2024, 10, 1), 9)),
+                updated_at: Some(at(date(2024, 10, 2), 9)),
+                ..Task::from_string(title)
+            },
+        );
+    }
+    store.root = vec!["3f2a9c1d-0000".to_string(), "3f2a9c7e-0000".to_string()];
+    store.subtasks.insert(
+        "3f2a9c1d-0000".to_string(),
+        vec!["b41d0e22-0000".to_string()],
+    );
+    store
+}
+
+#[test]
+fn test_new_ids() {
+    let mut store = JsonDataStore::default();
+    let first = store.add_task(Task::from_string("first"), None).unwrap();
+    let second = store
+        .add_task(Task::from_string("second"), Some(&first))
+        .unwrap();
+
+    assert!(uuid::Uuid::parse_str(&first).is_ok());
+    assert_ne!(first, second);
+    assert_eq!(store.subtasks(&first).unwrap(), &vec![second]);
+
+    // Deleting a task never lets its id be given out again
+    store.delete_task(&first).unwrap();
+    let third = store.add_task(Task::from_string("third"), None).unwrap();
+    assert_ne!(first, third);
+}
+
+#[test]
+fn test_short_ids() {
+    let short_ids = cli::short_ids(&["3f2a9c1d-0000", "3f2a9c7e-0000", "b41d0e22-0000", "12"]);
+    assert_eq!(short_ids["3f2a9c1d-0000"], "3f2a9c1");
+    assert_eq!(short_ids["3f2a9c7e-0000"], "3f2a9c7");
+    assert_eq!(short_ids["b41d0e22-0000"], "b41d0e2");
+    // Ids from before UUIDs are shown whole
+    assert_eq!(short_ids["12"], "12");
+
+    // Longer when the ids share more than the minimum
+    let short_ids = cli::short_ids(&["aaaaaaaa1", "aaaaaaaa2"]);
+    assert_eq!(short_ids["aaaaaaaa1"], "aaaaaaaa1");
+
+    // Edited ids can contain characters longer than a byte
+    let short_ids = cli::short_ids(&["tâche-é1", "tâche-é2", "ééééééééé"]);
+    assert_eq!(short_ids["tâche-é1"], "tâche-é1");
+    assert_eq!(short_ids["ééééééééé"], "ééééééé");
+}
+
+#[test]
+fn test_resolve_id() {
+    let store = store();
+    assert_eq!(cli::resolve_id(&store, "b4").unwrap(), "b41d0e22-0000");
+    assert_eq!(
+        cli::resolve_id(&store, "3f2a9c1d-0000").unwrap(),
+        "3f2a9c1d-0000"
+    );
+    assert!(matches!(
+        cli::resolve_id(&store, "3f2a"),
+        Err(AppError::InvalidArgument(message))
+            if message == "the id 3f2a could be 3f2a9c1d-0000, 3f2a9c7e-0000"
+    ));
+    assert!(matches!(
+        cli::resolve_id(&store, "ff"),
+        Err(AppError::InvalidArgument(message)) if message == "no task has the id ff"
+    ));
+}
+
+#[test]
+fn test_list_and_show() {
+    assert_eq!(
+        cli::parse_args(["list".to_string()]).unwrap(),
+        Some(Command::List)
+    );
+    assert_eq!(
+        cli::parse_args(["show".to_string(), "3f2a".to_string()]).unwrap(),
+        Some(Command::Show {
+            id: "3f2a".to_string()
+        })
+    );
+    assert!(cli::parse_args(["show".to_string()]).is_err());
+
+    let mut store = store();
+    assert_eq!(
+        cli::list(&store),
+        "3f2a9c1  Parent
+b41d0e2    Child
+3f2a9c7  Sibling
+"
+    );
+    assert_eq!(
+        cli::list(&JsonDataStore::default()),
+        "There are no open tasks.\n"
+    );
+
+    store
+        .modify_task("b41d0e22-0000", |task| task.priority = Priority::High)
+        .unwrap();
+    store
+        .complete_task("b41d0e22-0000", at(date(2024, 10, 3), 9))
+        .unwrap();
+    assert_eq!(
+        cli::show(&store, "b41").unwrap(),
+        "Child
+
+Id         b41d0e22-0000
+Priority   High
+Created    2024-10-01 09:00
+Updated    2024-10-03 09:00
+Completed  2024-10-03 09:00
+"
+    );
+}
+
+#[test]
+fn test_migrate_v2_ids() {
+    let store =
+        migration::load(include_str!("fixtures/v2.json"), at(date(2024, 10, 10), 9)).unwrap();
+    // The ids that were counted up are kept
+    assert_eq!(store.root, vec!["1"]);
+    assert_eq!(store.task("1").unwrap().title, "Task");
+
+    let value = serde_json::to_value(&store).unwrap();
+    assert!(value.get("task_count").is_none());
+}
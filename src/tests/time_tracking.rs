@@ -0,0 +1,160 @@
+use chrono::{Duration, NaiveDate, NaiveDateTime};
+
+use crate::{
+    cli::{self, Command},
+    data::{data_store::DataTaskStore, json_data_store::JsonDataStore},
+    task::{CompletedTask, Task, TimeEntry},
+    utils::test::{get_task_from_pos, input_char, setup},
+};
+
+fn time(day: u32, hour: u32, minute: u32) -> NaiveDateTime {
+    NaiveDate::from_ymd_opt(2024, 5, day)
+        .unwrap()
+        .and_hms_opt(hour, minute, 0)
+        .unwrap()
+}
+
+fn entry(start: NaiveDateTime, end: NaiveDateTime) -> TimeEntry {
+    TimeEntry {
+        start,
+        end: Some(end),
+    }
+}
+
+#[test]
+fn test_toggle_timer() {
+    let mut task_store = JsonDataStore::default();
+    let first = task_store
+        .add_task(Task::from_string("first"), None)
+        .unwrap();
+    let second = task_store
+        .add_task(Task::from_string("second"), None)
+        .unwrap();
+    let mut main_app = setup(task_store);
+
+    input_char('T', &mut main_app);
+    assert_eq!(
+        main_app.app.running_timer().as_deref(),
+        Some(first.as_str())
+    );
+
+    // Starting another timer stops the first
+    input_char('j', &mut main_app);
+    input_char('T', &mut main_app);
+    assert_eq!(
+        main_app.app.running_timer().as_deref(),
+        Some(second.as_str())
+    );
+    let first = get_task_from_pos(&main_app.app.task_store, 0);
+    assert_eq!(first.time_entries.len(), 1);
+    assert!(first.running_timer().is_none());
+
+    input_char('T', &mut main_app);
+    assert_eq!(main_app.app.running_timer(), None);
+    assert_eq!(
+        get_task_from_pos(&main_app.app.task_store, 1)
+            .time_entries
+            .len(),
+        1
+    );
+}
+
+#[test]
+fn test_complete_stops_timer() {
+    let mut task_store = JsonDataStore::default();
+    let id = task_store
+        .add_task(Task::from_string("task"), None)
+        .unwrap();
+    let mut main_app = setup(task_store);
+
+    input_char('T', &mut main_app);
+    input_char('c', &mut main_app);
+    let completed_task = main_app.app.task_store.completed_task(&id).unwrap();
+    assert!(completed_task.task.running_timer().is_none());
+}
+
+#[test]
+fn test_tracked_time_by_day() {
+    let mut task = Task::from_string("task");
+    task.time_entries = vec![
+        entry(time(1, 9, 0), time(1, 10, 30)),
+        // Crosses midnight
+        entry(time(1, 23, 0), time(2, 1, 0)),
+        TimeEntry {
+            start: time(3, 9, 0),
+            end: None,
+        },
+    ];
+    let now = time(3, 9, 45);
+
+    assert_eq!(task.tracked_time(now), Duration::minutes(90 + 120 + 45));
+    let days = task.tracked_time_by_day(now, time(1, 0, 0).date(), time(2, 0, 0).date());
+    assert_eq!(
+        days.into_iter().collect::<Vec<_>>(),
+        vec![
+            (time(1, 0, 0).date(), Duration::minutes(150)),
+            (time(2, 0, 0).date(), Duration::minutes(60)),
+        ]
+    );
+}
+
+#[test]
+fn test_report() {
+    let mut task_store = JsonDataStore::default();
+    let mut task = Task::from_string("Write report\nfor the client");
+    task.time_entries = vec![entry(time(1, 9, 0), time(1, 10, 30))];
+    task_store.add_task(task, None).unwrap();
+    task_store
+        .add_task(Task::from_string("No time"), None)
+        .unwrap();
+
+    let mut done = Task::from_string("Call vendor");
+    done.time_entries = vec![
+        entry(time(2, 9, 0), time(2, 9, 20)),
+        // Outside of the range
+        entry(time(20, 9, 0), time(20, 10, 0)),
+    ];
+    task_store.completed_root.push("done".to_string());
+    task_store.completed_tasks.insert(
+        "done".to_string(),
+        CompletedTask::from_task(done, time(20, 10, 0)),
+    );
+
+    let report = cli::report(
+        &task_store,
+        time(1, 0, 0).date(),
+        time(10, 0, 0).date(),
+        time(21, 0, 0),
+    );
+    assert_eq!(
+        report,
+        "Time tracked from 2024-05-01 to 2024-05-10
+
+   1h 30m  Write report
+      20m  Call vendor
+
+   1h 30m  Wed 2024-05-01
+      20m  Thu 2024-05-02
+
+   1h 50m  Total (1.83 hours)
+"
+    );
+}
+
+#[test]
+fn test_parse_args() {
+    let args = |args: &[&str]| cli::parse_args(args.iter().map(|f| f.to_string()));
+
+    assert_eq!(args(&[]).unwrap(), None);
+    assert_eq!(
+        args(&["report", "--from", "2024-05-01", "--to", "2024-05-10"]).unwrap(),
+        Some(Command::Report {
+            from: time(1, 0, 0).date(),
+            to: time(10, 0, 0).date(),
+        })
+    );
+    assert!(args(&["report", "--from"]).is_err());
+    assert!(args(&["report", "--from", "yesterday"]).is_err());
+    assert!(args(&["report", "--from", "2024-05-10", "--to", "2024-05-01"]).is_err());
+    assert!(args(&["unknown"]).is_err());
+}
@@ -0,0 +1,122 @@
+use chrono::Local;
+
+use crate::{
+    data::{
+        data_store::DataTaskStore, json_data_store::JsonDataStore,
+        todoist::todoist_task::TodoistItem,
+    },
+    task::Task,
+    utils::test::{at, date, input_char, setup},
+};
+
+#[test]
+fn test_add_and_modify() {
+    let before = Local::now().naive_local();
+    let mut json_data_store = JsonDataStore::default();
+    let imported_id = json_data_store
+        .add_task(
+            Task {
+                created_at: Some(at(date(2024, 10, 1), 9)),
+                ..Task::from_string("imported")
+            },
+            None,
+        )
+        .unwrap();
+    let new_id = json_data_store
+        .add_task(Task::from_string("new"), None)
+        .unwrap();
+
+    let imported = json_data_store.task(&imported_id).unwrap();
+    assert_eq!(imported.created_at, Some(at(date(2024, 10, 1), 9)));
+    assert!(imported.updated_at.unwrap() >= before);
+
+    let new = json_data_store.task(&new_id).unwrap();
+    assert!(new.created_at.unwrap() >= before);
+    assert_eq!(new.created_at, new.updated_at);
+
+    json_data_store
+        .modify_task(&imported_id, |task| {
+            task.updated_at = Some(at(date(2024, 10, 2), 9))
+        })
+        .unwrap();
+    json_data_store
+        .add_comment(
+            &imported_id,
+            "comment".to_string(),
+            at(date(2024, 10, 3), 9),
+        )
+        .unwrap();
+    let imported = json_data_store.task(&imported_id).unwrap();
+    assert_eq!(imported.created_at, Some(at(date(2024, 10, 1), 9)));
+    assert!(imported.updated_at.unwrap() >= before);
+}
+
+#[test]
+fn test_complete_and_move() {
+    let mut json_data_store = JsonDataStore::default();
+    let first = json_data_store
+        .add_task(Task::from_string("first"), None)
+        .unwrap();
+    let second = json_data_store
+        .add_task(Task::from_string("second"), None)
+        .unwrap();
+    json_data_store.tasks.get_mut(&second).unwrap().updated_at = None;
+
+    json_data_store.move_task(&second, None, 0, None).unwrap();
+    assert!(json_data_store.task(&second).unwrap().updated_at.is_some());
+
+    json_data_store
+        .complete_task(&first, at(date(2024, 10, 4), 9))
+        .unwrap();
+    assert_eq!(
+        json_data_store
+            .completed_task(&first)
+            .unwrap()
+            .task
+            .updated_at,
+        Some(at(date(2024, 10, 4), 9))
+    );
+}
+
+#[test]
+fn test_edit_through_ui() {
+    let mut json_data_store = JsonDataStore::default();
+    let id = json_data_store
+        .add_task(Task::from_string("task"), None)
+        .unwrap();
+    json_data_store.tasks.get_mut(&id).unwrap().updated_at = None;
+    let mut screen_manager = setup(json_data_store);
+
+    // Cycles the priority
+    input_char('p', &mut screen_manager);
+    assert!(screen_manager
+        .app
+        .task_store
+        .task(&id)
+        .unwrap()
+        .updated_at
+        .is_some());
+}
+
+#[test]
+fn test_todoist_timestamps() {
+    let item: TodoistItem = serde_json::from_str(
+        r#"{
+            "id": "1",
+            "content": "task",
+            "parent_id": null,
+            "child_order": 0,
+            "description": "",
+            "is_collapsed": false,
+            "priority": 1,
+            "due": null,
+            "completed_at": null,
+            "added_at": "2024-10-01T09:00:00Z",
+            "updated_at": "2024-10-02T09:00:00Z"
+        }"#,
+    )
+    .unwrap();
+    let task: Task = item.into();
+    assert_eq!(task.created_at, Some(at(date(2024, 10, 1), 9)));
+    assert_eq!(task.updated_at, Some(at(date(2024, 10, 2), 9)));
+}
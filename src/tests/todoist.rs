@@ -0,0 +1,398 @@
+use std::collections::HashMap;
+
+use crate::{
+    data::{
+        data_store::DataTaskStore,
+        todoist::{
+            todoist_command::{CompletedPageRequest, TodoistCommand},
+            todoist_data_store::TodoistDataStore,
+            todoist_main::{handle_sync, TaskSync},
+            todoist_response::{TodoistGetAllCompletedItemResponse, TodoistSync},
+        },
+    },
+    task::{CompletedTask, Priority},
+    utils::test::{date, todoist_store, todoist_store_with_sink},
+};
+
+fn sync_items(data_store: &mut TodoistDataStore, items: &str) {
+    let sync: TodoistSync =
+        serde_json::from_str(&format!(r#"{{"sync_token": "token", "items": {}}}"#, items)).unwrap();
+    handle_sync(data_store, TaskSync::Sync(sync, HashMap::new()));
+}
+
+fn item(id: &str, content: &str, parent_id: Option<&str>, child_order: usize) -> String {
+    format!(
+        r#"{{"id": "{}", "content": "{}", "parent_id": {}, "child_order": {},
+            "description": "", "is_collapsed": false, "priority": 1, "due": null}}"#,
+        id,
+        content,
+        parent_id.map_or("null".to_string(), |p| format!("\"{}\"", p)),
+        child_order
+    )
+}
+
+fn initial_store() -> TodoistDataStore {
+    let mut data_store = todoist_store();
+    sync_items(
+        &mut data_store,
+        &format!(
+            "[{}, {}, {}]",
+            item("1", "first", None, 0),
+            item("2", "second", None, 1),
+            item("3", "child", Some("1"), 0)
+        ),
+    );
+    data_store
+}
+
+#[test]
+fn test_sync_new_tasks() {
+    let data_store = initial_store();
+    assert_eq!(data_store.root_tasks(), &vec!["1", "2"]);
+    assert_eq!(data_store.subtasks("1"), Some(&vec!["3".to_string()]));
+}
+
+#[test]
+fn test_sync_remote_delete() {
+    let mut data_store = initial_store();
+    sync_items(
+        &mut data_store,
+        r#"[{"id": "3", "content": "child", "parent_id": "1", "child_order": 0,
+             "description": "", "is_collapsed": false, "priority": 1, "due": null,
+             "is_deleted": true}]"#,
+    );
+    assert!(data_store.task("3").is_none());
+    assert_eq!(data_store.subtasks("1"), Some(&vec![]));
+    assert_eq!(data_store.root_tasks().len(), 2);
+}
+
+#[test]
+fn test_sync_remote_completion() {
+    let mut data_store = initial_store();
+    sync_items(
+        &mut data_store,
+        r#"[{"id": "2", "content": "second", "parent_id": null, "child_order": 1,
+             "description": "", "is_collapsed": false, "priority": 1, "due": null,
+             "completed_at": "2024-05-01T10:00:00Z"}]"#,
+    );
+    assert!(data_store.task("2").is_none());
+    assert_eq!(data_store.root_tasks(), &vec!["1"]);
+    assert_eq!(data_store.completed_root_tasks(), &vec!["2"]);
+    assert!(data_store.completed_task("2").is_some());
+
+    // Uncompleted remotely
+    sync_items(
+        &mut data_store,
+        &format!("[{}]", item("2", "second", None, 1)),
+    );
+    assert!(data_store.completed_task("2").is_none());
+    assert!(data_store.completed_root_tasks().is_empty());
+    assert_eq!(data_store.root_tasks(), &vec!["1", "2"]);
+}
+
+#[test]
+fn test_sync_parent_change() {
+    let mut data_store = initial_store();
+    data_store
+        .modify_task("3", |task| task.progress = true)
+        .unwrap();
+    sync_items(
+        &mut data_store,
+        &format!("[{}]", item("3", "moved", Some("2"), 0)),
+    );
+
+    assert_eq!(data_store.subtasks("1"), Some(&vec![]));
+    assert_eq!(data_store.subtasks("2"), Some(&vec!["3".to_string()]));
+    let task = data_store.task("3").unwrap();
+    assert_eq!(task.title, "moved\n");
+    // Local only state is kept
+    assert!(task.progress);
+}
+
+fn completed_item(id: &str, parent_id: Option<&str>, completed_at: &str) -> String {
+    format!(
+        r#"{{"id": "{}", "parent_id": {}, "content": "done {}", "description": "",
+            "priority": 4, "due": {{"date": "2024-06-01"}}, "labels": ["work"],
+            "completed_at": "{}"}}"#,
+        id,
+        parent_id.map_or("null".to_string(), |p| format!("\"{}\"", p)),
+        id,
+        completed_at
+    )
+}
+
+fn completed_page(
+    data_store: &mut TodoistDataStore,
+    request: CompletedPageRequest,
+    items: &[String],
+    next_cursor: Option<&str>,
+) {
+    let response: TodoistGetAllCompletedItemResponse = serde_json::from_str(&format!(
+        r#"{{"items": [{}], "next_cursor": {}}}"#,
+        items.join(","),
+        next_cursor.map_or("null".to_string(), |p| format!("\"{}\"", p)),
+    ))
+    .unwrap();
+    handle_sync(data_store, TaskSync::CompletedPage(request, response));
+}
+
+#[test]
+fn test_completed_page_request_next() {
+    let request = CompletedPageRequest::ending_at(date(2024, 6, 1));
+    assert_eq!(request.since, date(2024, 3, 1));
+
+    // More tasks in the same period
+    let next = request.next(Some("cursor".to_string()), None).unwrap();
+    assert_eq!(next.since, request.since);
+    assert_eq!(next.cursor.as_deref(), Some("cursor"));
+
+    // The previous period
+    let previous = next.next(None, None).unwrap();
+    assert_eq!(previous.until, date(2024, 3, 1));
+    assert_eq!(previous.cursor, None);
+
+    // Stops once the period contains when the user joined
+    assert_eq!(request.next(None, Some(date(2024, 4, 1))), None);
+    assert!(request.next(None, Some(date(2023, 4, 1))).is_some());
+
+    // Keeps going past empty periods when it is unknown when the user joined
+    let old = CompletedPageRequest::ending_at(date(2010, 6, 1));
+    assert!(old.next(None, None).is_some());
+    let first = CompletedPageRequest::ending_at(date(2007, 3, 1));
+    assert_eq!(first.next(None, None), None);
+}
+
+#[test]
+fn test_invalid_completion_date_is_skipped() {
+    let mut data_store = initial_store();
+    completed_page(
+        &mut data_store,
+        CompletedPageRequest::ending_at(date(2024, 6, 1)),
+        &[
+            completed_item("5", None, "not a date"),
+            completed_item("6", None, "2024-05-01T10:00:00Z"),
+        ],
+        None,
+    );
+    assert_eq!(data_store.completed_root_tasks(), &vec!["6"]);
+    assert!(data_store.completed_task("5").is_none());
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_empty_page_loads_the_next() {
+    let (mut data_store, mut commands) = todoist_store_with_sink();
+    let request = CompletedPageRequest::ending_at(date(2024, 6, 1));
+    data_store.completed_history = Some(request.clone());
+    data_store.loading_completed_history = true;
+
+    completed_page(&mut data_store, request, &[], None);
+    let next = CompletedPageRequest::ending_at(date(2024, 3, 1));
+    assert_eq!(data_store.completed_history, Some(next.clone()));
+    assert!(data_store.loading_completed_history);
+    assert!(commands.try_recv() == Ok(TodoistCommand::LoadCompleted(next)));
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_empty_pages_stop_loading() {
+    let (mut data_store, mut commands) = todoist_store_with_sink();
+    let mut request = CompletedPageRequest::ending_at(date(2024, 6, 1));
+    data_store.completed_history = Some(request.clone());
+
+    for _ in 0..3 {
+        completed_page(&mut data_store, request, &[], None);
+        let Ok(TodoistCommand::LoadCompleted(next)) = commands.try_recv() else {
+            panic!("the next page should be loaded");
+        };
+        request = next;
+    }
+    // Waits for the user to scroll before loading further back
+    completed_page(&mut data_store, request, &[], None);
+    assert!(commands.try_recv().is_err());
+    assert!(!data_store.loading_completed_history);
+    assert_eq!(
+        data_store.completed_history,
+        Some(CompletedPageRequest::ending_at(date(2023, 6, 1)))
+    );
+
+    // A page with tasks in it starts counting again
+    let request = data_store.completed_history.clone().unwrap();
+    completed_page(
+        &mut data_store,
+        request,
+        &[completed_item("12", None, "2023-05-10T10:00:00Z")],
+        None,
+    );
+    assert_eq!(data_store.empty_completed_pages, 0);
+    assert!(commands.try_recv().is_err());
+}
+
+#[test]
+fn test_completed_item_fidelity() {
+    let completed_task: CompletedTask =
+        serde_json::from_str::<crate::data::todoist::todoist_task::TodoistCompletedItem>(
+            &completed_item("5", Some("1"), "2024-05-01T10:00:00Z"),
+        )
+        .unwrap()
+        .into_completed_task()
+        .unwrap();
+
+    assert_eq!(completed_task.task.priority, Priority::High);
+    assert_eq!(completed_task.task.tags, vec!["work"]);
+    assert_eq!(completed_task.task.due_date, Some(date(2024, 6, 1)));
+    assert_eq!(completed_task.parent_id.as_deref(), Some("1"));
+}
+
+#[test]
+fn test_completed_history_pages() {
+    let mut data_store = initial_store();
+    let request = CompletedPageRequest::ending_at(date(2024, 6, 1));
+    data_store.completed_history = Some(request.clone());
+    data_store.loading_completed_history = true;
+    data_store.completed_root.push("10".to_string());
+    data_store.completed_tasks.insert(
+        "10".to_string(),
+        serde_json::from_str::<crate::data::todoist::todoist_task::TodoistCompletedItem>(
+            &completed_item("10", None, "2024-05-20T10:00:00Z"),
+        )
+        .unwrap()
+        .into_completed_task()
+        .unwrap(),
+    );
+
+    completed_page(
+        &mut data_store,
+        request,
+        &[
+            completed_item("12", None, "2024-05-10T10:00:00Z"),
+            completed_item("11", None, "2024-04-10T10:00:00Z"),
+            // Already loaded
+            completed_item("10", None, "2024-05-20T10:00:00Z"),
+        ],
+        Some("next"),
+    );
+
+    // Older tasks go before the loaded ones, sorted by when they were completed
+    assert_eq!(data_store.completed_root_tasks(), &vec!["11", "12", "10"]);
+    assert!(!data_store.loading_completed_history);
+    assert_eq!(
+        data_store
+            .completed_history
+            .as_ref()
+            .and_then(|f| f.cursor.as_deref()),
+        Some("next")
+    );
+    assert!(data_store.tags().contains_key("work"));
+
+    let request = data_store.completed_history.clone().unwrap();
+    completed_page(&mut data_store, request, &[], None);
+    assert_eq!(
+        data_store.completed_history,
+        Some(CompletedPageRequest::ending_at(date(2024, 3, 1)))
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_restore_to_parent() {
+    let mut data_store = initial_store();
+    completed_page(
+        &mut data_store,
+        CompletedPageRequest::ending_at(date(2024, 6, 1)),
+        &[
+            completed_item("5", Some("1"), "2024-05-01T10:00:00Z"),
+            completed_item("6", Some("missing"), "2024-05-01T10:00:00Z"),
+        ],
+        None,
+    );
+
+    data_store.restore("5").unwrap();
+    assert_eq!(
+        data_store.subtasks("1"),
+        Some(&vec!["3".to_string(), "5".to_string()])
+    );
+    let task = data_store.task("5").unwrap();
+    assert_eq!(task.priority, Priority::High);
+    assert_eq!(task.tags, vec!["work"]);
+
+    // The parent no longer exists
+    data_store.restore("6").unwrap();
+    assert_eq!(data_store.root_tasks(), &vec!["1", "2", "6"]);
+
+    // Completing a subtask remembers the parent
+    data_store
+        .complete_task("3", date(2024, 6, 1).and_hms_opt(0, 0, 0).unwrap())
+        .unwrap();
+    assert_eq!(
+        data_store.completed_task("3").unwrap().parent_id.as_deref(),
+        Some("1")
+    );
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_complete_parent_keeps_subtree() {
+    let mut data_store = initial_store();
+    data_store
+        .complete_task("1", date(2024, 6, 1).and_hms_opt(0, 0, 0).unwrap())
+        .unwrap();
+    assert_eq!(data_store.root_tasks(), &vec!["2"]);
+    assert_eq!(data_store.completed_root_tasks(), &vec!["1"]);
+    assert_eq!(
+        data_store.completed_subtasks("1"),
+        Some(&vec!["3".to_string()])
+    );
+
+    // Todoist also reports the subtask as completed
+    sync_items(
+        &mut data_store,
+        r#"[{"id": "3", "content": "child", "parent_id": "1", "child_order": 0,
+             "description": "", "is_collapsed": false, "priority": 1, "due": null,
+             "completed_at": "2024-06-01T00:00:00Z"}]"#,
+    );
+    assert_eq!(data_store.completed_root_tasks(), &vec!["1"]);
+
+    data_store.restore("1").unwrap();
+    assert_eq!(data_store.root_tasks(), &vec!["1", "2"]);
+    assert_eq!(data_store.subtasks("1"), Some(&vec!["3".to_string()]));
+    assert!(data_store.completed_tasks.is_empty());
+}
+
+#[test]
+fn test_sync_comments() {
+    let mut data_store = initial_store();
+    data_store
+        .tasks
+        .get_mut("1")
+        .unwrap()
+        .comments
+        .push(crate::task::Comment {
+            id: "temp".to_string(),
+            content: "called vendor".to_string(),
+            posted_at: date(2024, 5, 1).and_hms_opt(9, 0, 0).unwrap(),
+        });
+
+    let sync: TodoistSync = serde_json::from_str(
+        r#"{"sync_token": "token", "notes": [
+            {"id": "n2", "item_id": "1", "content": "vendor replied",
+             "posted_at": "2024-05-02T10:00:00Z"},
+            {"id": "n3", "item_id": "3", "content": "removed",
+             "posted_at": "2024-05-02T10:00:00Z", "is_deleted": true}
+        ]}"#,
+    )
+    .unwrap();
+    handle_sync(
+        &mut data_store,
+        TaskSync::Sync(
+            sync,
+            HashMap::from([("temp".to_string(), "n1".to_string())]),
+        ),
+    );
+
+    let comments = &data_store.task("1").unwrap().comments;
+    assert_eq!(
+        comments
+            .iter()
+            .map(|f| (f.id.as_str(), f.content.as_str()))
+            .collect::<Vec<_>>(),
+        vec![("n1", "called vendor"), ("n2", "vendor replied")]
+    );
+    assert!(data_store.task("3").unwrap().comments.is_empty());
+}
@@ -0,0 +1,296 @@
+use chrono::Local;
+
+use crate::{
+    data::{
+        data_store::{DataTaskStore, TaskID, TaskIDRef},
+        json_data_store::JsonDataStore,
+        todoist::todoist_data_store::TodoistDataStore,
+    },
+    error::StoreError,
+    task::Task,
+    utils::test::todoist_store,
+};
+
+const STEPS: usize = 300;
+
+/// A xorshift generator, so a failing sequence can be replayed from its seed.
+/// Tasks are picked in the order they were added rather than by their random
+/// ids, so the same seed makes the same changes.
+struct Rng(u64);
+
+impl Rng {
+    fn new(seed: u64) -> Rng {
+        Rng(seed.wrapping_mul(0x9E37_79B9_7F4A_7C15) | 1)
+    }
+
+    fn next(&mut self) -> u64 {
+        self.0 ^= self.0 << 13;
+        self.0 ^= self.0 >> 7;
+        self.0 ^= self.0 << 17;
+        self.0
+    }
+
+    fn below(&mut self, n: usize) -> usize {
+        (self.next() % n as u64) as usize
+    }
+
+    fn pick<T: Clone>(&mut self, items: &[T]) -> Option<T> {
+        (!items.is_empty()).then(|| items[self.below(items.len())].clone())
+    }
+}
+
+/// The ids of the tasks a store holds, which the trait does not list.
+trait TaskIds: DataTaskStore {
+    fn open_ids(&self) -> Vec<TaskID>;
+
+    fn completed_ids(&self) -> Vec<TaskID>;
+}
+
+impl TaskIds for JsonDataStore {
+    fn open_ids(&self) -> Vec<TaskID> {
+        self.tasks.keys().cloned().collect()
+    }
+
+    fn completed_ids(&self) -> Vec<TaskID> {
+        self.completed_tasks.keys().cloned().collect()
+    }
+}
+
+impl TaskIds for TodoistDataStore {
+    fn open_ids(&self) -> Vec<TaskID> {
+        self.tasks.keys().cloned().collect()
+    }
+
+    fn completed_ids(&self) -> Vec<TaskID> {
+        self.completed_tasks.keys().cloned().collect()
+    }
+}
+
+/// Each task below the roots with its parent, in the order they are listed.
+/// Stops after the limit, in case the lists have a cycle.
+fn walk(
+    roots: &[TaskID],
+    subtasks: impl Fn(&str) -> Option<Vec<TaskID>>,
+    limit: usize,
+) -> Vec<(TaskID, Option<TaskID>)> {
+    let mut found = Vec::new();
+    let mut to_visit = roots
+        .iter()
+        .rev()
+        .map(|id| (id.clone(), None))
+        .collect::<Vec<_>>();
+    while let Some((id, parent)) = to_visit.pop() {
+        if found.len() > limit {
+            break;
+        }
+        if let Some(subtasks) = subtasks(&id) {
+            to_visit.extend(
+                subtasks
+                    .into_iter()
+                    .rev()
+                    .map(|subtask| (subtask, Some(id.clone()))),
+            );
+        }
+        found.push((id, parent));
+    }
+    found
+}
+
+fn tree<T: TaskIds>(store: &T) -> Vec<(TaskID, Option<TaskID>)> {
+    walk(
+        store.root_tasks(),
+        |id| store.subtasks(id).cloned(),
+        store.open_ids().len(),
+    )
+}
+
+fn completed_tree<T: TaskIds>(store: &T) -> Vec<(TaskID, Option<TaskID>)> {
+    walk(
+        store.completed_root_tasks(),
+        |id| store.completed_subtasks(id).cloned(),
+        store.completed_ids().len(),
+    )
+}
+
+fn sorted(ids: impl Iterator<Item = TaskID>) -> Vec<TaskID> {
+    let mut ids = ids.collect::<Vec<_>>();
+    ids.sort();
+    ids
+}
+
+/// Every task is reachable exactly once and is found below the parent it is
+/// listed under, the same goes for the completed tasks.
+fn check_tree<T: TaskIds>(store: &T, context: &str) {
+    let tree = tree(store);
+    assert_eq!(
+        sorted(tree.iter().map(|(id, _)| id.clone())),
+        sorted(store.open_ids().into_iter()),
+        "{context}: the open tasks are not each reachable once"
+    );
+    for (id, parent) in &tree {
+        let found = store.find_parent(id).unwrap();
+        assert_eq!(&found.parent_id, parent, "{context}: parent of {id}");
+        let list = match parent {
+            Some(parent) => store.subtasks(parent).unwrap(),
+            None => store.root_tasks(),
+        };
+        assert_eq!(
+            &list[found.task_local_offset], id,
+            "{context}: offset of {id}"
+        );
+    }
+    assert_eq!(
+        sorted(completed_tree(store).into_iter().map(|(id, _)| id)),
+        sorted(store.completed_ids().into_iter()),
+        "{context}: the completed tasks are not each reachable once"
+    );
+    assert_eq!(store.find_tasks_draw_size(), store.visible_rows().len());
+}
+
+fn is_below<T: TaskIds>(store: &T, id: TaskIDRef, ancestor: TaskIDRef) -> bool {
+    walk(
+        &[ancestor.to_string()],
+        |id| store.subtasks(id).cloned(),
+        store.open_ids().len(),
+    )
+    .iter()
+    .any(|(found, _)| found == id)
+}
+
+/// Applies a random sequence of changes, checking the tree after each one.
+fn run<T: TaskIds>(store: &mut T, seed: u64) {
+    let mut rng = Rng::new(seed);
+    let now = Local::now().naive_local();
+    let mut added: Vec<TaskID> = Vec::new();
+    for step in 0..STEPS {
+        let open_ids = store.open_ids();
+        let open = added
+            .iter()
+            .filter(|id| open_ids.contains(id))
+            .cloned()
+            .collect::<Vec<_>>();
+        let context = format!("seed {seed}, step {step}");
+        match rng.below(10) {
+            0..=2 => {
+                let parent = rng.pick(&open).filter(|_| rng.below(3) != 0);
+                let id = store
+                    .add_task(Task::from_string(format!("task {step}")), parent.as_deref())
+                    .unwrap();
+                added.push(id);
+            }
+            3..=5 => {
+                let Some(id) = rng.pick(&open) else {
+                    continue;
+                };
+                let parent = rng.pick(&open).filter(|_| rng.below(4) != 0);
+                let order = rng.below(8);
+                let global = (rng.below(2) == 0).then_some(());
+                let before = tree(store);
+                let result = store.move_task(&id, parent.clone(), order, global);
+                if parent
+                    .as_ref()
+                    .is_some_and(|parent| is_below(store, parent, &id))
+                {
+                    assert_eq!(result, Err(StoreError::CycleDetected(id)), "{context}");
+                    assert_eq!(
+                        tree(store),
+                        before,
+                        "{context}: a failed move changed the tree"
+                    );
+                } else {
+                    assert_eq!(result, Ok(()), "{context}");
+                }
+            }
+            6 => {
+                if let Some(id) = rng.pick(&open) {
+                    store.delete_task(&id).unwrap();
+                }
+            }
+            7 | 8 => {
+                if let Some(id) = rng.pick(&open) {
+                    store.complete_task(&id, now).unwrap();
+                }
+            }
+            _ => {
+                if let Some(id) = rng.pick(store.completed_root_tasks()) {
+                    store.restore(&id).unwrap();
+                }
+            }
+        }
+        check_tree(store, &context);
+    }
+}
+
+#[test]
+fn test_json_tree_properties() {
+    for seed in 0..20 {
+        run(&mut JsonDataStore::default(), seed);
+    }
+}
+
+#[tokio::test(flavor = "multi_thread")]
+async fn test_todoist_tree_properties() {
+    for seed in 0..5 {
+        run(&mut todoist_store(), seed);
+    }
+}
+
+#[test]
+fn test_move_below_descendant() {
+    let mut store = JsonDataStore::default();
+    let parent = store.add_task(Task::from_string("parent"), None).unwrap();
+    let child = store
+        .add_task(Task::from_string("child"), Some(&parent))
+        .unwrap();
+    let nested = store
+        .add_task(Task::from_string("nested"), Some(&child))
+        .unwrap();
+
+    assert_eq!(
+        store.move_task(&parent, Some(nested.clone()), 0, None),
+        Err(StoreError::CycleDetected(parent.clone()))
+    );
+    assert_eq!(
+        store.move_task(&child, Some(nested.clone()), 0, None),
+        Err(StoreError::CycleDetected(child.clone()))
+    );
+    check_tree(&store, "after the failed moves");
+
+    // The nested task can still be moved up
+    store
+        .move_task(&nested, Some(parent.clone()), 0, None)
+        .unwrap();
+    assert_eq!(store.subtasks(&parent).unwrap(), &vec![nested, child]);
+}
+
+#[test]
+fn test_order_past_end() {
+    let mut store = JsonDataStore::default();
+    let first = store.add_task(Task::from_string("first"), None).unwrap();
+    let second = store.add_task(Task::from_string("second"), None).unwrap();
+
+    store.move_task(&first, None, 100, None).unwrap();
+    assert_eq!(store.root_tasks(), &vec![second.clone(), first.clone()]);
+    store
+        .move_task(&first, Some(second.clone()), 100, None)
+        .unwrap();
+    assert_eq!(store.subtasks(&second).unwrap(), &vec![first]);
+}
+
+#[test]
+fn test_delete_removes_subtasks() {
+    let mut store = JsonDataStore::default();
+    let parent = store.add_task(Task::from_string("parent"), None).unwrap();
+    let child = store
+        .add_task(Task::from_string("child"), Some(&parent))
+        .unwrap();
+    store
+        .add_task(Task::from_string("nested"), Some(&child))
+        .unwrap();
+    let other = store.add_task(Task::from_string("other"), None).unwrap();
+
+    store.delete_task(&parent).unwrap();
+    assert_eq!(sorted(store.tasks.keys().cloned()), vec![other]);
+    assert!(store.subtasks.is_empty());
+    check_tree(&store, "after deleting");
+}
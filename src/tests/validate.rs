@@ -0,0 +1,258 @@
+use crate::{
+    cli::{self, Command},
+    data::json_data_store::JsonDataStore,
+    storage::json::validate::{validate, Location, Problem},
+    task::{CompletedTask, Task},
+};
+
+fn store(root: &[&str], subtasks: &[(&str, &[&str])], tasks: &[&str]) -> JsonDataStore {
+    let ids = |ids: &[&str]| ids.iter().map(|f| f.to_string()).collect::<Vec<_>>();
+    JsonDataStore {
+        tasks: tasks
+            .iter()
+            .map(|id| (id.to_string(), Task::from_string(*id)))
+            .collect(),
+        root: ids(root),
+        subtasks: subtasks
+            .iter()
+            .map(|(parent, subtasks)| (parent.to_string(), ids(subtasks)))
+            .collect(),
+        ..Default::default()
+    }
+}
+
+fn repair_all(store: &mut JsonDataStore) {
+    for problem in validate(store) {
+        problem.repair(store);
+    }
+}
+
+#[test]
+fn test_valid() {
+    let store = store(&["1", "2"], &[("1", &["3"])], &["1", "2", "3"]);
+    assert_eq!(validate(&store), Vec::new());
+}
+
+#[test]
+fn test_dangling() {
+    let mut store = store(&["1", "2"], &[("1", &["3", "4"])], &["1", "3"]);
+    store.completed_root.push("5".to_string());
+    store.completed_tasks.insert(
+        "6".to_string(),
+        CompletedTask::from_string("6".to_string(), Default::default()),
+    );
+    store.completed_root.push("6".to_string());
+
+    assert_eq!(
+        validate(&store),
+        vec![
+            Problem::Dangling {
+                id: "2".to_string(),
+                location: Location::Root
+            },
+            Problem::Dangling {
+                id: "4".to_string(),
+                location: Location::Subtasks("1".to_string())
+            },
+            Problem::Dangling {
+                id: "5".to_string(),
+                location: Location::CompletedRoot
+            },
+        ]
+    );
+
+    repair_all(&mut store);
+    assert_eq!(validate(&store), Vec::new());
+    assert_eq!(store.root, vec!["1"]);
+    assert_eq!(store.subtasks["1"], vec!["3"]);
+    assert_eq!(store.completed_root, vec!["6"]);
+}
+
+#[test]
+fn test_duplicate_parent() {
+    let mut store = store(
+        &["1", "2", "3"],
+        &[("1", &["3", "4", "4"]), ("2", &["4"])],
+        &["1", "2", "3", "4"],
+    );
+
+    assert_eq!(
+        validate(&store),
+        vec![
+            Problem::DuplicateParent {
+                id: "3".to_string(),
+                locations: vec![Location::Root, Location::Subtasks("1".to_string())]
+            },
+            Problem::DuplicateParent {
+                id: "4".to_string(),
+                locations: vec![
+                    Location::Subtasks("1".to_string()),
+                    Location::Subtasks("1".to_string()),
+                    Location::Subtasks("2".to_string())
+                ]
+            },
+        ]
+    );
+
+    repair_all(&mut store);
+    assert_eq!(validate(&store), Vec::new());
+    assert_eq!(store.root, vec!["1", "2", "3"]);
+    assert_eq!(store.subtasks["1"], vec!["4"]);
+    assert!(store.subtasks["2"].is_empty());
+}
+
+#[test]
+fn test_cycle() {
+    let mut store = store(
+        &["1"],
+        &[("2", &["3"]), ("3", &["4"]), ("4", &["2"]), ("5", &["5"])],
+        &["1", "2", "3", "4", "5"],
+    );
+
+    let problems = validate(&store);
+    assert_eq!(
+        problems,
+        vec![
+            Problem::Cycle {
+                ids: vec!["2".to_string(), "3".to_string(), "4".to_string()]
+            },
+            Problem::Cycle {
+                ids: vec!["5".to_string()]
+            },
+        ]
+    );
+    assert_eq!(
+        problems[0].to_string(),
+        "2 -> 3 -> 4 -> 2 are subtasks of each other"
+    );
+    assert_eq!(problems[0].repair_description(), "Move 2 to the root tasks");
+
+    repair_all(&mut store);
+    assert_eq!(validate(&store), Vec::new());
+    assert_eq!(store.root, vec!["1", "2", "5"]);
+    assert!(store.subtasks["4"].is_empty());
+}
+
+#[test]
+fn test_orphans() {
+    // "5" was deleted without removing its subtasks, "4" is not listed
+    let mut store = store(
+        &["1"],
+        &[("5", &["2", "3"]), ("1", &["3"])],
+        &["1", "2", "3", "4"],
+    );
+
+    assert_eq!(
+        validate(&store),
+        vec![
+            Problem::OrphanedSubtasks {
+                parent: "5".to_string(),
+                subtasks: vec!["2".to_string(), "3".to_string()]
+            },
+            Problem::OrphanedTask {
+                id: "4".to_string()
+            },
+        ]
+    );
+
+    repair_all(&mut store);
+    assert_eq!(validate(&store), Vec::new());
+    assert!(!store.subtasks.contains_key("5"));
+    // "3" is still a subtask of "1"
+    assert_eq!(store.root, vec!["1", "2", "4"]);
+    assert_eq!(store.subtasks["1"], vec!["3"]);
+}
+
+#[test]
+fn test_doctor() {
+    assert_eq!(
+        cli::parse_args(["doctor".to_string(), "--dry-run".to_string()]).unwrap(),
+        Some(Command::Doctor { dry_run: true })
+    );
+    assert_eq!(
+        cli::parse_args(["doctor".to_string()]).unwrap(),
+        Some(Command::Doctor { dry_run: false })
+    );
+    assert!(cli::parse_args(["doctor".to_string(), "--fix".to_string()]).is_err());
+
+    let mut store = store(&["1", "1", "2"], &[], &["1"]);
+    assert_eq!(
+        cli::doctor(&mut store, true),
+        "2 is in the root tasks but does not exist
+  Remove 2 from the root tasks
+1 is in the root tasks and the root tasks
+  Keep 1 only in the root tasks
+
+Found 2 problems, run without --dry-run to repair them.
+"
+    );
+    assert_eq!(store.root, vec!["1", "1", "2"]);
+
+    assert!(cli::doctor(&mut store, false).ends_with("Repaired 2 problems.\n"));
+    assert_eq!(store.root, vec!["1"]);
+    assert_eq!(cli::doctor(&mut store, false), "No problems were found.\n");
+}
+
+fn completed(store: &mut JsonDataStore, id: &str) {
+    store.completed_tasks.insert(
+        id.to_string(),
+        CompletedTask::from_string(id.to_string(), Default::default()),
+    );
+}
+
+#[test]
+fn test_completed_subtasks() {
+    let mut store = store(&[], &[], &[]);
+    for id in ["1", "2", "4"] {
+        completed(&mut store, id);
+    }
+    store.completed_root = vec!["1".to_string()];
+    store.completed_subtasks = [
+        ("1".to_string(), vec!["2".to_string(), "3".to_string()]),
+        ("5".to_string(), vec!["4".to_string()]),
+    ]
+    .into();
+
+    assert_eq!(
+        validate(&store),
+        vec![
+            Problem::Dangling {
+                id: "3".to_string(),
+                location: Location::CompletedSubtasks("1".to_string())
+            },
+            Problem::OrphanedCompletedSubtasks {
+                parent: "5".to_string(),
+                subtasks: vec!["4".to_string()]
+            },
+        ]
+    );
+
+    repair_all(&mut store);
+    assert_eq!(validate(&store), Vec::new());
+    assert_eq!(store.completed_root, vec!["1", "4"]);
+    assert_eq!(store.completed_subtasks["1"], vec!["2"]);
+    assert!(!store.completed_subtasks.contains_key("5"));
+}
+
+#[test]
+fn test_open_and_completed() {
+    let mut store = store(&["1"], &[], &["1"]);
+    for id in ["1", "2"] {
+        completed(&mut store, id);
+    }
+    store.completed_root = vec!["1".to_string()];
+    store.completed_subtasks = [("1".to_string(), vec!["2".to_string()])].into();
+
+    assert_eq!(
+        validate(&store),
+        vec![Problem::OpenAndCompleted {
+            id: "1".to_string()
+        }]
+    );
+
+    repair_all(&mut store);
+    assert_eq!(validate(&store), Vec::new());
+    assert_eq!(store.root, vec!["1"]);
+    assert!(!store.completed_tasks.contains_key("1"));
+    assert_eq!(store.completed_root, vec!["2"]);
+}
@@ -0,0 +1,186 @@
+use std::collections::HashMap;
+
+use chrono::Local;
+use crossterm::event::KeyCode;
+use tui::{backend::TestBackend, layout::Rect, Terminal};
+
+use crate::{
+    component::task_list::TaskList,
+    data::{
+        data_store::{DataTaskStore, TaskID},
+        json_data_store::JsonDataStore,
+        todoist::{
+            todoist_main::{handle_sync, TaskSync},
+            todoist_response::TodoistSync,
+        },
+        visible_rows::VisibleRows,
+    },
+    framework::component::{Component, Drawer},
+    task::Task,
+    utils::test::{input_char, input_code, setup, task_tree, todoist_store},
+};
+
+/// The ids are parent, child, nested and second.
+fn nested_store() -> (JsonDataStore, [TaskID; 4]) {
+    task_tree([(0, "parent"), (1, "child"), (2, "nested"), (0, "second")])
+}
+
+fn rows<T: DataTaskStore>(store: &T) -> Vec<(String, usize)> {
+    store
+        .visible_rows()
+        .iter()
+        .map(|(id, depth)| (store.task(id).unwrap().title.trim().to_string(), depth))
+        .collect()
+}
+
+fn row(title: &str, depth: usize) -> (String, usize) {
+    (title.to_string(), depth)
+}
+
+#[test]
+fn test_rows() {
+    let (mut store, [parent, child, nested, second]) = nested_store();
+    let visible_rows = store.visible_rows();
+    assert_eq!(visible_rows.len(), 4);
+    assert_eq!(visible_rows.get(2), Some((nested.as_str(), 2)));
+    assert_eq!(visible_rows.get(4), None);
+    assert_eq!(visible_rows.row_of(&second), Some(3));
+
+    // The subtasks of a closed task are not shown
+    store
+        .modify_task(&child, |task| task.opened = false)
+        .unwrap();
+    assert_eq!(
+        rows(&store),
+        vec![row("parent", 0), row("child", 1), row("second", 0)]
+    );
+    assert_eq!(store.visible_rows().row_of(&nested), None);
+    assert_eq!(store.visible_rows().row_of(&parent), Some(0));
+}
+
+#[test]
+fn test_rows_kept_until_changed() {
+    let (mut store, [parent, child, nested, second]) = nested_store();
+    store.visible_rows();
+    store
+        .modify_task(&nested, |task| task.title = "renamed".to_string())
+        .unwrap();
+    assert!(store.visible_rows.get().is_some());
+
+    let now = Local::now().naive_local();
+    let changes: [&dyn Fn(&mut JsonDataStore); 5] = [
+        &|store| store.move_task(&second, None, 0, None).unwrap(),
+        &|store| {
+            store
+                .move_task(&nested, Some(parent.clone()), 0, None)
+                .unwrap()
+        },
+        &|store| store.complete_task(&child, now).unwrap(),
+        &|store| store.restore(&child).unwrap(),
+        &|store| {
+            store.delete_task(&second).unwrap();
+        },
+    ];
+    for change in changes {
+        store.visible_rows();
+        change(&mut store);
+        assert!(store.visible_rows.get().is_none());
+        assert_eq!(
+            store.visible_rows().iter().collect::<Vec<_>>(),
+            VisibleRows::new(&store).iter().collect::<Vec<_>>()
+        );
+    }
+    assert_eq!(
+        rows(&store),
+        vec![row("parent", 0), row("renamed", 1), row("child", 1)]
+    );
+}
+
+#[test]
+fn test_navigation() {
+    let (store, _) = nested_store();
+    let mut main_app = setup(store);
+
+    input_char('G', &mut main_app);
+    assert_eq!(main_app.app.task_list.selected_index, 3);
+
+    // Closing the parent hides its subtasks from the cursor
+    input_char('g', &mut main_app);
+    input_code(KeyCode::Enter, &mut main_app);
+    input_char('G', &mut main_app);
+    assert_eq!(main_app.app.task_list.selected_index, 1);
+    assert_eq!(main_app.app.task_store.find_tasks_draw_size(), 2);
+}
+
+#[test]
+fn test_todoist_sync() {
+    let mut store = todoist_store();
+    let sync = |store: &mut _, items: &str| {
+        let sync: TodoistSync =
+            serde_json::from_str(&format!(r#"{{"sync_token": "token", "items": {items}}}"#))
+                .unwrap();
+        handle_sync(store, TaskSync::Sync(sync, HashMap::new()));
+    };
+    let item = |id: &str, content: &str, parent_id: &str, child_order: usize| {
+        format!(
+            r#"{{"id": "{id}", "content": "{content}", "parent_id": {parent_id},
+                "child_order": {child_order}, "description": "", "is_collapsed": false,
+                "priority": 1, "due": null}}"#
+        )
+    };
+
+    sync(&mut store, &format!("[{}]", item("1", "first", "null", 0)));
+    assert_eq!(rows(&store), vec![row("first", 0)]);
+
+    sync(
+        &mut store,
+        &format!(
+            "[{}, {}]",
+            item("1", "first", "null", 0),
+            item("2", "child", "\"1\"", 0)
+        ),
+    );
+    assert_eq!(rows(&store), vec![row("first", 0), row("child", 1)]);
+}
+
+#[test]
+fn test_draw_scrolled() {
+    let mut store = JsonDataStore::default();
+    for i in 0..10 {
+        store
+            .add_task(Task::from_string(format!("task {i}")), None)
+            .unwrap();
+    }
+    let mut main_app = setup(store);
+    for _ in 0..7 {
+        input_char('j', &mut main_app);
+    }
+
+    let mut task_list = TaskList::new();
+    task_list.update_layout(Rect::new(0, 0, 30, 6));
+    let mut terminal = Terminal::new(TestBackend::new(30, 6)).unwrap();
+    terminal
+        .draw(|f| task_list.draw(&main_app.app, &mut Drawer::new(f)))
+        .unwrap();
+
+    // Four rows fit, so the selected task is at the bottom
+    let buffer = terminal.backend().buffer();
+    let lines = (1..5)
+        .map(|y| {
+            (1..29)
+                .map(|x| buffer[(x, y)].symbol())
+                .collect::<String>()
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(
+        lines,
+        vec![
+            "[ ]     task 4",
+            "[ ]     task 5",
+            "[ ]     task 6",
+            "[ ]     task 7"
+        ]
+    );
+}
@@ -1,67 +1,108 @@
-// use crate::{task::Task, tests::assert_screen, utils::test::setup};
-// use std::collections::HashMap;
-// use std::io;
-// use tui::style::{Color, Modifier, Style};
+use tui::style::Color;
 
-// #[test]
-// fn test_main() -> io::Result<()> {
-//     use crate::app::TaskStore;
+use crate::{
+    data::{data_store::DataTaskStore, json_data_store::JsonDataStore},
+    framework::screen_manager::ScreenManager,
+    task::{Priority, Tag, Task},
+    tests::{assert_screen, diff_lines},
+    utils::test::{at, date, input_char, setup},
+};
 
-//     let (mut app, mut stack_layout) = setup(TaskStore {
-//         tasks: vec![Task::from_string(String::from("meme"))],
-//         completed_tasks: vec![],
-//         tags: HashMap::new(),
-//         auto_sort: false,
-//     });
+/// The sizes each screen is drawn at, the narrow ones stack the task list,
+/// completed list and viewer as they are below `MINIMUM_SCREEN`.
+const SIZES: [(u16, u16); 4] = [(120, 30), (100, 30), (99, 30), (60, 20)];
 
-//     let format_styles = vec![
-//         Style::default().fg(Color::Green).bg(Color::Reset),
-//         Style::default().fg(Color::Reset).bg(Color::Reset),
-//         Style::default().fg(Color::White).bg(Color::Reset),
-//         Style::default()
-//             .fg(Color::LightBlue)
-//             .bg(Color::Reset)
-//             .add_modifier(Modifier::BOLD),
-//         Style::default()
-//             .fg(Color::White)
-//             .bg(Color::Reset)
-//             .add_modifier(Modifier::BOLD),
-//     ];
+/// A task added at a fixed time, so the viewer draws the same each run.
+fn task(title: &str) -> Task {
+    Task {
+        created_at: Some(at(date(2020, 3, 1), 8)),
+        ..Task::from_string(title)
+    }
+}
 
-//     let str = "
-// {0:┌Current List────────────────────────────────────┐}{1:┌Task information────────────────────────────────┐}}
-// {0:│}{3:[ ] }{4:    }{3:meme}{1:                                    }{0:│}{1:│Title     meme                                  │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│Priority  }{2:None}{1:                                  │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│Tags      None                                  │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:│}{1:                                                }{0:│}{1:│                                                │}}
-// {0:└────────────────────────────────────────────────┘}{1:│                                                │}}
-// {2:┌Completed tasks─────────────────────────────────┐}{1:│                                                │}}
-// {2:│                                                │}{1:│                                                │}}
-// {2:│                                                │}{1:│                                                │}}
-// {2:│                                                │}{1:│                                                │}}
-// {2:│                                                │}{1:│                                                │}}
-// {2:│                                                │}{1:│                                                │}}
-// {2:│                                                │}{1:│                                                │}}
-// {2:│                                                │}{1:│                                                │}}
-// {2:└────────────────────────────────────────────────┘}{1:└────────────────────────────────────────────────┘}}
-// {2:Press x for help. Press q to exit.}{1:                                                                  }}"
-//         .trim();
+/// A few nested tasks with priorities and tags, along with completed tasks.
+/// The times are fixed so the viewer and completed list draw the same each
+/// run.
+fn screen_manager() -> ScreenManager {
+    let mut store = JsonDataStore::default();
+    store.set_tag(
+        "0",
+        Tag {
+            name: "work".to_string(),
+            colour: Color::Blue,
+        },
+    );
+    let report = store
+        .add_task(
+            Task {
+                priority: Priority::High,
+                tags: vec!["0".to_string()],
+                ..task("Write the report")
+            },
+            None,
+        )
+        .unwrap();
+    let numbers = store
+        .add_task(task("Collect the numbers"), Some(&report))
+        .unwrap();
+    let charts = store
+        .add_task(
+            Task {
+                priority: Priority::Low,
+                ..task("Draw the charts")
+            },
+            Some(&report),
+        )
+        .unwrap();
+    let plants = store.add_task(task("Water the plants"), None).unwrap();
+    for id in [report, numbers, charts, plants] {
+        store
+            .modify_task(&id, |task| task.updated_at = Some(at(date(2020, 3, 1), 10)))
+            .unwrap();
+    }
+    let done = store.add_task(task("Book the flights"), None).unwrap();
+    store.complete_task(&done, at(date(2020, 3, 2), 9)).unwrap();
+    setup(store)
+}
 
-//     assert_screen(&mut app, &mut stack_layout, format_styles, str);
+#[test]
+fn test_main_screen() {
+    for (width, height) in SIZES {
+        assert_screen(
+            &mut screen_manager(),
+            &format!("main_screen_{}x{}", width, height),
+            width,
+            height,
+        );
+    }
+}
 
-//     Ok(())
-// }
+#[test]
+fn test_help_overlay() {
+    for (width, height) in [SIZES[0], SIZES[3]] {
+        let mut screen_manager = screen_manager();
+        input_char('x', &mut screen_manager);
+        assert_screen(
+            &mut screen_manager,
+            &format!("help_{}x{}", width, height),
+            width,
+            height,
+        );
+    }
+}
+
+#[test]
+fn test_completed_list_selected() {
+    let mut screen_manager = screen_manager();
+    input_char('2', &mut screen_manager);
+    input_char('j', &mut screen_manager);
+    assert_screen(&mut screen_manager, "completed_list_100x30", 100, 30);
+}
+
+#[test]
+fn test_diff_lines() {
+    assert_eq!(
+        diff_lines("same\nold\nshort", "same\nnew"),
+        "  same\n- old\n+ new\n- short"
+    );
+}
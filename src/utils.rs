@@ -180,6 +180,65 @@ pub fn handle_mouse_movement(
     PostEvent::noop(false)
 }
 
+/// Runs the command through the shell of the system.
+#[cfg(unix)]
+pub fn shell(command: &str) -> std::process::Command {
+    let mut shell = std::process::Command::new("sh");
+    shell.arg("-c").arg(command);
+    shell
+}
+
+/// Runs the command through the shell of the system.
+#[cfg(not(unix))]
+pub fn shell(command: &str) -> std::process::Command {
+    let mut shell = std::process::Command::new("cmd");
+    shell.arg("/C").arg(command);
+    shell
+}
+
+/// Formats a duration as hours and minutes (eg: `1h 05m`).
+pub fn format_duration(duration: chrono::Duration) -> String {
+    let minutes = duration.num_minutes();
+    if minutes < 60 {
+        format!("{}m", minutes)
+    } else {
+        format!("{}h {:02}m", minutes / 60, minutes % 60)
+    }
+}
+
+/// Parses a duration made of hours and minutes (eg: `1h30m`, `2h` or `45m`).
+/// A number without a unit is in minutes.
+pub fn parse_duration(duration: &str) -> Result<chrono::Duration, AppError> {
+    let invalid = || AppError::InvalidDuration(duration.to_string());
+    let duration_str = duration.trim().to_lowercase();
+    if duration_str.is_empty() {
+        return Err(invalid());
+    }
+    if let Ok(minutes) = duration_str.parse::<i64>() {
+        return Ok(chrono::Duration::minutes(minutes));
+    }
+
+    let mut total = chrono::Duration::zero();
+    let mut number = String::new();
+    for chr in duration_str.chars() {
+        if chr.is_ascii_digit() {
+            number.push(chr);
+            continue;
+        }
+        let amount = number.parse::<i64>().map_err(|_| invalid())?;
+        number.clear();
+        total += match chr {
+            'h' => chrono::Duration::hours(amount),
+            'm' => chrono::Duration::minutes(amount),
+            _ => return Err(invalid()),
+        };
+    }
+    if !number.is_empty() {
+        return Err(invalid());
+    }
+    Ok(total)
+}
+
 pub fn str_to_colour(colour: &str) -> Result<Color, AppError> {
     if colour.starts_with('#') {
         let red = u8::from_str_radix(&colour[1..3], 16)?;
@@ -202,78 +261,68 @@ pub fn str_to_colour(colour: &str) -> Result<Color, AppError> {
 }
 
 pub mod task_position {
-    use crate::data::data_store::{DataTaskStore, TaskID, TaskIDRef};
-
-    fn find_task_id<T: DataTaskStore>(
-        store: &T,
-        pos: &mut usize,
-        task_id: TaskIDRef,
-    ) -> Option<TaskID> {
-        if *pos == 0 {
-            return Some(task_id.to_string());
-        }
-        *pos -= 1;
-
-        let task = store.task(task_id)?;
-        if !task.opened {
-            return None;
-        }
-
-        store
-            .subtasks(task_id)?
-            .iter()
-            .find_map(|subtask_id| find_task_id(store, pos, subtask_id))
-    }
+    use crate::{
+        data::data_store::{DataTaskStore, TaskID, TaskIDRef},
+        task::{CompletedTask, Task},
+    };
 
-    pub fn cursor_to_task<T: DataTaskStore>(store: &T, mut pos: usize) -> Option<TaskID> {
+    /// The id of the task drawn at this row of the task list.
+    pub fn cursor_to_task<T: DataTaskStore>(store: &T, pos: usize) -> Option<TaskID> {
         store
-            .root_tasks()
-            .iter()
-            .find_map(|root_task_id| find_task_id(store, &mut pos, root_task_id))
+            .visible_rows()
+            .get(pos)
+            .map(|(task_id, _)| task_id.to_string())
     }
 
-    pub fn cursor_to_completed_task<T: DataTaskStore>(store: &T, mut pos: usize) -> Option<TaskID> {
-        store
-            .completed_root_tasks()
-            .iter()
-            .find_map(|root_task_id| find_task_id(store, &mut pos, root_task_id))
+    /// The row of the task list this task is drawn at.
+    pub fn task_to_cursor<T: DataTaskStore>(store: &T, id: TaskIDRef) -> Option<usize> {
+        store.visible_rows().row_of(id)
     }
 
-    fn find_cursor_position<T: DataTaskStore>(
-        store: &T,
-        current_index: &mut usize,
-        to_find: TaskIDRef,
-        curr: TaskIDRef,
-    ) -> Option<()> {
-        if to_find == curr {
-            return Some(());
-        }
-        *current_index += 1;
-        let t = store.task(curr)?;
-        if !t.opened {
-            return None;
-        }
-        if let Some(subtasks) = store.subtasks(curr) {
-            for task in subtasks {
-                if task == to_find {
-                    return Some(());
-                }
-                if let Some(()) = find_cursor_position(store, current_index, to_find, task) {
-                    return Some(());
-                }
+    /// The open tasks, parents before their subtasks.
+    pub fn open_tasks<T: DataTaskStore>(store: &T) -> Vec<(TaskID, &Task)> {
+        let mut tasks = Vec::new();
+        let mut to_visit = store.root_tasks().iter().rev().cloned().collect::<Vec<_>>();
+        while let Some(task_id) = to_visit.pop() {
+            if let Some(subtasks) = store.subtasks(&task_id) {
+                to_visit.extend(subtasks.iter().rev().cloned());
+            }
+            if let Some(task) = store.task(&task_id) {
+                tasks.push((task_id, task));
             }
         }
-        None
+        tasks
     }
 
-    pub fn task_to_cursor<T: DataTaskStore>(store: &T, id: TaskIDRef) -> Option<usize> {
-        let mut current_index = 0;
-        for curr in store.root_tasks() {
-            if let Some(()) = find_cursor_position(store, &mut current_index, id, curr) {
-                return Some(current_index);
+    /// The completed tasks, parents before the subtasks completed with them.
+    pub fn completed_tasks<T: DataTaskStore>(store: &T) -> Vec<(TaskID, &CompletedTask)> {
+        let mut tasks = Vec::new();
+        let mut to_visit = store
+            .completed_root_tasks()
+            .iter()
+            .rev()
+            .cloned()
+            .collect::<Vec<_>>();
+        while let Some(task_id) = to_visit.pop() {
+            if let Some(subtasks) = store.completed_subtasks(&task_id) {
+                to_visit.extend(subtasks.iter().rev().cloned());
+            }
+            if let Some(completed_task) = store.completed_task(&task_id) {
+                tasks.push((task_id, completed_task));
             }
         }
-        None
+        tasks
+    }
+
+    /// The open tasks followed by the completed tasks.
+    pub fn all_tasks<T: DataTaskStore>(store: &T) -> Vec<(TaskID, &Task)> {
+        let mut tasks = open_tasks(store);
+        tasks.extend(
+            completed_tasks(store)
+                .into_iter()
+                .map(|(task_id, completed_task)| (task_id, &completed_task.task)),
+        );
+        tasks
     }
 }
 
@@ -412,16 +461,32 @@ pub mod wrap {
 
 #[cfg(test)]
 pub mod test {
+    use chrono::{NaiveDate, NaiveDateTime};
     use crossterm::event::{KeyCode, KeyModifiers};
 
-    use crate::data::data_store::{DataTaskStore, DataTaskStoreKind};
+    use std::collections::HashMap;
+    use std::sync::{Arc, Mutex};
+    use tokio::sync::mpsc::Receiver;
+
+    use crate::data::data_store::{DataTaskStore, DataTaskStoreKind, TaskID};
     use crate::data::json_data_store::JsonDataStore;
+    use crate::data::todoist::todoist_command::TodoistCommand;
+    use crate::data::todoist::todoist_data_store::TodoistDataStore;
     use crate::framework::screen_manager::ScreenManager;
     use crate::task::Task;
     use crate::{app::App, input};
 
     use super::task_position::cursor_to_task;
 
+    pub fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    /// The start of the hour on the day.
+    pub fn at(day: NaiveDate, hour: u32) -> NaiveDateTime {
+        day.and_hms_opt(hour, 0, 0).unwrap()
+    }
+
     pub fn input_char(character: char, screen_manager: &mut ScreenManager) {
         let result = input::key_event(
             screen_manager,
@@ -432,6 +497,16 @@ pub mod test {
         }
     }
 
+    pub fn input_ctrl(character: char, screen_manager: &mut ScreenManager) {
+        let result = input::key_event(
+            screen_manager,
+            crossterm::event::KeyEvent::new(KeyCode::Char(character), KeyModifiers::CONTROL),
+        );
+        if let Ok(post_event) = result {
+            screen_manager.handle_post_event(post_event);
+        }
+    }
+
     pub fn input_code(key: KeyCode, screen_manager: &mut ScreenManager) {
         let result = input::key_event(
             screen_manager,
@@ -442,6 +517,10 @@ pub mod test {
         }
     }
 
+    pub fn input_str(text: &str, screen_manager: &mut ScreenManager) {
+        text.chars().for_each(|chr| input_char(chr, screen_manager));
+    }
+
     pub fn setup(task_store: JsonDataStore) -> ScreenManager {
         ScreenManager {
             overlays: vec![],
@@ -452,6 +531,70 @@ pub mod test {
         }
     }
 
+    /// Adds each task below the last task that is one level less nested,
+    /// returning their ids in the same order.
+    pub fn add_tasks<T: DataTaskStore>(store: &mut T, tasks: &[(usize, &str)]) -> Vec<TaskID> {
+        let mut parents: Vec<TaskID> = Vec::new();
+        let mut ids = Vec::new();
+        for (depth, title) in tasks {
+            parents.truncate(*depth);
+            let id = store
+                .add_task(
+                    Task::from_string(*title),
+                    parents.last().map(|f| f.as_str()),
+                )
+                .unwrap();
+            parents.push(id.clone());
+            ids.push(id);
+        }
+        ids
+    }
+
+    /// A store with the tasks as nested by [`add_tasks`], along with their ids.
+    pub fn task_tree<const N: usize>(tasks: [(usize, &str); N]) -> (JsonDataStore, [TaskID; N]) {
+        let mut store = JsonDataStore::default();
+        let ids = add_tasks(&mut store, &tasks);
+        (store, ids.try_into().unwrap())
+    }
+
+    /// A Todoist data store that is not connected to Todoist, commands that
+    /// are sent to it are dropped.
+    pub fn todoist_store() -> TodoistDataStore {
+        let (command_sender, _) = tokio::sync::mpsc::channel(100);
+        TodoistDataStore {
+            tasks: HashMap::new(),
+            completed_tasks: HashMap::new(),
+            subtasks: HashMap::new(),
+            root: Vec::new(),
+            completed_root: Vec::new(),
+            completed_subtasks: HashMap::new(),
+            tags: HashMap::new(),
+            task_count: 0,
+            parent_index: Default::default(),
+            visible_rows: Default::default(),
+            subscribers: Default::default(),
+            currently_syncing: Arc::new(Mutex::new(false)),
+            command_sender,
+            inbox_project: None,
+            temporary_mappings: HashMap::new(),
+            completed_history: None,
+            loading_completed_history: false,
+            empty_completed_pages: 0,
+            joined_at: None,
+        }
+    }
+
+    /// A Todoist data store whose commands are kept in the receiver instead
+    /// of being sent to Todoist.
+    pub fn todoist_store_with_sink() -> (TodoistDataStore, Receiver<TodoistCommand>) {
+        let (command_sender, commands) = tokio::sync::mpsc::channel(1000);
+        let store = TodoistDataStore {
+            command_sender,
+            ..todoist_store()
+        };
+        (store, commands)
+    }
+
     pub fn get_task_from_pos(task_store: &DataTaskStoreKind, pos: usize) -> &Task {
         task_store
             .task(&cursor_to_task(task_store, pos).unwrap())